@@ -0,0 +1,387 @@
+#![forbid(unsafe_code)]
+
+//! Integrated pest management (IPM) plan bundles.
+//!
+//! A bundle fills each slot of a template (exclusion, sanitation, a monitoring or
+//! trapping component, ...) with one method that passed the registry query. Members
+//! are combined into a single K/E/R, checked against material limits and a bundle
+//! risk ceiling, and the bundles are ranked the same way single methods are.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{ControlFamily, PestContext};
+use crate::query::{MethodRegistry, QueryOptions, RankedMethod};
+use crate::rank::{pareto_tiers, Objectives, Ranking};
+use crate::store::StoreError;
+
+/// One component of a plan.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleSlot {
+    pub name: String,
+    /// Families that can fill the slot.
+    pub families: Vec<ControlFamily>,
+    /// Without a candidate for a required slot no bundle is built; optional slots
+    /// may stay empty.
+    pub required: bool,
+}
+
+/// Template, search width and constraints for bundle building.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BundleConfig {
+    pub slots: Vec<BundleSlot>,
+    /// Best-ranked candidates tried per slot.
+    pub candidates_per_slot: usize,
+    /// Most members using disposable electronics.
+    pub max_disposable_electronics: usize,
+    /// Most members generating persistent plastic.
+    pub max_persistent_plastic: usize,
+    /// Ceiling on the combined R of a bundle, under the query's `risk_bound`.
+    pub max_r: f64,
+    /// Bundles returned.
+    pub max_bundles: usize,
+}
+
+impl Default for BundleConfig {
+    fn default() -> Self {
+        Self {
+            slots: vec![
+                BundleSlot {
+                    name: "exclusion".to_string(),
+                    families: vec![ControlFamily::Exclusion],
+                    required: true,
+                },
+                BundleSlot {
+                    name: "sanitation".to_string(),
+                    families: vec![ControlFamily::Sanitation, ControlFamily::HabitatChange],
+                    required: true,
+                },
+                BundleSlot {
+                    name: "monitoring_or_trapping".to_string(),
+                    families: vec![
+                        ControlFamily::MonitoringOnly,
+                        ControlFamily::LiveCapture,
+                        ControlFamily::MechanicalKill,
+                    ],
+                    required: true,
+                },
+                BundleSlot {
+                    name: "predator_support".to_string(),
+                    families: vec![ControlFamily::PredatorSupport],
+                    required: false,
+                },
+            ],
+            candidates_per_slot: 3,
+            max_disposable_electronics: 1,
+            max_persistent_plastic: 1,
+            max_r: 0.3,
+            max_bundles: 10,
+        }
+    }
+}
+
+/// A method filling one slot.
+#[derive(Clone, Debug, Serialize)]
+pub struct BundleMember {
+    pub slot: String,
+    pub method: RankedMethod,
+}
+
+/// A complete plan with its combined scores.
+#[derive(Clone, Debug, Serialize)]
+pub struct PlanBundle {
+    /// In slot order; empty optional slots are left out.
+    pub members: Vec<BundleMember>,
+    /// Lowest member K: a plan is only as well evidenced as its weakest part.
+    pub k: f64,
+    /// Mean member E.
+    pub e: f64,
+    /// 1 − Π(1 − rᵢ) over member R under the query's `risk_bound`: the chance
+    /// that at least one component causes harm, treating them as independent.
+    pub r: f64,
+    /// Pareto tier among every bundle that met the constraints; 0 is the frontier.
+    pub tier: usize,
+    /// Weighted scalarization, under `Ranking::Weighted` only.
+    pub scalar: Option<f64>,
+}
+
+impl PlanBundle {
+    fn objectives(&self) -> Objectives {
+        Objectives::new(self.k, self.e, self.r)
+    }
+}
+
+/// Build and rank plan bundles for a pest context.
+pub fn plan_bundles(
+    registry: &MethodRegistry,
+    ctx: &PestContext,
+    opts: &QueryOptions,
+    cfg: &BundleConfig,
+) -> Result<Vec<PlanBundle>, StoreError> {
+    let ranked = registry.rank_safest_methods_in_context(ctx, opts)?;
+
+    // Candidates per slot, best first; a method fills at most one slot.
+    let mut slot_candidates: Vec<Vec<&RankedMethod>> = Vec::with_capacity(cfg.slots.len());
+    for slot in &cfg.slots {
+        let candidates: Vec<&RankedMethod> = ranked
+            .iter()
+            .filter(|r| slot.families.contains(&r.method.family))
+            .take(cfg.candidates_per_slot)
+            .collect();
+        if candidates.is_empty() && slot.required {
+            return Ok(Vec::new());
+        }
+        slot_candidates.push(candidates);
+    }
+
+    let mut bundles = Vec::new();
+    let mut chosen: Vec<Option<&RankedMethod>> = Vec::with_capacity(cfg.slots.len());
+    combine(&slot_candidates, opts, cfg, &mut chosen, &mut bundles);
+
+    rank_bundles(&mut bundles, opts.ranking);
+    bundles.truncate(cfg.max_bundles);
+    Ok(bundles)
+}
+
+/// Depth-first over slots; optional slots are also tried empty (`None`).
+fn combine<'a>(
+    slot_candidates: &[Vec<&'a RankedMethod>],
+    opts: &QueryOptions,
+    cfg: &BundleConfig,
+    chosen: &mut Vec<Option<&'a RankedMethod>>,
+    out: &mut Vec<PlanBundle>,
+) {
+    let depth = chosen.len();
+    if depth == slot_candidates.len() {
+        if let Some(bundle) = assemble(chosen, opts, cfg) {
+            out.push(bundle);
+        }
+        return;
+    }
+
+    if !cfg.slots[depth].required {
+        chosen.push(None);
+        combine(slot_candidates, opts, cfg, chosen, out);
+        chosen.pop();
+    }
+    for &candidate in &slot_candidates[depth] {
+        let id = &candidate.method.id;
+        if chosen.iter().flatten().any(|c| &c.method.id == id) {
+            continue;
+        }
+        chosen.push(Some(candidate));
+        combine(slot_candidates, opts, cfg, chosen, out);
+        chosen.pop();
+    }
+}
+
+/// Combined scores for one choice of members, or `None` if it breaks a constraint.
+fn assemble(
+    chosen: &[Option<&RankedMethod>],
+    opts: &QueryOptions,
+    cfg: &BundleConfig,
+) -> Option<PlanBundle> {
+    let members: Vec<BundleMember> = cfg
+        .slots
+        .iter()
+        .zip(chosen)
+        .filter_map(|(slot, c)| {
+            c.map(|method| BundleMember {
+                slot: slot.name.clone(),
+                method: method.clone(),
+            })
+        })
+        .collect();
+    if members.is_empty() {
+        return None;
+    }
+
+    let electronics = members
+        .iter()
+        .filter(|m| m.method.method.uses_disposable_electronics)
+        .count();
+    let plastic = members
+        .iter()
+        .filter(|m| m.method.method.generates_persistent_plastic)
+        .count();
+    if electronics > cfg.max_disposable_electronics || plastic > cfg.max_persistent_plastic {
+        return None;
+    }
+
+    let n = members.len() as f64;
+    let k = members
+        .iter()
+        .map(|m| m.method.ker.k)
+        .fold(f64::INFINITY, f64::min);
+    let e = members.iter().map(|m| m.method.ker.e).sum::<f64>() / n;
+    let r = 1.0
+        - members
+            .iter()
+            .map(|m| 1.0 - opts.filtered_r(&m.method.ker))
+            .product::<f64>();
+    if r > cfg.max_r {
+        return None;
+    }
+
+    Some(PlanBundle {
+        members,
+        k,
+        e,
+        r,
+        tier: 0,
+        scalar: None,
+    })
+}
+
+/// Order bundles as `ranking` orders single methods, filling in tier and scalar.
+fn rank_bundles(bundles: &mut [PlanBundle], ranking: Ranking) {
+    let objectives: Vec<Objectives> = bundles.iter().map(PlanBundle::objectives).collect();
+    for (bundle, tier) in bundles.iter_mut().zip(pareto_tiers(&objectives)) {
+        bundle.tier = tier;
+        if let Ranking::Weighted(w) = ranking {
+            bundle.scalar = Some(w.scalarize(&bundle.objectives()));
+        }
+    }
+
+    let lexicographic = |a: &PlanBundle, b: &PlanBundle| {
+        b.k.partial_cmp(&a.k)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.e.partial_cmp(&a.e).unwrap_or(Ordering::Equal))
+    };
+    match ranking {
+        Ranking::Lexicographic => bundles.sort_by(lexicographic),
+        Ranking::Pareto => {
+            bundles.sort_by(|a, b| a.tier.cmp(&b.tier).then_with(|| lexicographic(a, b)))
+        }
+        Ranking::Weighted(_) => bundles.sort_by(|a, b| {
+            b.scalar
+                .partial_cmp(&a.scalar)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| lexicographic(a, b))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ker::{score_method, KerConfig};
+    use crate::model::fixtures::{context, meta, method};
+    use crate::model::{CredibleInterval, LocationType};
+    use crate::query::RiskBound;
+
+    /// A ranked method with the given K, E, and mean and upper R.
+    fn ranked(id: &str, family: ControlFamily, k: f64, e: f64, r: (f64, f64)) -> RankedMethod {
+        let method = method(id, family);
+        let mut ker = score_method(&method, &[], &KerConfig::default());
+        ker.k = k;
+        ker.e = e;
+        ker.r = r.0;
+        ker.r_interval = CredibleInterval {
+            lower: r.0,
+            upper: r.1,
+        };
+        RankedMethod {
+            method,
+            objectives: Objectives::new(k, e, r.0),
+            ker,
+            tier: 0,
+            scalar: None,
+            priorities: Vec::new(),
+            policy_id: String::new(),
+        }
+    }
+
+    fn opts(risk_bound: RiskBound) -> QueryOptions {
+        QueryOptions {
+            risk_bound,
+            ..QueryOptions::new(1.0)
+        }
+    }
+
+    /// Template with just the two required tier-0 slots.
+    fn two_slots() -> BundleConfig {
+        BundleConfig {
+            slots: BundleConfig::default().slots[..2].to_vec(),
+            ..BundleConfig::default()
+        }
+    }
+
+    #[test]
+    fn bundle_k_is_its_weakest_member() {
+        let seal = ranked("seal", ControlFamily::Exclusion, 0.9, 0.8, (0.1, 0.1));
+        let dry = ranked("dry", ControlFamily::Sanitation, 0.6, 0.6, (0.1, 0.1));
+        let bundle = assemble(
+            &[Some(&seal), Some(&dry)],
+            &opts(RiskBound::Mean),
+            &two_slots(),
+        )
+        .unwrap();
+        assert_eq!(bundle.k, 0.6);
+        assert!((bundle.e - 0.7).abs() < 1e-12);
+        assert!((bundle.r - 0.19).abs() < 1e-12);
+        assert_eq!(bundle.members[1].slot, "sanitation");
+    }
+
+    #[test]
+    fn bundle_risk_uses_the_query_bound() {
+        let seal = ranked("seal", ControlFamily::Exclusion, 0.9, 0.8, (0.1, 0.3));
+        let dry = ranked("dry", ControlFamily::Sanitation, 0.8, 0.6, (0.1, 0.3));
+        let chosen = [Some(&seal), Some(&dry)];
+        let cfg = two_slots();
+        assert!(assemble(&chosen, &opts(RiskBound::Mean), &cfg).is_some());
+        // Upper bounds combine to 0.51, over the 0.3 ceiling.
+        assert!(assemble(&chosen, &opts(RiskBound::Upper), &cfg).is_none());
+    }
+
+    #[test]
+    fn bundles_respect_material_limits() {
+        let mut seal = ranked("seal", ControlFamily::Exclusion, 0.9, 0.8, (0.0, 0.0));
+        let mut dry = ranked("dry", ControlFamily::Sanitation, 0.8, 0.6, (0.0, 0.0));
+        seal.method.uses_disposable_electronics = true;
+        let cfg = two_slots();
+        let o = opts(RiskBound::Mean);
+        assert!(assemble(&[Some(&seal), Some(&dry)], &o, &cfg).is_some());
+        dry.method.uses_disposable_electronics = true;
+        assert!(assemble(&[Some(&seal), Some(&dry)], &o, &cfg).is_none());
+        assert!(assemble(&[None, None], &o, &cfg).is_none());
+    }
+
+    #[test]
+    fn pareto_tiers_rank_bundles() {
+        let make = |k: f64, e: f64, r: f64| PlanBundle {
+            members: Vec::new(),
+            k,
+            e,
+            r,
+            tier: 0,
+            scalar: None,
+        };
+        let mut bundles = [
+            make(0.7, 0.5, 0.2),
+            make(0.8, 0.5, 0.1),
+            make(0.9, 0.4, 0.3),
+        ];
+        rank_bundles(&mut bundles, Ranking::Pareto);
+        let ks: Vec<(f64, usize)> = bundles.iter().map(|b| (b.k, b.tier)).collect();
+        // The first bundle is dominated by the second.
+        assert_eq!(ks, [(0.9, 0), (0.8, 0), (0.7, 1)]);
+    }
+
+    #[test]
+    fn missing_required_slot_yields_no_bundles() {
+        let mut registry = MethodRegistry::new();
+        registry
+            .add_method(method("seal", ControlFamily::Exclusion), meta(0))
+            .unwrap();
+        let bundles = plan_bundles(
+            &registry,
+            &context(LocationType::Home),
+            &QueryOptions::new(1.0),
+            &BundleConfig::default(),
+        )
+        .unwrap();
+        assert!(bundles.is_empty());
+    }
+}
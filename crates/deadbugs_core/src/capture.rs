@@ -0,0 +1,196 @@
+#![forbid(unsafe_code)]
+
+//! Objective, rate-based effectiveness from `target_count` and `observation_days`.
+//!
+//! Logs from the same `location_cell` form a time series of deployments. A working
+//! method drives the capture rate down, so each follow-up log is classified by its
+//! capture-rate decline against the first log at that site. Comparing that class
+//! with the operator's band gives an agreement share that feeds into K.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{CaptureEffectiveness, EffectivenessBand, OutcomeLog};
+use crate::stats::beta_mean;
+
+/// Decline thresholds and the weight of objective/subjective agreement in K.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// Decline at or above this classifies a follow-up log as `High`.
+    pub high_decline: f64,
+    /// Decline at or above this (and below `high_decline`) classifies it as `Medium`.
+    pub medium_decline: f64,
+    /// Largest relative change agreement can make to K.
+    pub consistency_weight: f64,
+    /// Beta prior (agree, disagree) on the agreement share.
+    pub agreement_prior: (f64, f64),
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            high_decline: 0.5,
+            medium_decline: 0.2,
+            consistency_weight: 0.3,
+            agreement_prior: (1.0, 1.0),
+        }
+    }
+}
+
+impl CaptureConfig {
+    fn band(&self, decline: f64) -> EffectivenessBand {
+        if decline >= self.high_decline {
+            EffectivenessBand::High
+        } else if decline >= self.medium_decline {
+            EffectivenessBand::Medium
+        } else {
+            EffectivenessBand::Low
+        }
+    }
+
+    /// Shift a K value towards 1 on agreement and towards 0 on disagreement.
+    /// Neutral when the agreement posterior sits at one half (e.g. no paired logs).
+    pub fn adjust_k(&self, k: f64, capture: &CaptureEffectiveness) -> f64 {
+        let (a, b) = self.agreement_prior;
+        let agree = capture.agreeing_logs as f64;
+        let disagree = (capture.paired_logs - capture.agreeing_logs) as f64;
+        let d = 2.0 * beta_mean(a + agree, b + disagree) - 1.0;
+        let w = self.consistency_weight;
+        if d >= 0.0 {
+            k + (1.0 - k) * w * d
+        } else {
+            k * (1.0 + w * d)
+        }
+    }
+}
+
+/// Captures per day for one log (every deployment counts for at least one day).
+fn capture_rate(log: &OutcomeLog) -> f64 {
+    f64::from(log.target_count) / f64::from(log.observation_days.max(1))
+}
+
+/// Pooled capture rate, per-site decline, and agreement with the operator's bands.
+pub fn capture_effectiveness(logs: &[&OutcomeLog], cfg: &CaptureConfig) -> CaptureEffectiveness {
+    let mut out = CaptureEffectiveness::default();
+    if logs.is_empty() {
+        return out;
+    }
+
+    let captures: f64 = logs.iter().map(|l| f64::from(l.target_count)).sum();
+    let days: f64 = logs
+        .iter()
+        .map(|l| f64::from(l.observation_days.max(1)))
+        .sum();
+    out.captures_per_day = captures / days;
+
+    let mut by_site: HashMap<&str, Vec<&OutcomeLog>> = HashMap::new();
+    for log in logs {
+        by_site
+            .entry(log.meta.location_cell.as_str())
+            .or_default()
+            .push(log);
+    }
+
+    let mut declines = Vec::new();
+    for series in by_site.values_mut() {
+        if series.len() < 2 {
+            continue;
+        }
+        series.sort_by_key(|l| l.meta.timestamp);
+        let first = capture_rate(series[0]);
+        if first <= 0.0 {
+            continue;
+        }
+
+        for log in &series[1..] {
+            let decline = (1.0 - capture_rate(log) / first).clamp(0.0, 1.0);
+            out.paired_logs += 1;
+            if cfg.band(decline) == log.effectiveness {
+                out.agreeing_logs += 1;
+            }
+        }
+        let last = capture_rate(series[series.len() - 1]);
+        declines.push((1.0 - last / first).clamp(0.0, 1.0));
+    }
+
+    if !declines.is_empty() {
+        out.decline = Some(declines.iter().sum::<f64>() / declines.len() as f64);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures::log;
+
+    /// Log `n` at cell `site` with `captures` over a week, banded `band`.
+    fn at(site: &str, n: u64, captures: u32, band: EffectivenessBand) -> OutcomeLog {
+        let mut l = log("trap.snap", n);
+        l.meta.location_cell = site.to_string();
+        l.target_count = captures;
+        l.observation_days = 7;
+        l.effectiveness = band;
+        l
+    }
+
+    #[test]
+    fn pools_captures_over_observation_days() {
+        let mut short = at("A", 0, 3, EffectivenessBand::High);
+        short.observation_days = 0;
+        let logs = [short, at("B", 1, 7, EffectivenessBand::High)];
+        let refs: Vec<&OutcomeLog> = logs.iter().collect();
+        let out = capture_effectiveness(&refs, &CaptureConfig::default());
+        // A zero-day log counts as one day: 10 captures over 8 days.
+        assert_eq!(out.captures_per_day, 10.0 / 8.0);
+        assert_eq!(out.decline, None);
+        assert_eq!(out.paired_logs, 0);
+    }
+
+    #[test]
+    fn decline_runs_from_first_to_last_log_per_site() {
+        let logs = [
+            // Out of order on purpose: series are sorted by timestamp.
+            at("A", 2, 2, EffectivenessBand::High),
+            at("A", 0, 10, EffectivenessBand::High),
+            at("A", 1, 6, EffectivenessBand::Low),
+            at("B", 0, 4, EffectivenessBand::Low),
+            at("B", 1, 4, EffectivenessBand::Low),
+            at("C", 0, 0, EffectivenessBand::High),
+            at("C", 1, 5, EffectivenessBand::High),
+        ];
+        let refs: Vec<&OutcomeLog> = logs.iter().collect();
+        let out = capture_effectiveness(&refs, &CaptureConfig::default());
+        // A: 0.8, B: 0.0; C has no baseline captures and is skipped.
+        assert!((out.decline.unwrap() - 0.4).abs() < 1e-12);
+        // A1 declined 0.4 (medium, banded low), A2 0.8 (high), B1 0.0 (low).
+        assert_eq!(out.paired_logs, 3);
+        assert_eq!(out.agreeing_logs, 2);
+    }
+
+    #[test]
+    fn agreement_moves_k_and_no_pairs_leave_it() {
+        let cfg = CaptureConfig::default();
+        let none = CaptureEffectiveness::default();
+        assert_eq!(cfg.adjust_k(0.6, &none), 0.6);
+
+        let agree = CaptureEffectiveness {
+            paired_logs: 8,
+            agreeing_logs: 8,
+            ..Default::default()
+        };
+        let disagree = CaptureEffectiveness {
+            paired_logs: 8,
+            agreeing_logs: 0,
+            ..Default::default()
+        };
+        let up = cfg.adjust_k(0.6, &agree);
+        let down = cfg.adjust_k(0.6, &disagree);
+        assert!(up > 0.6 && up <= 0.6 + 0.4 * cfg.consistency_weight);
+        assert!(down < 0.6 && down >= 0.6 * (1.0 - cfg.consistency_weight));
+        assert!(cfg.adjust_k(1.0, &agree) <= 1.0);
+        assert!(cfg.adjust_k(0.0, &disagree) >= 0.0);
+    }
+}
@@ -0,0 +1,439 @@
+#![forbid(unsafe_code)]
+
+//! Opt-in explanation traces for `KerScore`.
+//!
+//! A `KerExplanation` records every contribution behind a score (family base,
+//! material penalties, success blend, weighted corridor terms, query-time uplifts)
+//! and every filter that rejected the method. Render with `Display` for people or
+//! `to_json` for tools.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ker::KerConfig;
+use crate::lifecycle::LifecycleState;
+use crate::model::{ControlFamily, ControlMethod, Corridor, CorridorBand, KerScore, Stratum};
+use crate::policy::Exclusion;
+use crate::query::RiskBound;
+
+/// How E was built up.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EBreakdown {
+    pub family: ControlFamily,
+    pub family_base: f64,
+    /// Applied electronics penalty (0 when the method uses none).
+    pub electronics_penalty: f64,
+    /// Applied plastic penalty (0 when the method generates none).
+    pub plastic_penalty: f64,
+    /// Site-adjusted, recency-weighted success fraction; `None` without logs.
+    pub success_fraction: Option<f64>,
+    /// Multiplier on the family base from the success fraction (1 without logs).
+    pub blend_factor: f64,
+    /// E as returned by the scorer.
+    pub scored: f64,
+    /// Query-time uplift for tier-0 families.
+    pub tier0_uplift: f64,
+    /// Query-time uplift from context priority rules.
+    #[serde(default)]
+    pub context_uplift: f64,
+    /// Ids of the priority rules behind `context_uplift`.
+    #[serde(default)]
+    pub context_rules: Vec<String>,
+    pub final_e: f64,
+}
+
+/// How K was built up.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KBreakdown {
+    /// Weighted share of the modal effectiveness band; `None` without logs.
+    pub modal_share: Option<f64>,
+    /// Posterior mean before the capture-agreement adjustment.
+    pub posterior_k: f64,
+    /// Observed agreement share between capture decline and operator bands.
+    pub capture_agreement: Option<f64>,
+    /// Distinct `bostrom_address` values behind the evidence.
+    pub distinct_operators: usize,
+    /// Distinct `location_cell` values behind the evidence.
+    pub distinct_sites: usize,
+    /// Ceiling on K from source diversity.
+    pub k_cap: f64,
+    pub final_k: f64,
+}
+
+/// One weighted term of R.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CorridorTerm {
+    pub corridor: Corridor,
+    pub value: f64,
+    pub upper: f64,
+    pub weight: f64,
+    /// `weight × value`; the terms sum to R before clamping.
+    pub contribution: f64,
+    /// Share of deployments with an incident; `band` grades this.
+    #[serde(default)]
+    pub incidence: f64,
+    pub band: CorridorBand,
+}
+
+/// Why a query dropped a method.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "filter", rename_all = "snake_case")]
+pub enum Rejection {
+    /// The method's lifecycle state keeps it out of this query.
+    NotOffered { state: LifecycleState },
+    /// An eligibility-policy rule excludes the method here.
+    Ineligible(Exclusion),
+    /// Protected corridors in the hard band.
+    HardViolation { corridors: Vec<Corridor> },
+    /// R (under `bound`) above the caller's ceiling.
+    RiskCeiling {
+        r: f64,
+        bound: RiskBound,
+        max_r: f64,
+    },
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::NotOffered { state } => write!(f, "method is {state}"),
+            Rejection::Ineligible(exclusion) => {
+                write!(f, "{} ({})", exclusion.reason, exclusion.rule)
+            }
+            Rejection::HardViolation { corridors } => {
+                let names: Vec<&str> = corridors.iter().map(|c| c.as_str()).collect();
+                write!(f, "hard band on {}", names.join(", "))
+            }
+            Rejection::RiskCeiling { r, bound, max_r } => {
+                write!(f, "R ({bound:?}) {r:.3} exceeds ceiling {max_r:.3}")
+            }
+        }
+    }
+}
+
+/// Full trace behind one method's score in one query.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KerExplanation {
+    pub method_id: String,
+    pub config_id: String,
+    /// Eligibility policy of the query; empty for traces made outside a query.
+    #[serde(default)]
+    pub policy_id: String,
+    pub stratum: Stratum,
+    /// Logs inside the evidence window.
+    pub n_logs: usize,
+    pub n_effective: f64,
+    pub k: KBreakdown,
+    pub e: EBreakdown,
+    pub corridors: Vec<CorridorTerm>,
+    pub r: f64,
+    pub r_upper: f64,
+    /// Empty when the method passed every filter.
+    pub rejections: Vec<Rejection>,
+}
+
+impl KerExplanation {
+    /// Assemble a trace from a finished score and the scorer's breakdowns.
+    pub fn new(
+        method: &ControlMethod,
+        score: &KerScore,
+        n_logs: usize,
+        k: KBreakdown,
+        e: EBreakdown,
+        cfg: &KerConfig,
+    ) -> Self {
+        let corridors = Corridor::ALL
+            .iter()
+            .map(|&c| {
+                let value = score.coords.get(c);
+                let weight = cfg.weights.weight(c);
+                CorridorTerm {
+                    corridor: c,
+                    value,
+                    upper: score.coords_upper.get(c),
+                    weight,
+                    contribution: weight * value,
+                    incidence: score.incidence.get(c),
+                    band: score.bands.get(c),
+                }
+            })
+            .collect();
+
+        Self {
+            method_id: method.id.clone(),
+            config_id: score.config_id.clone(),
+            policy_id: String::new(),
+            stratum: score.stratum,
+            n_logs,
+            n_effective: score.n_effective,
+            k,
+            e,
+            corridors,
+            r: score.r,
+            r_upper: score.r_interval.upper,
+            rejections: Vec::new(),
+        }
+    }
+
+    pub fn accepted(&self) -> bool {
+        self.rejections.is_empty()
+    }
+
+    /// Record a query-time E uplift; `final_e` is the value the query ranks on.
+    pub fn apply_tier0_uplift(&mut self, uplift: f64, final_e: f64) {
+        self.e.tier0_uplift = uplift;
+        self.e.final_e = final_e;
+    }
+
+    /// Record an uplift from context priority rules.
+    pub fn apply_context_uplift(&mut self, rules: Vec<String>, uplift: f64, final_e: f64) {
+        self.e.context_uplift = uplift;
+        self.e.context_rules = rules;
+        self.e.final_e = final_e;
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl fmt::Display for KerExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = if self.accepted() {
+            "accepted"
+        } else {
+            "rejected"
+        };
+        writeln!(
+            f,
+            "{} [{verdict}] config={} policy={} stratum={:?} logs={} n_eff={:.1}",
+            self.method_id,
+            self.config_id,
+            self.policy_id,
+            self.stratum,
+            self.n_logs,
+            self.n_effective
+        )?;
+
+        let k = &self.k;
+        write!(f, "  K = {:.3}: posterior {:.3}", k.final_k, k.posterior_k)?;
+        if let Some(share) = k.modal_share {
+            write!(f, " (modal share {share:.2})")?;
+        }
+        if let Some(agree) = k.capture_agreement {
+            write!(f, ", capture agreement {agree:.2}")?;
+        }
+        write!(
+            f,
+            ", {} operators / {} sites (cap {:.3})",
+            k.distinct_operators, k.distinct_sites, k.k_cap
+        )?;
+        writeln!(f)?;
+
+        let e = &self.e;
+        write!(
+            f,
+            "  E = {:.3}: {:?} base {:.3}",
+            e.final_e, e.family, e.family_base
+        )?;
+        if let Some(success) = e.success_fraction {
+            write!(f, " × blend {:.3} (success {success:.2})", e.blend_factor)?;
+        }
+        if e.electronics_penalty > 0.0 {
+            write!(f, " − electronics {:.3}", e.electronics_penalty)?;
+        }
+        if e.plastic_penalty > 0.0 {
+            write!(f, " − plastic {:.3}", e.plastic_penalty)?;
+        }
+        if e.tier0_uplift > 0.0 {
+            write!(f, " + tier-0 {:.3}", e.tier0_uplift)?;
+        }
+        if e.context_uplift > 0.0 {
+            write!(
+                f,
+                " + context {:.3} ({})",
+                e.context_uplift,
+                e.context_rules.join(", ")
+            )?;
+        }
+        writeln!(f)?;
+
+        writeln!(f, "  R = {:.3} (upper {:.3})", self.r, self.r_upper)?;
+        for t in &self.corridors {
+            writeln!(
+                f,
+                "    {:<13} {:.3} × {:.2} = {:.3}  [{:?} at {:.2} of deployments, upper {:.3}]",
+                t.corridor.as_str(),
+                t.value,
+                t.weight,
+                t.contribution,
+                t.band,
+                t.incidence,
+                t.upper
+            )?;
+        }
+
+        for rejection in &self.rejections {
+            writeln!(f, "  rejected: {rejection}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ker::explain_method_refs;
+    use crate::model::fixtures::{log, meta, method};
+    use crate::model::{LocationType, OutcomeLog, PestSpecies};
+    use crate::query::{MethodRegistry, QueryOptions};
+
+    /// Eight logs numbered from `first`, so each batch gets its own stamps.
+    fn logs(method_id: &str, first: u64, pet_incidents: u64) -> Vec<OutcomeLog> {
+        (first..first + 8)
+            .map(|n| {
+                let mut l = log(method_id, n);
+                l.side_effects.pet_incident = n < first + pet_incidents;
+                l.side_effects.air_quality_concern = n == first + 7;
+                l
+            })
+            .collect()
+    }
+
+    fn approve(registry: &mut MethodRegistry, method: ControlMethod) {
+        let id = method.id.clone();
+        registry.add_method(method, meta(0)).unwrap();
+        registry
+            .transition(&id, LifecycleState::Approved, "reviewed", meta(0))
+            .unwrap();
+    }
+
+    #[test]
+    fn terms_add_up_to_the_score() {
+        let mut m = method("trap.live", ControlFamily::LiveCapture);
+        m.generates_persistent_plastic = true;
+        let logs = logs("trap.live", 0, 1);
+        let refs: Vec<&OutcomeLog> = logs.iter().collect();
+        let (score, x) = explain_method_refs(&m, &refs, &KerConfig::default());
+
+        let sum: f64 = x.corridors.iter().map(|t| t.contribution).sum();
+        assert!((sum - score.r).abs() < 1e-12);
+        assert_eq!(x.corridors.len(), Corridor::ALL.len());
+        let pets = &x.corridors[0];
+        assert_eq!(pets.corridor, Corridor::Pets);
+        assert_eq!(pets.incidence, 1.0 / 8.0);
+        assert_eq!(pets.band, score.bands.pets);
+
+        let e = &x.e;
+        let rebuilt = e.family_base * e.blend_factor - e.electronics_penalty - e.plastic_penalty;
+        assert!((rebuilt - score.e).abs() < 1e-12);
+        assert!(e.plastic_penalty > 0.0);
+        assert_eq!(e.electronics_penalty, 0.0);
+        assert_eq!(e.final_e, score.e);
+        assert_eq!(x.k.final_k, score.k);
+        assert_eq!(x.n_logs, 8);
+        assert_eq!(x.config_id, score.config_id);
+    }
+
+    #[test]
+    fn queries_record_uplifts_and_rejections() {
+        let mut registry = MethodRegistry::new();
+        approve(
+            &mut registry,
+            method("exclusion.seal", ControlFamily::Exclusion),
+        );
+        approve(
+            &mut registry,
+            method("trap.live", ControlFamily::LiveCapture),
+        );
+        registry
+            .add_method(method("sensor.cam", ControlFamily::MonitoringOnly), meta(0))
+            .unwrap();
+        for l in logs("exclusion.seal", 0, 0)
+            .into_iter()
+            .chain(logs("trap.live", 8, 4))
+        {
+            registry.add_log(l).unwrap();
+        }
+
+        let explained = registry
+            .explain_safest_methods(
+                PestSpecies::Rodent,
+                LocationType::Home,
+                &QueryOptions::new(1.0),
+            )
+            .unwrap();
+        let ids: Vec<&str> = explained.iter().map(|x| x.method_id.as_str()).collect();
+        assert_eq!(ids[0], "exclusion.seal");
+        // Every trace names the policy that filtered it, as ranked results do.
+        let policy_id = &registry.policy().policy_id;
+        assert!(explained.iter().all(|x| &x.policy_id == policy_id));
+        assert!(explained[0]
+            .to_string()
+            .contains("policy=deadbugs-eligibility-default-v2"));
+        let ranked = registry
+            .rank_safest_methods(
+                PestSpecies::Rodent,
+                LocationType::Home,
+                &QueryOptions::new(1.0),
+            )
+            .unwrap();
+        assert_eq!(&ranked[0].policy_id, policy_id);
+        assert!(explained[0].accepted());
+        assert!(explained[0].e.tier0_uplift > 0.0);
+        assert_eq!(
+            explained[0].e.final_e,
+            (explained[0].e.scored + explained[0].e.tier0_uplift).min(1.0)
+        );
+
+        // The pet incidents quarantined the trap on the way in.
+        let trap = explained
+            .iter()
+            .find(|x| x.method_id == "trap.live")
+            .unwrap();
+        assert!(matches!(
+            trap.rejections.as_slice(),
+            [
+                Rejection::NotOffered {
+                    state: LifecycleState::Suspended
+                },
+                Rejection::HardViolation { corridors },
+            ] if corridors == &[Corridor::Pets]
+        ));
+        let cam = explained
+            .iter()
+            .find(|x| x.method_id == "sensor.cam")
+            .unwrap();
+        assert!(matches!(
+            cam.rejections.as_slice(),
+            [Rejection::NotOffered {
+                state: LifecycleState::Proposed
+            }]
+        ));
+    }
+
+    #[test]
+    fn renders_as_text_and_json() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let logs = logs("trap.live", 0, 4);
+        let refs: Vec<&OutcomeLog> = logs.iter().collect();
+        let (_, mut x) = explain_method_refs(&m, &refs, &KerConfig::default());
+        x.rejections.push(Rejection::HardViolation {
+            corridors: vec![Corridor::Pets],
+        });
+
+        let text = x.to_string();
+        assert!(text.starts_with("trap.live [rejected]"));
+        assert!(text.contains("  K = "));
+        assert!(text.contains("  E = "));
+        assert!(text.contains("Hard at 0.50 of deployments"));
+        assert!(text.contains("rejected: hard band on pets"));
+
+        let back: KerExplanation = serde_json::from_str(&x.to_json().unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&back).unwrap(),
+            serde_json::to_value(&x).unwrap()
+        );
+    }
+}
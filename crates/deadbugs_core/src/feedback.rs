@@ -0,0 +1,372 @@
+#![forbid(unsafe_code)]
+
+//! Recommendation-to-outcome feedback loop.
+//!
+//! Each recommendation is stored as a `RecommendationRecord`: who chose which
+//! method where, and the K/E/R snapshot it was chosen under, keyed by a stable
+//! recommendation ID. Follow-up `OutcomeLog`s carry that ID in
+//! `recommendation_id`, and `feedback_report` compares what was predicted with
+//! what was observed, per method.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Corridor, EffectivenessBand, EvidenceMeta, KerScore, OutcomeLog, PestContext};
+use crate::recency::RecencyConfig;
+use crate::shard::{fnv1a64, provenance_key, ShardSchema};
+use crate::strata::ScoringContext;
+
+/// Corridors observed directly in follow-up side effects.
+const OBSERVED_CORRIDORS: [Corridor; 5] = [
+    Corridor::Pets,
+    Corridor::Wildlife,
+    Corridor::HumanInjury,
+    Corridor::Air,
+    Corridor::Bycatch,
+];
+
+/// A recommendation as made: who, where, what, and the scores behind it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecommendationRecord {
+    pub recommendation_id: String,
+    pub method_id: String,
+    /// Site the recommendation was made for.
+    pub context: PestContext,
+    /// Operator, location cell and decision time.
+    pub meta: EvidenceMeta,
+    /// K/E/R at decision time.
+    pub snapshot: KerScore,
+    /// Share of the evidence behind `snapshot` (same stratum, same recency
+    /// weighting) with a Medium or High band.
+    pub predicted_success: Option<f64>,
+}
+
+impl ShardSchema for RecommendationRecord {
+    const SCHEMA: &'static str = "DeadbugsRecommendation";
+}
+
+impl RecommendationRecord {
+    pub fn new(
+        method_id: &str,
+        context: PestContext,
+        meta: EvidenceMeta,
+        snapshot: KerScore,
+        predicted_success: Option<f64>,
+    ) -> Self {
+        Self {
+            recommendation_id: recommendation_id(method_id, &meta),
+            method_id: method_id.to_string(),
+            context,
+            meta,
+            snapshot,
+            predicted_success,
+        }
+    }
+}
+
+/// Stable ID from the method and the decision's provenance (operator, hex stamp,
+/// time): FNV-1a 64, so the same decision always maps to the same ID.
+pub fn recommendation_id(method_id: &str, meta: &EvidenceMeta) -> String {
+    let hash = fnv1a64(provenance_key(method_id, meta).as_bytes());
+    format!("rec-{hash:016x}")
+}
+
+/// Share of logs with a Medium or High band; `None` without logs.
+pub fn success_share(logs: &[&OutcomeLog]) -> Option<f64> {
+    if logs.is_empty() {
+        return None;
+    }
+    let successes = logs.iter().filter(|l| succeeded(l)).count();
+    Some(successes as f64 / logs.len() as f64)
+}
+
+/// [`success_share`] over the evidence `snapshot` was scored from: the logs in its
+/// stratum around `ctx`, weighted by the recency it records (`fallback` when it
+/// records none). `None` when no log carries weight.
+pub fn predicted_success(
+    logs: &[&OutcomeLog],
+    ctx: &ScoringContext,
+    snapshot: &KerScore,
+    fallback: &RecencyConfig,
+) -> Option<f64> {
+    let recency = snapshot.recency.as_ref().unwrap_or(fallback);
+    let as_of = recency.reference_time();
+    let (mut total, mut successes) = (0.0, 0.0);
+    for log in logs.iter().filter(|l| ctx.matches(l, snapshot.stratum)) {
+        let weight = recency.weight(log.meta.timestamp, as_of);
+        total += weight;
+        if succeeded(log) {
+            successes += weight;
+        }
+    }
+    (total > 0.0).then(|| successes / total)
+}
+
+fn succeeded(log: &OutcomeLog) -> bool {
+    matches!(
+        log.effectiveness,
+        EffectivenessBand::High | EffectivenessBand::Medium
+    )
+}
+
+fn incident(log: &OutcomeLog, corridor: Corridor) -> bool {
+    let s = &log.side_effects;
+    match corridor {
+        Corridor::Pets => s.pet_incident,
+        Corridor::Wildlife => s.wildlife_incident,
+        Corridor::HumanInjury => s.human_injury,
+        Corridor::Air => s.air_quality_concern,
+        Corridor::Bycatch => s.non_target_kill_count > 0,
+        Corridor::Waste => s.waste_burden == "high",
+    }
+}
+
+/// Predicted vs realized incidence on one corridor.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CorridorFeedback {
+    pub corridor: Corridor,
+    /// Mean snapshot incidence (share of deployments with an incident) over the
+    /// recommendations, on the same scale as `realized`.
+    pub predicted: f64,
+    /// Share of follow-up logs reporting an incident on this corridor.
+    pub realized: Option<f64>,
+}
+
+/// Predicted vs realized outcomes for one method.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MethodFeedback {
+    pub method_id: String,
+    pub recommendations: usize,
+    /// Recommendations with at least one linked follow-up log.
+    pub followed_up: usize,
+    pub outcome_logs: usize,
+    /// Mean snapshot K, E and R.
+    pub predicted_k: f64,
+    pub predicted_e: f64,
+    pub predicted_r: f64,
+    /// Mean `predicted_success` over the recommendations that have one.
+    pub predicted_success: Option<f64>,
+    /// Share of follow-up logs with a Medium or High band.
+    pub realized_success: Option<f64>,
+    pub corridors: Vec<CorridorFeedback>,
+}
+
+/// Per-method comparison of recommendations with the logs linked back to them.
+/// Logs whose `recommendation_id` matches no record, or whose method differs from
+/// the record's, are ignored.
+pub fn feedback_report(
+    records: &[RecommendationRecord],
+    logs: &[&OutcomeLog],
+) -> Vec<MethodFeedback> {
+    let mut follow_ups: BTreeMap<&str, Vec<&OutcomeLog>> = BTreeMap::new();
+    for &log in logs {
+        if let Some(id) = log.recommendation_id.as_deref() {
+            follow_ups.entry(id).or_default().push(log);
+        }
+    }
+
+    let mut by_method: BTreeMap<&str, Vec<&RecommendationRecord>> = BTreeMap::new();
+    for record in records {
+        by_method
+            .entry(record.method_id.as_str())
+            .or_default()
+            .push(record);
+    }
+
+    by_method
+        .into_iter()
+        .map(|(method_id, recs)| {
+            let n = recs.len() as f64;
+            let per_record: Vec<Vec<&OutcomeLog>> = recs
+                .iter()
+                .map(|r| {
+                    follow_ups
+                        .get(r.recommendation_id.as_str())
+                        .into_iter()
+                        .flatten()
+                        .copied()
+                        .filter(|l| l.method_id == r.method_id)
+                        .collect()
+                })
+                .collect();
+            let followed_up = per_record.iter().filter(|logs| !logs.is_empty()).count();
+            let linked: Vec<&OutcomeLog> = per_record.into_iter().flatten().collect();
+
+            let predictions: Vec<f64> = recs.iter().filter_map(|r| r.predicted_success).collect();
+            let predicted_success = if predictions.is_empty() {
+                None
+            } else {
+                Some(predictions.iter().sum::<f64>() / predictions.len() as f64)
+            };
+
+            let corridors = OBSERVED_CORRIDORS
+                .iter()
+                .map(|&corridor| CorridorFeedback {
+                    corridor,
+                    predicted: recs
+                        .iter()
+                        .map(|r| r.snapshot.incidence.get(corridor))
+                        .sum::<f64>()
+                        / n,
+                    realized: if linked.is_empty() {
+                        None
+                    } else {
+                        let hits = linked.iter().filter(|l| incident(l, corridor)).count();
+                        Some(hits as f64 / linked.len() as f64)
+                    },
+                })
+                .collect();
+
+            MethodFeedback {
+                method_id: method_id.to_string(),
+                recommendations: recs.len(),
+                followed_up,
+                outcome_logs: linked.len(),
+                predicted_k: recs.iter().map(|r| r.snapshot.k).sum::<f64>() / n,
+                predicted_e: recs.iter().map(|r| r.snapshot.e).sum::<f64>() / n,
+                predicted_r: recs.iter().map(|r| r.snapshot.r).sum::<f64>() / n,
+                predicted_success,
+                realized_success: success_share(&linked),
+                corridors,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ker::{score_method, KerConfig};
+    use crate::model::fixtures::{context, log, meta, method};
+    use crate::model::{ControlFamily, LocationType, Stratum};
+    use crate::query::MethodRegistry;
+    use crate::store::StoreError;
+
+    /// A live-trap recommendation whose snapshot predicts `pets` incidence.
+    fn record(n: u64, pets: f64) -> RecommendationRecord {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let mut snapshot = score_method(&m, &[], &KerConfig::default());
+        snapshot.incidence.r_pets = pets;
+        RecommendationRecord::new(
+            &m.id,
+            context(LocationType::Home),
+            meta(n),
+            snapshot,
+            Some(0.5),
+        )
+    }
+
+    fn follow_up(record: &RecommendationRecord, n: u64, pet_incident: bool) -> OutcomeLog {
+        let mut l = log(&record.method_id, n);
+        l.recommendation_id = Some(record.recommendation_id.clone());
+        l.side_effects.pet_incident = pet_incident;
+        l
+    }
+
+    fn pets(feedback: &MethodFeedback) -> &CorridorFeedback {
+        feedback
+            .corridors
+            .iter()
+            .find(|c| c.corridor == Corridor::Pets)
+            .unwrap()
+    }
+
+    #[test]
+    fn predicted_and_realized_share_a_scale() {
+        let rec = record(1, 0.25);
+        let logs: Vec<OutcomeLog> = (0..4).map(|n| follow_up(&rec, 10 + n, n == 0)).collect();
+        let refs: Vec<&OutcomeLog> = logs.iter().collect();
+        let report = feedback_report(&[rec], &refs);
+        assert_eq!(report.len(), 1);
+        let pets = pets(&report[0]);
+        assert_eq!(pets.predicted, 0.25);
+        assert_eq!(pets.realized, Some(0.25));
+        assert_eq!(report[0].realized_success, Some(1.0));
+    }
+
+    #[test]
+    fn follow_ups_for_another_method_are_ignored() {
+        let rec = record(1, 0.0);
+        let mut stray = follow_up(&rec, 10, true);
+        stray.method_id = "trap.snap".to_string();
+        let own = follow_up(&rec, 11, false);
+        let unlinked = log("trap.live", 12);
+
+        let report = feedback_report(std::slice::from_ref(&rec), &[&stray, &unlinked]);
+        assert_eq!(report[0].followed_up, 0);
+        assert_eq!(report[0].outcome_logs, 0);
+        assert_eq!(pets(&report[0]).realized, None);
+
+        let report = feedback_report(&[rec], &[&stray, &own]);
+        assert_eq!(report[0].followed_up, 1);
+        assert_eq!(report[0].outcome_logs, 1);
+        assert_eq!(pets(&report[0]).realized, Some(0.0));
+    }
+
+    #[test]
+    fn predicted_success_uses_the_snapshots_stratum_and_window() {
+        let mut registry = MethodRegistry::new();
+        let mut add = |n: u64, location: LocationType, band: EffectivenessBand| {
+            let mut l = log("trap.live", n);
+            l.context = context(location);
+            l.effectiveness = band;
+            registry.add_log(l).unwrap();
+        };
+        // Recent home logs succeed; stale home logs and farm logs fail.
+        for n in 0..3 {
+            add(n, LocationType::Home, EffectivenessBand::Low);
+        }
+        for n in 80..86 {
+            add(n, LocationType::Home, EffectivenessBand::High);
+        }
+        for n in 90..96 {
+            add(n, LocationType::Farm, EffectivenessBand::Low);
+        }
+
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let mut snapshot = score_method(&m, &[], &KerConfig::default());
+        snapshot.stratum = Stratum::Location;
+        snapshot.recency = Some(RecencyConfig {
+            window_days: Some(30.0),
+            as_of: Some(meta(100).timestamp),
+            ..RecencyConfig::uniform()
+        });
+        let id = registry
+            .recommend(
+                "trap.live",
+                context(LocationType::Home),
+                meta(100),
+                snapshot.clone(),
+            )
+            .unwrap();
+        let record = &registry.recommendations()[0];
+        assert_eq!(record.recommendation_id, id);
+        assert_eq!(record.predicted_success, Some(1.0));
+
+        // Pooled and unwindowed, the same logs mostly fail.
+        snapshot.stratum = Stratum::Pooled;
+        snapshot.recency = Some(RecencyConfig::uniform());
+        registry
+            .recommend(
+                "trap.live",
+                context(LocationType::Home),
+                meta(101),
+                snapshot,
+            )
+            .unwrap();
+        assert_eq!(registry.recommendations()[1].predicted_success, Some(0.4));
+    }
+
+    #[test]
+    fn recommendation_ids_are_recorded_once() {
+        let mut registry = MethodRegistry::new();
+        registry.add_recommendation(record(1, 0.0)).unwrap();
+        assert!(matches!(
+            registry.add_recommendation(record(1, 0.1)),
+            Err(StoreError::DuplicateRecommendation(id)) if id == record(1, 0.0).recommendation_id
+        ));
+        registry.add_recommendation(record(2, 0.0)).unwrap();
+        assert_eq!(registry.recommendations().len(), 2);
+    }
+}
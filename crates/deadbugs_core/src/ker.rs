@@ -1,8 +1,559 @@
 #![forbid(unsafe_code)]
 
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::capture::{capture_effectiveness, CaptureConfig};
+use crate::explain::{EBreakdown, KBreakdown, KerExplanation};
 use crate::model::{
-    ControlFamily, ControlMethod, EffectivenessBand, KerScore, OutcomeLog, RiskCoordinates,
+    ControlFamily, ControlMethod, Corridor, CorridorBand, CorridorBands, CredibleInterval,
+    EffectivenessBand, EvidenceMeta, KerScore, LocationType, OutcomeLog, PestContext,
+    RiskCoordinates, Stratum,
 };
+use crate::recency::{effective_sample_size, RecencyConfig};
+use crate::shard::{content_digest, from_json_stamped, to_json, ShardError, ShardSchema};
+use crate::stats::{beta_interval, beta_mean, gamma_interval};
+use crate::strata::StrataConfig;
+
+/// Corridor weights used to aggregate R; protected corridors should dominate.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorridorWeights {
+    pub pets: f64,
+    pub human_injury: f64,
+    pub wildlife: f64,
+    pub waste: f64,
+    pub air: f64,
+    pub bycatch: f64,
+}
+
+impl CorridorWeights {
+    pub fn weight(&self, corridor: Corridor) -> f64 {
+        match corridor {
+            Corridor::Pets => self.pets,
+            Corridor::Wildlife => self.wildlife,
+            Corridor::Waste => self.waste,
+            Corridor::Air => self.air,
+            Corridor::HumanInjury => self.human_injury,
+            Corridor::Bycatch => self.bycatch,
+        }
+    }
+}
+
+impl Default for CorridorWeights {
+    fn default() -> Self {
+        Self {
+            pets: 0.25,
+            human_injury: 0.25,
+            wildlife: 0.15,
+            waste: 0.1,
+            air: 0.1,
+            bycatch: 0.15,
+        }
+    }
+}
+
+/// Base eco-impact per control family, before material penalties and outcome blending.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FamilyBaseE {
+    pub exclusion: f64,
+    pub sanitation: f64,
+    pub habitat_change: f64,
+    pub predator_support: f64,
+    pub mechanical_kill: f64,
+    pub live_capture: f64,
+    pub monitoring_only: f64,
+}
+
+impl Default for FamilyBaseE {
+    fn default() -> Self {
+        Self {
+            exclusion: 0.95,
+            sanitation: 0.93,
+            habitat_change: 0.9,
+            predator_support: 0.88,
+            mechanical_kill: 0.8,
+            live_capture: 0.78,
+            monitoring_only: 0.7,
+        }
+    }
+}
+
+impl FamilyBaseE {
+    pub fn base(&self, family: ControlFamily) -> f64 {
+        match family {
+            ControlFamily::Exclusion => self.exclusion,
+            ControlFamily::Sanitation => self.sanitation,
+            ControlFamily::HabitatChange => self.habitat_change,
+            ControlFamily::PredatorSupport => self.predator_support,
+            ControlFamily::MechanicalKill => self.mechanical_kill,
+            ControlFamily::LiveCapture => self.live_capture,
+            ControlFamily::MonitoringOnly => self.monitoring_only,
+        }
+    }
+}
+
+/// Beta prior pseudo-counts (successes, failures).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BetaPrior {
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl BetaPrior {
+    /// Posterior parameters after `hits` of `n` trials.
+    fn posterior(&self, hits: f64, n: f64) -> (f64, f64) {
+        (self.alpha + hits, self.beta + (n - hits).max(0.0))
+    }
+}
+
+/// Gamma prior on a per-day event rate.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GammaPrior {
+    pub shape: f64,
+    /// Prior rate, in pseudo exposure days.
+    pub days: f64,
+}
+
+impl GammaPrior {
+    /// Posterior (shape, rate) after `events` over `exposure_days`.
+    fn posterior(&self, events: f64, exposure_days: f64) -> (f64, f64) {
+        (self.shape + events, self.days + exposure_days)
+    }
+}
+
+/// Posterior settings for K and the incident corridors.
+///
+/// K is the posterior mean share of the modal effectiveness band, so it rises with
+/// both sample size and consistency; the default K prior (0.5, 4.5) gives the old
+/// "untested" K of 0.1 with no logs. Incident and bycatch corridors are Poisson
+/// rates per exposure day under a weak Gamma prior.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PosteriorConfig {
+    pub k_prior: BetaPrior,
+    pub incident_prior: GammaPrior,
+    /// Probability mass inside reported credible intervals.
+    pub credible_mass: f64,
+}
+
+impl Default for PosteriorConfig {
+    fn default() -> Self {
+        Self {
+            k_prior: BetaPrior {
+                alpha: 0.5,
+                beta: 4.5,
+            },
+            incident_prior: GammaPrior {
+                shape: 0.5,
+                days: 1.0,
+            },
+            credible_mass: 0.9,
+        }
+    }
+}
+
+/// Independent-replication requirements for K.
+///
+/// K is capped at `single_source_cap` while all evidence comes from one operator at
+/// one site; the cap lifts linearly as distinct operators (`bostrom_address`) and
+/// distinct sites (`location_cell`) accumulate, and disappears once both reach
+/// their `full_credit_*` counts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiversityConfig {
+    pub single_source_cap: f64,
+    pub full_credit_operators: usize,
+    pub full_credit_sites: usize,
+}
+
+impl Default for DiversityConfig {
+    fn default() -> Self {
+        Self {
+            single_source_cap: 0.5,
+            full_credit_operators: 3,
+            full_credit_sites: 3,
+        }
+    }
+}
+
+impl DiversityConfig {
+    /// Distinct operators and sites in `logs`, and the resulting cap on K.
+    pub fn k_cap(&self, logs: &[&OutcomeLog]) -> (usize, usize, f64) {
+        let operators: HashSet<&str> = logs
+            .iter()
+            .map(|l| l.meta.bostrom_address.as_str())
+            .collect();
+        let sites: HashSet<&str> = logs.iter().map(|l| l.meta.location_cell.as_str()).collect();
+
+        let credit = |distinct: usize, full: usize| -> f64 {
+            if full <= 1 {
+                return 1.0;
+            }
+            (distinct.saturating_sub(1) as f64 / (full - 1) as f64).min(1.0)
+        };
+        let diversity = 0.5 * credit(operators.len(), self.full_credit_operators)
+            + 0.5 * credit(sites.len(), self.full_credit_sites);
+        let cap = self.single_source_cap + (1.0 - self.single_source_cap) * diversity;
+        (operators.len(), sites.len(), clamp01(cap))
+    }
+}
+
+/// Band edges for one corridor: `< gold` is safe, `>= hard` is hard, gold in between.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BandThresholds {
+    pub gold: f64,
+    pub hard: f64,
+}
+
+impl BandThresholds {
+    pub fn band(&self, r: f64) -> CorridorBand {
+        if r >= self.hard {
+            CorridorBand::Hard
+        } else if r >= self.gold {
+            CorridorBand::Gold
+        } else {
+            CorridorBand::Safe
+        }
+    }
+}
+
+/// Safe/gold/hard band table, one row per corridor.
+/// Incident corridors are thresholds on the share of deployments with an incident
+/// (`KerScore::incidence`), so a method that harms in a fraction of deployments lands
+/// in the hard band whatever the length of the logs that report it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandTable {
+    pub pets: BandThresholds,
+    pub wildlife: BandThresholds,
+    pub waste: BandThresholds,
+    pub air: BandThresholds,
+    pub human_injury: BandThresholds,
+    pub bycatch: BandThresholds,
+}
+
+impl Default for BandTable {
+    fn default() -> Self {
+        Self {
+            pets: BandThresholds {
+                gold: 0.02,
+                hard: 0.1,
+            },
+            wildlife: BandThresholds {
+                gold: 0.05,
+                hard: 0.2,
+            },
+            waste: BandThresholds {
+                gold: 0.5,
+                hard: 0.9,
+            },
+            air: BandThresholds {
+                gold: 0.1,
+                hard: 0.3,
+            },
+            human_injury: BandThresholds {
+                gold: 0.01,
+                hard: 0.05,
+            },
+            bycatch: BandThresholds {
+                gold: 0.1,
+                hard: 0.3,
+            },
+        }
+    }
+}
+
+impl BandTable {
+    /// Band each coordinate against its row of the table.
+    pub fn classify(&self, coords: &RiskCoordinates) -> CorridorBands {
+        CorridorBands {
+            pets: self.pets.band(coords.r_pets),
+            wildlife: self.wildlife.band(coords.r_wildlife),
+            waste: self.waste.band(coords.r_waste),
+            air: self.air.band(coords.r_air),
+            human_injury: self.human_injury.band(coords.r_human_injury),
+            bycatch: self.bycatch.band(coords.r_bycatch),
+        }
+    }
+}
+
+/// Share of a deployment day during which people and pets are on site, per location.
+/// Scales exposure for the pet and human-injury corridors.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OccupancyWeights {
+    pub home: f64,
+    pub restaurant: f64,
+    pub farm: f64,
+    pub warehouse: f64,
+    pub hospital: f64,
+    pub other: f64,
+}
+
+impl Default for OccupancyWeights {
+    fn default() -> Self {
+        Self {
+            home: 1.0,
+            restaurant: 0.6,
+            farm: 0.5,
+            warehouse: 0.4,
+            hospital: 1.0,
+            other: 0.7,
+        }
+    }
+}
+
+impl OccupancyWeights {
+    pub fn weight(&self, location: LocationType) -> f64 {
+        match location {
+            LocationType::Home => self.home,
+            LocationType::Restaurant => self.restaurant,
+            LocationType::Farm => self.farm,
+            LocationType::Warehouse => self.warehouse,
+            LocationType::Hospital => self.hospital,
+            LocationType::Other => self.other,
+        }
+    }
+}
+
+/// Exposure model for rate-based corridors.
+///
+/// Each log contributes `observation_days` device-days (one device per deployment;
+/// at least one day). Wildlife, air and bycatch use raw device-days; pets and human
+/// injury use occupied days (device-days × site occupancy). A corridor is reported
+/// as the chance of at least one event over `reference_days` at the posterior rate,
+/// so short trials and long installations land on the same scale for R. Bands do not
+/// use these rates: a projection onto a fixed length understates harm reported by
+/// long logs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExposureModel {
+    /// Length of the reference deployment rates are projected onto.
+    pub reference_days: f64,
+    pub occupancy: OccupancyWeights,
+}
+
+impl Default for ExposureModel {
+    fn default() -> Self {
+        Self {
+            reference_days: 7.0,
+            occupancy: OccupancyWeights::default(),
+        }
+    }
+}
+
+impl ExposureModel {
+    /// Per-day rate → probability of at least one event in a reference deployment.
+    fn project(&self, rate_per_day: f64) -> f64 {
+        clamp01(1.0 - (-rate_per_day * self.reference_days).exp())
+    }
+}
+
+/// Site burden from baseline conditions recorded on each log.
+///
+/// A failed deployment at a burdened site is only partly charged to the method:
+/// it counts as `burden` of a success when computing the success fraction for E.
+/// Conditions a family exists to fix are not excused (hygiene for sanitation,
+/// building gaps for exclusion).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiteAdjustment {
+    pub food_left_out: f64,
+    pub open_garbage: f64,
+    pub standing_water: f64,
+    pub organic_debris: f64,
+    /// Applied when cleaning happens fewer than `min_cleaning_per_week` times.
+    pub infrequent_cleaning: f64,
+    pub min_cleaning_per_week: u8,
+    /// Applied when the building still has unsealed entries.
+    pub building_gaps: f64,
+    /// Cap on the total burden of one site.
+    pub max_burden: f64,
+}
+
+impl Default for SiteAdjustment {
+    fn default() -> Self {
+        Self {
+            food_left_out: 0.15,
+            open_garbage: 0.15,
+            standing_water: 0.1,
+            organic_debris: 0.05,
+            infrequent_cleaning: 0.1,
+            min_cleaning_per_week: 2,
+            building_gaps: 0.15,
+            max_burden: 0.6,
+        }
+    }
+}
+
+impl SiteAdjustment {
+    /// Share of a failure at this site attributed to the site rather than the method.
+    pub fn burden(&self, ctx: &PestContext, family: ControlFamily) -> f64 {
+        let mut burden = 0.0;
+        if family != ControlFamily::Sanitation {
+            let h = &ctx.hygiene;
+            if h.food_left_out {
+                burden += self.food_left_out;
+            }
+            if h.open_garbage {
+                burden += self.open_garbage;
+            }
+            if h.standing_water {
+                burden += self.standing_water;
+            }
+            if h.organic_debris {
+                burden += self.organic_debris;
+            }
+            if h.cleaning_frequency_per_week < self.min_cleaning_per_week {
+                burden += self.infrequent_cleaning;
+            }
+        }
+        if family != ControlFamily::Exclusion && ctx.building_has_gaps {
+            burden += self.building_gaps;
+        }
+        burden.min(self.max_burden).max(0.0)
+    }
+}
+
+/// Waste corridor baseline and per-log nudges from reported burden.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WasteModel {
+    /// Baseline when disposable electronics or persistent plastics are involved.
+    pub material_base: f64,
+    pub clean_base: f64,
+    pub high_burden: f64,
+    pub moderate_burden: f64,
+}
+
+impl Default for WasteModel {
+    fn default() -> Self {
+        Self {
+            material_base: 0.7,
+            clean_base: 0.2,
+            high_burden: 0.2,
+            moderate_burden: 0.1,
+        }
+    }
+}
+
+/// Every tunable constant of the K/E/R scorer.
+/// Regional governance bodies publish these as config shards; `config_id` is copied
+/// into each `KerScore` so results can be reproduced against the exact config.
+/// Fields missing from a config file take their default values, so files written
+/// before a field existed keep loading.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KerConfig {
+    pub config_id: String,
+    pub weights: CorridorWeights,
+    pub family_base_e: FamilyBaseE,
+    pub electronics_penalty: f64,
+    pub plastic_penalty: f64,
+    pub waste: WasteModel,
+    pub exposure: ExposureModel,
+    pub posterior: PosteriorConfig,
+    pub bands: BandTable,
+    pub strata: StrataConfig,
+    pub site: SiteAdjustment,
+    pub capture: CaptureConfig,
+    pub recency: RecencyConfig,
+    pub diversity: DiversityConfig,
+}
+
+impl Default for KerConfig {
+    fn default() -> Self {
+        Self {
+            config_id: "deadbugs-ker-default-v12".to_string(),
+            weights: CorridorWeights::default(),
+            family_base_e: FamilyBaseE::default(),
+            electronics_penalty: 0.15,
+            plastic_penalty: 0.1,
+            waste: WasteModel::default(),
+            exposure: ExposureModel::default(),
+            posterior: PosteriorConfig::default(),
+            bands: BandTable::default(),
+            strata: StrataConfig::default(),
+            site: SiteAdjustment::default(),
+            capture: CaptureConfig::default(),
+            recency: RecencyConfig::default(),
+            diversity: DiversityConfig::default(),
+        }
+    }
+}
+
+/// A `KerConfig` as published by a governance body, with its publisher metadata.
+/// `meta.hex_stamp` is an integrity checksum of the config, not a signature: it
+/// catches corrupted or hand-edited files, but does not prove who published them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KerConfigShard {
+    pub config: KerConfig,
+    pub meta: EvidenceMeta,
+}
+
+impl ShardSchema for KerConfigShard {
+    const SCHEMA: &'static str = "DeadbugsKerConfig";
+}
+
+impl KerConfigShard {
+    /// Stamp `meta.hex_stamp` with the content digest of `config`, which must pass
+    /// [`KerConfig::validate`].
+    pub fn new(config: KerConfig, mut meta: EvidenceMeta) -> Result<Self, ShardError> {
+        config.validate()?;
+        meta.hex_stamp = content_digest(&config)?;
+        Ok(Self { config, meta })
+    }
+
+    /// Write the shard as a single-record JSON file.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), ShardError> {
+        fs::write(path, to_json(std::slice::from_ref(self))?)?;
+        Ok(())
+    }
+
+    /// Load the single config record of a JSON shard file, rejecting it unless
+    /// `meta.hex_stamp` matches the config's content digest and the config passes
+    /// [`KerConfig::validate`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ShardError> {
+        let shard: Self = from_json_stamped(&fs::read_to_string(path)?, "config")?;
+        shard.config.validate()?;
+        Ok(shard)
+    }
+}
+
+impl KerConfig {
+    /// Load a bare config from a JSON file (no checksum; for local overrides and tests).
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, ShardError> {
+        let json = fs::read_to_string(path)?;
+        let config: Self = serde_json::from_str(&json)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject priors the posteriors cannot use: every Beta and Gamma prior
+    /// parameter must be finite and positive, or K and R come out NaN.
+    pub fn validate(&self) -> Result<(), ShardError> {
+        let p = &self.posterior;
+        let priors = [
+            ("posterior.k_prior.alpha", p.k_prior.alpha),
+            ("posterior.k_prior.beta", p.k_prior.beta),
+            ("posterior.incident_prior.shape", p.incident_prior.shape),
+            ("posterior.incident_prior.days", p.incident_prior.days),
+        ];
+        for (field, value) in priors {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(ShardError::Invalid(format!(
+                    "{field} must be positive, got {value}"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
 
 /// Helper: clamp into [0,1].
 fn clamp01(x: f64) -> f64 {
@@ -15,183 +566,874 @@ fn clamp01(x: f64) -> f64 {
     }
 }
 
-/// Compute normalized risk coordinates from a set of outcome logs.
-fn compute_risk_coordinates(logs: &[OutcomeLog], method: &ControlMethod) -> RiskCoordinates {
-    if logs.is_empty() {
-        return RiskCoordinates::default();
-    }
+/// Point estimate plus credible bounds for every risk coordinate.
+struct CoordinatePosterior {
+    mean: RiskCoordinates,
+    lower: RiskCoordinates,
+    upper: RiskCoordinates,
+}
 
-    let n = logs.len() as f64;
+/// A log paired with its recency weight.
+type Weighted<'a> = (&'a OutcomeLog, f64);
+
+/// Compute normalized risk coordinates from a set of recency-weighted outcome logs.
+/// Incident and bycatch corridors are exposure-normalized rates (see [`ExposureModel`])
+/// with Gamma credible bounds; the waste corridor is material-driven and carries no
+/// posterior width.
+fn compute_risk_coordinates(
+    logs: &[Weighted],
+    method: &ControlMethod,
+    cfg: &KerConfig,
+) -> CoordinatePosterior {
+    let n: f64 = logs.iter().map(|&(_, w)| w).sum();
+    let exposure = &cfg.exposure;
 
     let mut pet_events = 0.0;
     let mut wildlife_events = 0.0;
     let mut human_injury_events = 0.0;
     let mut air_events = 0.0;
+    let mut non_target_kills = 0.0;
+    let mut device_days = 0.0;
+    let mut occupied_days = 0.0;
 
-    for log in logs {
+    for &(log, w) in logs {
         if log.side_effects.pet_incident {
-            pet_events += 1.0;
+            pet_events += w;
         }
         if log.side_effects.wildlife_incident {
-            wildlife_events += 1.0;
+            wildlife_events += w;
         }
         if log.side_effects.human_injury {
-            human_injury_events += 1.0;
+            human_injury_events += w;
         }
         if log.side_effects.air_quality_concern {
-            air_events += 1.0;
+            air_events += w;
         }
+        non_target_kills += w * f64::from(log.side_effects.non_target_kill_count);
+
+        let days = w * f64::from(log.observation_days.max(1));
+        device_days += days;
+        occupied_days += days * exposure.occupancy.weight(log.context.location_type);
     }
 
-    // Simple frequency-based normalization; can be refined with exposure denominators later.
-    let r_pets = clamp01(pet_events / n);
-    let r_wildlife = clamp01(wildlife_events / n);
-    let r_human_injury = clamp01(human_injury_events / n);
-    let r_air = clamp01(air_events / n);
+    let prior = &cfg.posterior.incident_prior;
+    let mass = cfg.posterior.credible_mass;
+    let rate = |events: f64, days: f64| -> (f64, CredibleInterval) {
+        let point = if days > 0.0 {
+            exposure.project(events / days)
+        } else {
+            0.0
+        };
+        let (shape, rate) = prior.posterior(events, days);
+        let ci = gamma_interval(shape, rate, mass);
+        // Keep the observed rate inside its bounds (zero events → zero lower bound).
+        let projected = CredibleInterval {
+            lower: exposure.project(ci.lower).min(point),
+            upper: exposure.project(ci.upper).max(point),
+        };
+        (point, projected)
+    };
+
+    let (r_pets, ci_pets) = rate(pet_events, occupied_days);
+    let (r_wildlife, ci_wildlife) = rate(wildlife_events, device_days);
+    let (r_human_injury, ci_human) = rate(human_injury_events, occupied_days);
+    let (r_air, ci_air) = rate(air_events, device_days);
+    let (r_bycatch, ci_bycatch) = rate(non_target_kills, device_days);
 
     // Waste corridor: baseline from material flags, nudged by reported burden.
     let base_waste = if method.uses_disposable_electronics || method.generates_persistent_plastic {
-        0.7
+        cfg.waste.material_base
     } else {
-        0.2
+        cfg.waste.clean_base
     };
 
     let mut waste_extra = 0.0;
-    for log in logs {
+    for &(log, w) in logs {
         match log.side_effects.waste_burden.as_str() {
-            "high" => waste_extra += 0.2,
-            "moderate" => waste_extra += 0.1,
+            "high" => waste_extra += w * cfg.waste.high_burden,
+            "moderate" => waste_extra += w * cfg.waste.moderate_burden,
             _ => {}
         }
     }
-    let r_waste = clamp01(base_waste + waste_extra / n);
+    let r_waste = if n > 0.0 {
+        clamp01(base_waste + waste_extra / n)
+    } else {
+        0.0
+    };
 
+    CoordinatePosterior {
+        mean: RiskCoordinates {
+            r_pets,
+            r_wildlife,
+            r_waste,
+            r_air,
+            r_human_injury,
+            r_bycatch,
+        },
+        lower: RiskCoordinates {
+            r_pets: ci_pets.lower,
+            r_wildlife: ci_wildlife.lower,
+            r_waste,
+            r_air: ci_air.lower,
+            r_human_injury: ci_human.lower,
+            r_bycatch: ci_bycatch.lower,
+        },
+        upper: RiskCoordinates {
+            r_pets: ci_pets.upper,
+            r_wildlife: ci_wildlife.upper,
+            r_waste,
+            r_air: ci_air.upper,
+            r_human_injury: ci_human.upper,
+            r_bycatch: ci_bycatch.upper,
+        },
+    }
+}
+
+/// Weighted share of deployments with at least one incident, per corridor; the
+/// waste corridor has no incidents and passes `r_waste` through.
+fn compute_incidence(logs: &[Weighted], r_waste: f64) -> RiskCoordinates {
+    let n: f64 = logs.iter().map(|&(_, w)| w).sum();
+    let share = |hit: fn(&OutcomeLog) -> bool| {
+        let events: f64 = logs.iter().filter(|&&(l, _)| hit(l)).map(|&(_, w)| w).sum();
+        if n > 0.0 {
+            events / n
+        } else {
+            0.0
+        }
+    };
     RiskCoordinates {
-        r_pets,
-        r_wildlife,
+        r_pets: share(|l| l.side_effects.pet_incident),
+        r_wildlife: share(|l| l.side_effects.wildlife_incident),
         r_waste,
-        r_air,
-        r_human_injury,
+        r_air: share(|l| l.side_effects.air_quality_concern),
+        r_human_injury: share(|l| l.side_effects.human_injury),
+        r_bycatch: share(|l| l.side_effects.non_target_kill_count > 0),
     }
 }
 
-/// Knowledge-factor K based on evidence quality.
+/// Knowledge-factor K: posterior mean share of the modal effectiveness band.
+/// More logs and more consistent outcomes both raise and tighten the posterior.
 /// This is a virtual-only approximation from logs; external trial data can be layered later.
-fn compute_k(logs: &[OutcomeLog]) -> f64 {
-    if logs.is_empty() {
-        return 0.1; // anecdotal / untested
-    }
+/// Also returns the weighted modal share (`None` without logs) for explanations.
+fn compute_k(logs: &[Weighted], cfg: &PosteriorConfig) -> (f64, CredibleInterval, Option<f64>) {
+    let n: f64 = logs.iter().map(|&(_, w)| w).sum();
 
-    let n = logs.len() as f64;
-
-    // Reward consistency of effectiveness across logs.
     let mut high = 0.0;
     let mut med = 0.0;
     let mut low = 0.0;
-    for log in logs {
+    for &(log, w) in logs {
         match log.effectiveness {
-            EffectivenessBand::High => high += 1.0,
-            EffectivenessBand::Medium => med += 1.0,
-            EffectivenessBand::Low => low += 1.0,
+            EffectivenessBand::High => high += w,
+            EffectivenessBand::Medium => med += w,
+            EffectivenessBand::Low => low += w,
         }
     }
+    let modal = f64::max(high, f64::max(med, low));
 
-    let ph = high / n;
-    let pm = med / n;
-    let pl = low / n;
-
-    // Simple entropy-like penalty: more mixed outcomes → lower K.
-    let variability = (ph * (1.0 - ph)) + (pm * (1.0 - pm)) + (pl * (1.0 - pl));
+    let modal_share = (n > 0.0).then(|| modal / n);
 
-    let base = if n >= 20.0 {
-        0.9
-    } else if n >= 5.0 {
-        0.7
-    } else {
-        0.4
-    };
-
-    clamp01(base * (1.0 - 0.5 * variability))
+    let (a, b) = cfg.k_prior.posterior(modal, n);
+    (
+        beta_mean(a, b),
+        beta_interval(a, b, cfg.credible_mass),
+        modal_share,
+    )
 }
 
 /// Eco-impact E: reward exclusion, hygiene, and selective traps; penalize waste-heavy methods.
-fn compute_e(logs: &[OutcomeLog], method: &ControlMethod) -> f64 {
+/// Returns every term so explanations can show how E was built.
+fn compute_e(logs: &[Weighted], method: &ControlMethod, cfg: &KerConfig) -> EBreakdown {
     // Base by control family.
-    let base = match method.family {
-        ControlFamily::Exclusion => 0.95,
-        ControlFamily::Sanitation => 0.93,
-        ControlFamily::HabitatChange => 0.9,
-        ControlFamily::PredatorSupport => 0.88,
-        ControlFamily::MechanicalKill => 0.8,
-        ControlFamily::LiveCapture => 0.78,
-        ControlFamily::MonitoringOnly => 0.7,
-    };
+    let base = cfg.family_base_e.base(method.family);
 
     // Penalize plastics / disposable electronics as in biopack work.
-    let mut penalty = 0.0;
-    if method.uses_disposable_electronics {
-        penalty += 0.15;
-    }
-    if method.generates_persistent_plastic {
-        penalty += 0.1;
-    }
+    let electronics_penalty = if method.uses_disposable_electronics {
+        cfg.electronics_penalty
+    } else {
+        0.0
+    };
+    let plastic_penalty = if method.generates_persistent_plastic {
+        cfg.plastic_penalty
+    } else {
+        0.0
+    };
+    let penalty = electronics_penalty + plastic_penalty;
 
     // If effectiveness is systematically low, effective eco-gain is reduced.
-    let n = logs.len() as f64;
-    if n > 0.0 {
+    // Failures are discounted by the site burden so the method is not blamed for the site.
+    let n: f64 = logs.iter().map(|&(_, w)| w).sum();
+    let success_fraction = if n > 0.0 {
         let mut high_or_med = 0.0;
-        for log in logs {
+        for &(log, w) in logs {
             if matches!(
                 log.effectiveness,
                 EffectivenessBand::High | EffectivenessBand::Medium
             ) {
-                high_or_med += 1.0;
+                high_or_med += w;
+            } else {
+                high_or_med += w * cfg.site.burden(&log.context, method.family);
             }
         }
-        let success_frac = high_or_med / n;
-        // Blend base with success fraction.
-        let eff_factor = 0.5 + 0.5 * success_frac;
-        clamp01((base * eff_factor) - penalty)
+        Some(high_or_med / n)
     } else {
-        clamp01(base - penalty)
+        None
+    };
+
+    // Blend base with success fraction.
+    let blend_factor = success_fraction.map_or(1.0, |s| 0.5 + 0.5 * s);
+    let e = clamp01((base * blend_factor) - penalty);
+
+    EBreakdown {
+        family: method.family,
+        family_base: base,
+        electronics_penalty,
+        plastic_penalty,
+        success_fraction,
+        blend_factor,
+        scored: e,
+        tier0_uplift: 0.0,
+        context_uplift: 0.0,
+        context_rules: Vec::new(),
+        final_e: e,
     }
 }
 
 /// Aggregate R from risk coordinates with corridor weights.
-fn compute_r(coords: &RiskCoordinates) -> f64 {
-    // Emphasize pets, human injury, and wildlife as protected corridors.
-    let w_pets = 0.3;
-    let w_human = 0.3;
-    let w_wildlife = 0.2;
-    let w_waste = 0.1;
-    let w_air = 0.1;
-
+fn compute_r(coords: &RiskCoordinates, w: &CorridorWeights) -> f64 {
     clamp01(
-        w_pets * coords.r_pets
-            + w_human * coords.r_human_injury
-            + w_wildlife * coords.r_wildlife
-            + w_waste * coords.r_waste
-            + w_air * coords.r_air,
+        w.pets * coords.r_pets
+            + w.human_injury * coords.r_human_injury
+            + w.wildlife * coords.r_wildlife
+            + w.waste * coords.r_waste
+            + w.air * coords.r_air
+            + w.bycatch * coords.r_bycatch,
     )
 }
 
-/// Main scoring function: returns K, E, R and hard-violation flag.
-pub fn score_method(method: &ControlMethod, logs: &[OutcomeLog]) -> KerScore {
-    let coords = compute_risk_coordinates(logs, method);
-    let k = compute_k(logs);
-    let e = compute_e(logs, method);
-    let r = compute_r(&coords);
+/// Main scoring function: returns K, E, R, corridor bands and hard-violation flag under `cfg`.
+pub fn score_method(method: &ControlMethod, logs: &[OutcomeLog], cfg: &KerConfig) -> KerScore {
+    let refs: Vec<&OutcomeLog> = logs.iter().collect();
+    score_method_refs(method, &refs, cfg)
+}
+
+/// Same as [`score_method`], over borrowed logs (e.g. straight from a registry index).
+/// Logs are weighted by `cfg.recency`; logs outside the evidence window are dropped.
+pub fn score_method_refs(
+    method: &ControlMethod,
+    logs: &[&OutcomeLog],
+    cfg: &KerConfig,
+) -> KerScore {
+    score_parts(method, logs, cfg).0
+}
 
-    // Hard invariants: any corridor at 1.0 on protected dimensions disallows the method.
-    let hard_violation = coords.r_pets >= 1.0
-        || coords.r_human_injury >= 1.0
-        || coords.r_wildlife >= 1.0;
+/// [`score_method_refs`] plus the trace of every contribution behind the score.
+pub fn explain_method_refs(
+    method: &ControlMethod,
+    logs: &[&OutcomeLog],
+    cfg: &KerConfig,
+) -> (KerScore, KerExplanation) {
+    let (score, k, e, n_logs) = score_parts(method, logs, cfg);
+    let explanation = KerExplanation::new(method, &score, n_logs, k, e, cfg);
+    (score, explanation)
+}
 
-    KerScore {
+/// Score plus the K and E breakdowns and the number of logs in the window.
+fn score_parts(
+    method: &ControlMethod,
+    logs: &[&OutcomeLog],
+    cfg: &KerConfig,
+) -> (KerScore, KBreakdown, EBreakdown, usize) {
+    let as_of = cfg.recency.reference_time();
+    let weighted: Vec<Weighted> = logs
+        .iter()
+        .map(|&log| (log, cfg.recency.weight(log.meta.timestamp, as_of)))
+        .filter(|&(_, w)| w > 0.0)
+        .collect();
+    // The hard tier is not decayed and keeps incidents that left the window, unless
+    // the method's design has been revised since they were logged.
+    let hard_tier: Vec<Weighted> = logs
+        .iter()
+        .filter(|&&log| {
+            cfg.recency.weight(log.meta.timestamp, as_of) > 0.0
+                || method.revised_at.is_none_or(|at| log.meta.timestamp >= at)
+        })
+        .map(|&log| (log, 1.0))
+        .collect();
+    let in_window: Vec<&OutcomeLog> = weighted.iter().map(|&(log, _)| log).collect();
+    let n_effective = effective_sample_size(weighted.iter().map(|&(_, w)| w));
+
+    let posterior = compute_risk_coordinates(&weighted, method, cfg);
+    let coords = posterior.mean;
+    let (posterior_k, k_interval, modal_share) = compute_k(&weighted, &cfg.posterior);
+
+    // K rewards agreement between capture-rate decline and the operator's bands.
+    // Agreement is counted over every log in the evidence window, undecayed.
+    let capture = capture_effectiveness(&in_window, &cfg.capture);
+    // Single-source evidence is capped until independent operators and sites corroborate it.
+    let (distinct_operators, distinct_sites, k_cap) = cfg.diversity.k_cap(&in_window);
+    let k = cfg.capture.adjust_k(posterior_k, &capture).min(k_cap);
+    let k_interval = CredibleInterval {
+        lower: cfg.capture.adjust_k(k_interval.lower, &capture).min(k_cap),
+        upper: cfg.capture.adjust_k(k_interval.upper, &capture).min(k_cap),
+    };
+    let e_parts = compute_e(&weighted, method, cfg);
+    let e = e_parts.scored;
+    let r = compute_r(&coords, &cfg.weights);
+    let r_interval = CredibleInterval {
+        lower: compute_r(&posterior.lower, &cfg.weights),
+        upper: compute_r(&posterior.upper, &cfg.weights),
+    };
+
+    // Hard invariants: a protected corridor in the hard band disallows the method.
+    // Bands grade how often a deployment harms, not the projected rate.
+    let incidence = compute_incidence(&hard_tier, coords.r_waste);
+    let bands = cfg.bands.classify(&incidence);
+    let hard_violation = Corridor::PROTECTED
+        .iter()
+        .any(|&c| bands.get(c) == CorridorBand::Hard);
+
+    let k_parts = KBreakdown {
+        modal_share,
+        posterior_k,
+        capture_agreement: (capture.paired_logs > 0)
+            .then(|| f64::from(capture.agreeing_logs) / f64::from(capture.paired_logs)),
+        distinct_operators,
+        distinct_sites,
+        k_cap,
+        final_k: k,
+    };
+
+    let score = KerScore {
         k,
         e,
         r,
+        k_interval,
+        r_interval,
+        n_effective,
+        capture,
         coords,
+        coords_lower: posterior.lower,
+        coords_upper: posterior.upper,
+        incidence,
+        bands,
         hard_violation,
+        stratum: Stratum::Pooled,
+        config_id: cfg.config_id.clone(),
+        recency: Some(RecencyConfig {
+            as_of: Some(as_of),
+            ..cfg.recency.clone()
+        }),
+    };
+    (score, k_parts, e_parts, in_window.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::model::fixtures::{context, log, meta, method, DAY};
+
+    fn scratch_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("deadbugs-ker-{name}-{}.json", std::process::id()))
+    }
+
+    #[test]
+    fn config_shard_round_trips_with_its_stamp() {
+        let path = scratch_file("round-trip");
+        let config = KerConfig {
+            config_id: "region-7".to_string(),
+            ..KerConfig::default()
+        };
+        let shard = KerConfigShard::new(config, meta(1)).unwrap();
+        assert!(shard.meta.hex_stamp.starts_with("0x"));
+        shard.save_json(&path).unwrap();
+
+        let loaded = KerConfigShard::load(&path).unwrap();
+        assert_eq!(loaded.config.config_id, "region-7");
+        assert_eq!(loaded.meta.hex_stamp, shard.meta.hex_stamp);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn config_shard_rejects_edited_content() {
+        let path = scratch_file("tampered");
+        let shard = KerConfigShard::new(KerConfig::default(), meta(1)).unwrap();
+        shard.save_json(&path).unwrap();
+        let json = fs::read_to_string(&path).unwrap();
+        fs::write(
+            &path,
+            json.replace("\"plastic_penalty\": 0.1", "\"plastic_penalty\": 0.0"),
+        )
+        .unwrap();
+
+        let err = KerConfigShard::load(&path).unwrap_err();
+        assert!(matches!(err, ShardError::Integrity { .. }), "{err}");
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn config_shard_rejects_an_unstamped_record() {
+        let path = scratch_file("unstamped");
+        let shard = KerConfigShard {
+            config: KerConfig::default(),
+            meta: meta(1),
+        };
+        shard.save_json(&path).unwrap();
+        assert!(matches!(
+            KerConfigShard::load(&path),
+            Err(ShardError::Integrity { .. })
+        ));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn configs_with_non_positive_priors_are_rejected() {
+        let mut config = KerConfig::default();
+        assert!(config.validate().is_ok());
+        config.posterior.k_prior.alpha = 0.0;
+        assert!(matches!(
+            KerConfigShard::new(config.clone(), meta(1)),
+            Err(ShardError::Invalid(why)) if why.contains("k_prior.alpha")
+        ));
+
+        let path = scratch_file("bad-prior");
+        config.posterior.k_prior.alpha = 0.5;
+        config.posterior.incident_prior.days = -1.0;
+        fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+        assert!(matches!(
+            KerConfig::load_json(&path),
+            Err(ShardError::Invalid(why)) if why.contains("incident_prior.days")
+        ));
+
+        // A correctly stamped shard is still refused.
+        let mut shard = KerConfigShard::new(KerConfig::default(), meta(1)).unwrap();
+        shard.config = config;
+        shard.meta.hex_stamp = content_digest(&shard.config).unwrap();
+        shard.save_json(&path).unwrap();
+        assert!(matches!(
+            KerConfigShard::load(&path),
+            Err(ShardError::Invalid(_))
+        ));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn config_shard_reports_the_record_count() {
+        let path = scratch_file("count");
+        let shard = KerConfigShard::new(KerConfig::default(), meta(1)).unwrap();
+        fs::write(&path, to_json(&[shard.clone(), shard]).unwrap()).unwrap();
+        match KerConfigShard::load(&path) {
+            Err(ShardError::RecordCount { schema, found }) => {
+                assert_eq!(schema, "DeadbugsKerConfig");
+                assert_eq!(found, 2);
+            }
+            other => panic!("expected a record count error, got {other:?}"),
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn older_config_files_fill_in_missing_fields() {
+        let json = r#"{"config_id": "old", "weights": {"pets": 9.0}, "electronics_penalty": 0.5}"#;
+        let config: KerConfig = serde_json::from_str(json).unwrap();
+        let defaults = KerConfig::default();
+        assert_eq!(config.config_id, "old");
+        assert_eq!(config.weights.pets, 9.0);
+        assert_eq!(config.weights.wildlife, defaults.weights.wildlife);
+        assert_eq!(config.electronics_penalty, 0.5);
+        assert_eq!(config.plastic_penalty, defaults.plastic_penalty);
+        assert_eq!(config.strata.min_logs, defaults.strata.min_logs);
+        assert!(config.recency.as_of.is_none());
+    }
+
+    /// Default config with every log weighing the same.
+    fn pinned() -> KerConfig {
+        KerConfig {
+            recency: RecencyConfig::uniform(),
+            ..KerConfig::default()
+        }
+    }
+
+    /// Ten deployments of `days` each; pets are harmed in `incidents` of them.
+    fn pet_logs(days: u32, incidents: u64) -> Vec<OutcomeLog> {
+        (0..10)
+            .map(|n| {
+                let mut l = log("trap.live", n);
+                l.observation_days = days;
+                l.side_effects.pet_incident = n < incidents;
+                l
+            })
+            .collect()
+    }
+
+    #[test]
+    fn frequent_pet_harm_is_hard_at_any_log_length() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let cfg = pinned();
+        for days in [7, 30, 90, 365] {
+            let score = score_method(&m, &pet_logs(days, 4), &cfg);
+            assert!((score.incidence.r_pets - 0.4).abs() < 1e-12, "{days} days");
+            assert_eq!(score.bands.pets, CorridorBand::Hard, "{days} days");
+            assert!(score.hard_violation, "{days} days");
+        }
+    }
+
+    #[test]
+    fn longer_exposure_still_lowers_the_rate() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let cfg = pinned();
+        let short = score_method(&m, &pet_logs(7, 1), &cfg);
+        let long = score_method(&m, &pet_logs(365, 1), &cfg);
+        assert!(long.coords.r_pets < short.coords.r_pets);
+        assert!(long.r < short.r);
+        assert_eq!(short.incidence.r_pets, long.incidence.r_pets);
+    }
+
+    #[test]
+    fn bycatch_incidence_counts_deployments_not_kills() {
+        let m = method("trap.snap", ControlFamily::MechanicalKill);
+        let mut logs = pet_logs(14, 0);
+        logs[0].side_effects.non_target_kill_count = 5;
+        let score = score_method(&m, &logs, &pinned());
+        assert!((score.incidence.r_bycatch - 0.1).abs() < 1e-12);
+        assert_eq!(score.incidence.r_waste, score.coords.r_waste);
+    }
+
+    #[test]
+    fn clean_logs_stay_safe() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let score = score_method(&m, &pet_logs(30, 0), &pinned());
+        assert_eq!(score.incidence.r_pets, 0.0);
+        assert_eq!(score.bands.pets, CorridorBand::Safe);
+        assert!(!score.hard_violation);
+    }
+
+    #[test]
+    fn band_edges_belong_to_the_upper_band() {
+        let t = BandThresholds {
+            gold: 0.1,
+            hard: 0.3,
+        };
+        assert_eq!(t.band(0.0), CorridorBand::Safe);
+        assert_eq!(t.band(0.0999), CorridorBand::Safe);
+        assert_eq!(t.band(0.1), CorridorBand::Gold);
+        assert_eq!(t.band(0.2999), CorridorBand::Gold);
+        assert_eq!(t.band(0.3), CorridorBand::Hard);
+        assert_eq!(t.band(1.0), CorridorBand::Hard);
+    }
+
+    #[test]
+    fn classify_reads_each_corridor_against_its_own_row() {
+        let coords = RiskCoordinates {
+            r_pets: 0.05,
+            r_wildlife: 0.05,
+            r_waste: 0.95,
+            r_air: 0.0,
+            r_human_injury: 0.05,
+            r_bycatch: 0.2,
+        };
+        let bands = BandTable::default().classify(&coords);
+        assert_eq!(bands.pets, CorridorBand::Gold);
+        assert_eq!(bands.wildlife, CorridorBand::Gold);
+        assert_eq!(bands.waste, CorridorBand::Hard);
+        assert_eq!(bands.air, CorridorBand::Safe);
+        assert_eq!(bands.human_injury, CorridorBand::Hard);
+        assert_eq!(bands.bycatch, CorridorBand::Gold);
+    }
+
+    #[test]
+    fn only_protected_corridors_set_the_hard_flag() {
+        let m = ControlMethod {
+            uses_disposable_electronics: true,
+            ..method("trap.live", ControlFamily::LiveCapture)
+        };
+        let mut cfg = pinned();
+        cfg.bands.waste.hard = 0.5;
+        let mut logs = pet_logs(14, 0);
+        let score = score_method(&m, &logs, &cfg);
+        assert_eq!(score.bands.waste, CorridorBand::Hard);
+        assert!(!score.hard_violation);
+
+        logs[0].side_effects.human_injury = true;
+        let score = score_method(&m, &logs, &cfg);
+        assert_eq!(score.bands.human_injury, CorridorBand::Hard);
+        assert!(score.hard_violation);
+    }
+
+    #[test]
+    fn thresholds_come_from_the_config() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let logs = pet_logs(14, 4);
+        let mut cfg = pinned();
+        cfg.bands.pets = BandThresholds {
+            gold: 0.3,
+            hard: 0.5,
+        };
+        let score = score_method(&m, &logs, &cfg);
+        assert_eq!(score.bands.pets, CorridorBand::Gold);
+        assert!(!score.hard_violation);
+    }
+
+    #[test]
+    fn bycatch_rises_with_kills_and_falls_with_exposure() {
+        let m = method("trap.snap", ControlFamily::MechanicalKill);
+        let cfg = pinned();
+        let with_kills = |days: u32, kills: u32| {
+            let mut logs = pet_logs(days, 0);
+            logs[0].side_effects.non_target_kill_count = kills;
+            score_method(&m, &logs, &cfg)
+        };
+        let none = with_kills(14, 0);
+        let one = with_kills(14, 1);
+        let three = with_kills(14, 3);
+        let one_long = with_kills(140, 1);
+        assert_eq!(none.coords.r_bycatch, 0.0);
+        assert!(one.coords.r_bycatch > 0.0);
+        assert!(three.coords.r_bycatch > one.coords.r_bycatch);
+        assert!(one_long.coords.r_bycatch < one.coords.r_bycatch);
+        // 1 kill over 140 device-days projected onto a 7-day deployment.
+        let expected = 1.0 - (-7.0_f64 / 140.0).exp();
+        assert!((one.coords.r_bycatch - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bycatch_carries_weight_in_r() {
+        let m = method("trap.snap", ControlFamily::MechanicalKill);
+        let cfg = pinned();
+        let clean = score_method(&m, &pet_logs(14, 0), &cfg);
+        let mut logs = pet_logs(14, 0);
+        logs[0].side_effects.non_target_kill_count = 2;
+        let kills = score_method(&m, &logs, &cfg);
+        let dr = kills.r - clean.r;
+        assert!((dr - cfg.weights.bycatch * kills.coords.r_bycatch).abs() < 1e-12);
+        assert!(kills.r_interval.upper > kills.r);
+    }
+
+    #[test]
+    fn bycatch_in_the_hard_band_is_a_hard_violation() {
+        let m = method("trap.snap", ControlFamily::MechanicalKill);
+        let mut logs = pet_logs(14, 0);
+        for l in &mut logs[..3] {
+            l.side_effects.non_target_kill_count = 1;
+        }
+        let score = score_method(&m, &logs, &pinned());
+        assert_eq!(score.bands.bycatch, CorridorBand::Hard);
+        assert!(score.hard_violation);
+    }
+
+    /// A home with no hygiene problems and every entry sealed.
+    fn clean_site() -> PestContext {
+        let mut ctx = context(LocationType::Home);
+        ctx.hygiene.cleaning_frequency_per_week = 3;
+        ctx.building_has_gaps = false;
+        ctx
+    }
+
+    #[test]
+    fn site_burden_adds_up_and_is_capped() {
+        let site = SiteAdjustment::default();
+        assert_eq!(
+            site.burden(&clean_site(), ControlFamily::MechanicalKill),
+            0.0
+        );
+
+        let mut ctx = clean_site();
+        ctx.hygiene.open_garbage = true;
+        ctx.hygiene.standing_water = true;
+        assert!((site.burden(&ctx, ControlFamily::MechanicalKill) - 0.25).abs() < 1e-12);
+
+        ctx.hygiene.food_left_out = true;
+        ctx.hygiene.organic_debris = true;
+        ctx.hygiene.cleaning_frequency_per_week = 0;
+        ctx.building_has_gaps = true;
+        assert_eq!(
+            site.burden(&ctx, ControlFamily::MechanicalKill),
+            site.max_burden
+        );
+    }
+
+    #[test]
+    fn families_are_not_excused_for_what_they_fix() {
+        let site = SiteAdjustment::default();
+        let mut dirty = clean_site();
+        dirty.hygiene.open_garbage = true;
+        assert_eq!(site.burden(&dirty, ControlFamily::Sanitation), 0.0);
+        assert!(site.burden(&dirty, ControlFamily::Exclusion) > 0.0);
+
+        let mut gaps = clean_site();
+        gaps.building_has_gaps = true;
+        assert_eq!(site.burden(&gaps, ControlFamily::Exclusion), 0.0);
+        assert_eq!(
+            site.burden(&gaps, ControlFamily::Sanitation),
+            site.building_gaps
+        );
+    }
+
+    #[test]
+    fn failures_at_burdened_sites_cost_less_e() {
+        let m = method("trap.snap", ControlFamily::MechanicalKill);
+        let cfg = pinned();
+        let failures = |ctx: PestContext| -> Vec<OutcomeLog> {
+            (0..6)
+                .map(|n| {
+                    let mut l = log("trap.snap", n);
+                    l.context = ctx.clone();
+                    if n < 3 {
+                        l.effectiveness = EffectivenessBand::Low;
+                    }
+                    l
+                })
+                .collect()
+        };
+        let mut dirty = clean_site();
+        dirty.hygiene.open_garbage = true;
+        dirty.hygiene.food_left_out = true;
+
+        let clean = score_method(&m, &failures(clean_site()), &cfg);
+        let burdened = score_method(&m, &failures(dirty), &cfg);
+        assert!(burdened.e > clean.e);
+
+        // Successes are not changed by the site.
+        let all_high = |ctx: PestContext| {
+            let mut logs = failures(ctx);
+            for l in &mut logs {
+                l.effectiveness = EffectivenessBand::High;
+            }
+            score_method(&m, &logs, &cfg).e
+        };
+        let mut dirty = clean_site();
+        dirty.hygiene.open_garbage = true;
+        assert_eq!(all_high(clean_site()), all_high(dirty));
+    }
+
+    /// Decay with its window, measured from ten years after the fixture epoch.
+    fn decayed() -> KerConfig {
+        KerConfig {
+            recency: RecencyConfig {
+                as_of: Some(meta(3650).timestamp),
+                ..RecencyConfig::decay()
+            },
+            ..KerConfig::default()
+        }
+    }
+
+    /// Ten clean logs from the last year, after four injuries eight years back.
+    fn injured_then_clean() -> Vec<OutcomeLog> {
+        let mut logs: Vec<OutcomeLog> = (0..4)
+            .map(|n| {
+                let mut l = log("trap.live", 700 + n);
+                l.side_effects.human_injury = true;
+                l
+            })
+            .collect();
+        logs.extend((0..10).map(|n| log("trap.live", 3400 + n)));
+        logs
+    }
+
+    #[test]
+    fn default_scores_do_not_depend_on_the_clock() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let logs = pet_logs(14, 2);
+        let a = score_method(&m, &logs, &KerConfig::default());
+        let b = score_method(&m, &logs, &KerConfig::default());
+        assert_eq!(a.k, b.k);
+        assert_eq!(a.r, b.r);
+        assert_eq!(a.n_effective, 10.0);
+    }
+
+    #[test]
+    fn scores_record_the_recency_they_used() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let cfg = decayed();
+        let score = score_method(&m, &pet_logs(14, 0), &cfg);
+        let recency = score.recency.unwrap();
+        assert_eq!(recency.as_of, cfg.recency.as_of);
+        assert_eq!(recency.half_life_days, Some(365.0));
+        assert_eq!(recency.window_days, Some(5.0 * 365.0));
+
+        let score = score_method(&m, &pet_logs(14, 0), &KerConfig::default());
+        assert!(score.recency.unwrap().as_of.is_some());
+    }
+
+    #[test]
+    fn incidents_outside_the_window_stay_in_the_hard_tier() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let score = score_method(&m, &injured_then_clean(), &decayed());
+        // R only sees the window...
+        assert_eq!(score.coords.r_human_injury, 0.0);
+        assert!(score.n_effective < 11.0);
+        // ...but the old injuries still band the method.
+        assert!(score.incidence.r_human_injury > 0.0);
+        assert_eq!(score.bands.human_injury, CorridorBand::Hard);
+        assert!(score.hard_violation);
+    }
+
+    #[test]
+    fn a_design_revision_retires_incidents_outside_the_window() {
+        let mut m = method("trap.live", ControlFamily::LiveCapture);
+        m.revised_at = Some(meta(3200).timestamp);
+        let score = score_method(&m, &injured_then_clean(), &decayed());
+        assert_eq!(score.incidence.r_human_injury, 0.0);
+        assert!(!score.hard_violation);
+
+        // A revision does not hide incidents the window still holds.
+        let mut logs = injured_then_clean();
+        for (i, l) in logs[..4].iter_mut().enumerate() {
+            l.meta.timestamp = meta(3000).timestamp + Duration::from_secs(i as u64 * DAY);
+        }
+        let score = score_method(&m, &logs, &decayed());
+        assert!(score.hard_violation);
+    }
+
+    /// Twenty clean logs from `operators` signers spread over `sites` cells.
+    fn sourced(operators: u64, sites: u64) -> Vec<OutcomeLog> {
+        (0..20)
+            .map(|n| {
+                let mut l = log("trap.live", n);
+                l.meta.bostrom_address = format!("bostrom{}", n % operators);
+                l.meta.location_cell = format!("PHX-{}", n % sites);
+                l
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diversity_cap_lifts_with_operators_and_sites() {
+        let cfg = DiversityConfig::default();
+        let cap = |operators, sites| {
+            let logs = sourced(operators, sites);
+            let refs: Vec<&OutcomeLog> = logs.iter().collect();
+            cfg.k_cap(&refs)
+        };
+        assert_eq!(cap(1, 1), (1, 1, 0.5));
+        assert_eq!(cap(2, 2), (2, 2, 0.75));
+        assert_eq!(cap(3, 1), (3, 1, 0.75));
+        assert_eq!(cap(3, 3), (3, 3, 1.0));
+        assert_eq!(cap(10, 10), (10, 10, 1.0));
+        assert_eq!(cfg.k_cap(&[]), (0, 0, 0.5));
+    }
+
+    #[test]
+    fn single_source_evidence_is_capped_until_corroborated() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let cfg = pinned();
+        let single = score_method(&m, &sourced(1, 1), &cfg);
+        let independent = score_method(&m, &sourced(20, 20), &cfg);
+        assert!(single.k <= cfg.diversity.single_source_cap);
+        assert!(single.k_interval.upper <= cfg.diversity.single_source_cap);
+        assert!(independent.k > single.k);
+    }
+
+    #[test]
+    fn explanations_report_the_sources_behind_k() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let logs = sourced(2, 3);
+        let refs: Vec<&OutcomeLog> = logs.iter().collect();
+        let (score, x) = explain_method_refs(&m, &refs, &pinned());
+        assert_eq!(x.k.distinct_operators, 2);
+        assert_eq!(x.k.distinct_sites, 3);
+        assert!((x.k.k_cap - 0.875).abs() < 1e-12);
+        assert!(score.k <= x.k.k_cap);
     }
 }
@@ -0,0 +1,228 @@
+#![forbid(unsafe_code)]
+
+//! Method lifecycle states.
+//!
+//! Registry entries move from proposed through research-only to approved, and can
+//! be suspended or retired. Every change is a `LifecycleTransition` appended to the
+//! method's history (and to the store), so the current state is always the target
+//! of the last transition. Queries offer approved methods only, plus research-only
+//! ones on request; the registry suspends a method by itself when new logs put one
+//! of its protected corridors in the hard band.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::EvidenceMeta;
+use crate::shard::ShardSchema;
+use crate::store::StoreError;
+
+/// Where a method stands in review.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleState {
+    /// Registered, not yet reviewed.
+    Proposed,
+    /// Offered to research queries only.
+    ResearchOnly,
+    Approved,
+    /// Withdrawn pending review, manually or by automatic quarantine.
+    Suspended,
+    /// Withdrawn for good; no further transitions.
+    Retired,
+}
+
+impl LifecycleState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LifecycleState::Proposed => "proposed",
+            LifecycleState::ResearchOnly => "research_only",
+            LifecycleState::Approved => "approved",
+            LifecycleState::Suspended => "suspended",
+            LifecycleState::Retired => "retired",
+        }
+    }
+
+    /// Whether a method may move from this state to `to`.
+    pub fn can_transition_to(self, to: LifecycleState) -> bool {
+        use LifecycleState::*;
+        matches!(
+            (self, to),
+            (Proposed, ResearchOnly | Approved | Retired)
+                | (ResearchOnly, Approved | Suspended | Retired)
+                | (Approved, ResearchOnly | Suspended | Retired)
+                | (Suspended, ResearchOnly | Approved | Retired)
+        )
+    }
+
+    /// Whether queries may offer a method in this state; `research` also admits
+    /// research-only methods.
+    pub fn queryable(self, research: bool) -> bool {
+        match self {
+            LifecycleState::Approved => true,
+            LifecycleState::ResearchOnly => research,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for LifecycleState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One change of state, with who made it, when, and why.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LifecycleTransition {
+    pub method_id: String,
+    /// `None` for the entry that registered the method.
+    pub from: Option<LifecycleState>,
+    pub to: LifecycleState,
+    pub reason: String,
+    /// Signer and time; for automatic suspensions, those of the triggering log.
+    pub meta: EvidenceMeta,
+}
+
+impl ShardSchema for LifecycleTransition {
+    const SCHEMA: &'static str = "DeadbugsLifecycleTransition";
+}
+
+/// Current state of one method and how it got there.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MethodLifecycle {
+    pub state: LifecycleState,
+    /// Oldest first; empty for methods stored without history, which count as
+    /// proposed until reviewed.
+    pub history: Vec<LifecycleTransition>,
+}
+
+impl Default for MethodLifecycle {
+    fn default() -> Self {
+        Self {
+            state: LifecycleState::Proposed,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl MethodLifecycle {
+    /// Record a transition that has already been validated.
+    pub fn apply(&mut self, transition: LifecycleTransition) {
+        self.state = transition.to;
+        self.history.push(transition);
+    }
+}
+
+/// Errors raised by lifecycle operations on a registry.
+#[derive(Debug)]
+pub enum LifecycleError {
+    UnknownMethod(String),
+    /// A method with this ID is already registered.
+    DuplicateMethod(String),
+    InvalidTransition {
+        method_id: String,
+        from: LifecycleState,
+        to: LifecycleState,
+    },
+    Store(StoreError),
+}
+
+impl fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LifecycleError::UnknownMethod(id) => write!(f, "unknown method {id}"),
+            LifecycleError::DuplicateMethod(id) => write!(f, "method {id} already registered"),
+            LifecycleError::InvalidTransition {
+                method_id,
+                from,
+                to,
+            } => {
+                write!(f, "method {method_id} cannot move from {from} to {to}")
+            }
+            LifecycleError::Store(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for LifecycleError {}
+
+impl From<StoreError> for LifecycleError {
+    fn from(e: StoreError) -> Self {
+        LifecycleError::Store(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures::meta;
+
+    use LifecycleState::*;
+
+    const STATES: [LifecycleState; 5] = [Proposed, ResearchOnly, Approved, Suspended, Retired];
+
+    #[test]
+    fn transition_table() {
+        let allowed = [
+            (Proposed, ResearchOnly),
+            (Proposed, Approved),
+            (Proposed, Retired),
+            (ResearchOnly, Approved),
+            (ResearchOnly, Suspended),
+            (ResearchOnly, Retired),
+            (Approved, ResearchOnly),
+            (Approved, Suspended),
+            (Approved, Retired),
+            (Suspended, ResearchOnly),
+            (Suspended, Approved),
+            (Suspended, Retired),
+        ];
+        for from in STATES {
+            for to in STATES {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    allowed.contains(&(from, to)),
+                    "{from} -> {to}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn retired_is_final_and_nothing_returns_to_proposed() {
+        assert!(STATES.iter().all(|&to| !Retired.can_transition_to(to)));
+        assert!(STATES.iter().all(|&from| !from.can_transition_to(Proposed)));
+    }
+
+    #[test]
+    fn only_approved_and_research_methods_are_offered() {
+        let offered = |research| -> Vec<LifecycleState> {
+            STATES
+                .into_iter()
+                .filter(|s| s.queryable(research))
+                .collect()
+        };
+        assert_eq!(offered(false), [Approved]);
+        assert_eq!(offered(true), [ResearchOnly, Approved]);
+    }
+
+    #[test]
+    fn state_follows_the_last_transition() {
+        let mut lifecycle = MethodLifecycle::default();
+        assert_eq!(lifecycle.state, Proposed);
+        for (from, to) in [(None, Proposed), (Some(Proposed), Approved)] {
+            lifecycle.apply(LifecycleTransition {
+                method_id: "trap.live".to_string(),
+                from,
+                to,
+                reason: "review".to_string(),
+                meta: meta(0),
+            });
+        }
+        assert_eq!(lifecycle.state, Approved);
+        assert_eq!(lifecycle.history.len(), 2);
+        assert_eq!(Approved.to_string(), "approved");
+        assert_eq!(ResearchOnly.as_str(), "research_only");
+    }
+}
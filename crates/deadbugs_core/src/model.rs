@@ -2,8 +2,12 @@
 
 use std::time::SystemTime;
 
+use serde::{Deserialize, Serialize};
+
+use crate::recency::RecencyConfig;
+
 /// Bostrom / DID metadata for full auditability.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EvidenceMeta {
     /// Primary bostrom address of the operator or shard signer.
     pub bostrom_address: String,
@@ -18,7 +22,7 @@ pub struct EvidenceMeta {
 }
 
 /// Location class where the method is used.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum LocationType {
     Home,
     Restaurant,
@@ -29,7 +33,7 @@ pub enum LocationType {
 }
 
 /// High-level pest category; extensible as needed.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum PestSpecies {
     Rodent,
     Cockroach,
@@ -41,8 +45,21 @@ pub enum PestSpecies {
     Other,
 }
 
+impl PestSpecies {
+    pub const ALL: [PestSpecies; 8] = [
+        PestSpecies::Rodent,
+        PestSpecies::Cockroach,
+        PestSpecies::Fly,
+        PestSpecies::Mosquito,
+        PestSpecies::Termite,
+        PestSpecies::Ant,
+        PestSpecies::StoredProductInsect,
+        PestSpecies::Other,
+    ];
+}
+
 /// Categorical effectiveness band recorded by the operator.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EffectivenessBand {
     Low,
     Medium,
@@ -50,7 +67,7 @@ pub enum EffectivenessBand {
 }
 
 /// Qualitative side-effects observed for a method deployment.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SideEffects {
     /// Non-target animals captured or harmed (counts per deployment).
     pub non_target_kill_count: u32,
@@ -67,7 +84,7 @@ pub struct SideEffects {
 }
 
 /// Tags describing where humans and animals are present.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProximityTags {
     pub children_present: bool,
     pub pets_present: bool,
@@ -77,7 +94,7 @@ pub struct ProximityTags {
 
 /// Physical / behavioral control families.
 /// Explicitly excludes chemicals, toxins, or pathogens.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ControlFamily {
     Exclusion,          // sealing, screening, door sweeps
     Sanitation,         // food/waste/moisture control
@@ -89,7 +106,7 @@ pub enum ControlFamily {
 }
 
 /// Lure type restricted to food-grade / non-toxic materials.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LureType {
     None,
     FoodGradeBait,
@@ -98,7 +115,7 @@ pub enum LureType {
 }
 
 /// Construction for exclusion / proofing.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ExclusionDetails {
     pub sealed_cracks: bool,
     pub door_sweeps_installed: bool,
@@ -108,7 +125,7 @@ pub struct ExclusionDetails {
 }
 
 /// Sanitation / hygiene context.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct HygieneContext {
     pub food_left_out: bool,
     pub open_garbage: bool,
@@ -118,7 +135,7 @@ pub struct HygieneContext {
 }
 
 /// Core definition of a non-toxic control method.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ControlMethod {
     pub id: String,
     pub family: ControlFamily,
@@ -130,10 +147,15 @@ pub struct ControlMethod {
     /// True if persistent plastics are generated (non-biodegradable housings, liners, etc.).
     pub generates_persistent_plastic: bool,
     pub notes: Option<String>,
+    /// When the current design took effect. Incidents logged before it stop counting
+    /// toward the hard tier once they leave the evidence window; without a revision
+    /// they count for good.
+    #[serde(default)]
+    pub revised_at: Option<SystemTime>,
 }
 
 /// Context of a pest problem where the method is deployed.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PestContext {
     pub location_type: LocationType,
     pub pest: PestSpecies,
@@ -146,7 +168,7 @@ pub struct PestContext {
 }
 
 /// Outcome log for a single deployment instance of a method.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OutcomeLog {
     pub method_id: String,
     pub context: PestContext,
@@ -157,21 +179,192 @@ pub struct OutcomeLog {
     /// Duration of observation window in days.
     pub observation_days: u32,
     pub meta: EvidenceMeta,
+    /// Recommendation this deployment follows up, if any.
+    #[serde(default)]
+    pub recommendation_id: Option<String>,
 }
 
 /// Normalized risk coordinates for a method under a given corpus of logs.
 /// All values are in [0, 1] as in your corridor grammar.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct RiskCoordinates {
     pub r_pets: f64,
     pub r_wildlife: f64,
     pub r_waste: f64,
     pub r_air: f64,
     pub r_human_injury: f64,
+    /// Chance of at least one non-target kill over a reference deployment.
+    #[serde(default)]
+    pub r_bycatch: f64,
+}
+
+impl RiskCoordinates {
+    /// Every coordinate at 1.0, the upper bound when nothing is known.
+    pub fn ceiling() -> Self {
+        Self {
+            r_pets: 1.0,
+            r_wildlife: 1.0,
+            r_waste: 1.0,
+            r_air: 1.0,
+            r_human_injury: 1.0,
+            r_bycatch: 1.0,
+        }
+    }
+}
+
+/// One risk corridor (a coordinate of `RiskCoordinates`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Corridor {
+    Pets,
+    Wildlife,
+    Waste,
+    Air,
+    HumanInjury,
+    Bycatch,
+}
+
+impl Corridor {
+    pub const ALL: [Corridor; 6] = [
+        Corridor::Pets,
+        Corridor::Wildlife,
+        Corridor::Waste,
+        Corridor::Air,
+        Corridor::HumanInjury,
+        Corridor::Bycatch,
+    ];
+
+    /// Corridors whose hard band disallows a method outright.
+    pub const PROTECTED: [Corridor; 4] = [
+        Corridor::Pets,
+        Corridor::HumanInjury,
+        Corridor::Wildlife,
+        Corridor::Bycatch,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Corridor::Pets => "pets",
+            Corridor::Wildlife => "wildlife",
+            Corridor::Waste => "waste",
+            Corridor::Air => "air",
+            Corridor::HumanInjury => "human_injury",
+            Corridor::Bycatch => "bycatch",
+        }
+    }
+}
+
+impl RiskCoordinates {
+    pub fn get(&self, corridor: Corridor) -> f64 {
+        match corridor {
+            Corridor::Pets => self.r_pets,
+            Corridor::Wildlife => self.r_wildlife,
+            Corridor::Waste => self.r_waste,
+            Corridor::Air => self.r_air,
+            Corridor::HumanInjury => self.r_human_injury,
+            Corridor::Bycatch => self.r_bycatch,
+        }
+    }
+}
+
+/// Corridor band a risk coordinate falls in, from least to most severe.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum CorridorBand {
+    /// Below the gold threshold: no action needed.
+    #[default]
+    Safe,
+    /// Between gold and hard: admissible but flagged for review.
+    Gold,
+    /// At or above the hard threshold.
+    Hard,
+}
+
+/// Band per risk coordinate, mirroring `RiskCoordinates`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CorridorBands {
+    pub pets: CorridorBand,
+    pub wildlife: CorridorBand,
+    pub waste: CorridorBand,
+    pub air: CorridorBand,
+    pub human_injury: CorridorBand,
+    #[serde(default)]
+    pub bycatch: CorridorBand,
+}
+
+impl CorridorBands {
+    pub fn get(&self, corridor: Corridor) -> CorridorBand {
+        match corridor {
+            Corridor::Pets => self.pets,
+            Corridor::Wildlife => self.wildlife,
+            Corridor::Waste => self.waste,
+            Corridor::Air => self.air,
+            Corridor::HumanInjury => self.human_injury,
+            Corridor::Bycatch => self.bycatch,
+        }
+    }
+
+    /// Most severe band across all corridors.
+    pub fn worst(&self) -> CorridorBand {
+        [
+            self.pets,
+            self.wildlife,
+            self.waste,
+            self.air,
+            self.human_injury,
+            self.bycatch,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or_default()
+    }
+}
+
+/// Which slice of the log corpus a score was computed from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Stratum {
+    /// Same location type and proximity tags as the query.
+    LocationProximity,
+    /// Same location type.
+    Location,
+    /// Every log for the method and pest.
+    #[default]
+    Pooled,
+}
+
+/// Objective, rate-based effectiveness computed alongside the operator's bands.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CaptureEffectiveness {
+    /// Target captures per observation day, pooled over all logs.
+    pub captures_per_day: f64,
+    /// Mean capture-rate decline from first to last log per site, in [0,1];
+    /// `None` when no site has a usable series.
+    pub decline: Option<f64>,
+    /// Follow-up logs with both an objective and a subjective class.
+    pub paired_logs: u32,
+    /// Paired logs whose objective class matches the operator's band.
+    pub agreeing_logs: u32,
+}
+
+/// Equal-tailed posterior credible interval.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CredibleInterval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl CredibleInterval {
+    /// [0,1]: no information, for scores recorded before intervals existed.
+    pub fn unit() -> Self {
+        Self {
+            lower: 0.0,
+            upper: 1.0,
+        }
+    }
 }
 
 /// Aggregated K/E/R scores for a method.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct KerScore {
     /// Knowledge-factor K in [0,1].
     pub k: f64,
@@ -179,8 +372,111 @@ pub struct KerScore {
     pub e: f64,
     /// Risk-of-harm R in [0,1].
     pub r: f64,
-    /// Normalized risk coordinates for corridors.
+    /// Credible bounds on K (after the capture-agreement adjustment).
+    #[serde(default = "CredibleInterval::unit")]
+    pub k_interval: CredibleInterval,
+    /// Credible bounds on R (corridor weights applied to the coordinate bounds).
+    #[serde(default = "CredibleInterval::unit")]
+    pub r_interval: CredibleInterval,
+    /// Kish effective sample size of the recency-weighted logs.
+    #[serde(default)]
+    pub n_effective: f64,
+    /// Rate-based effectiveness from capture counts.
+    #[serde(default)]
+    pub capture: CaptureEffectiveness,
+    /// Normalized risk coordinates for corridors (observed frequencies).
     pub coords: RiskCoordinates,
-    /// Hard corridor flag: true if any protected corridor reached 1.0.
+    /// Lower credible bound of each risk coordinate.
+    #[serde(default)]
+    pub coords_lower: RiskCoordinates,
+    /// Upper credible bound of each risk coordinate.
+    #[serde(default = "RiskCoordinates::ceiling")]
+    pub coords_upper: RiskCoordinates,
+    /// Share of deployments with at least one incident per corridor (waste: the
+    /// waste coordinate). Independent of log length and undecayed; logs outside the
+    /// evidence window still count unless `ControlMethod::revised_at` postdates them.
+    /// This is what the bands grade.
+    #[serde(default)]
+    pub incidence: RiskCoordinates,
+    /// Band of each corridor under the configured thresholds; all safe in
+    /// scores recorded before bands existed, where only `hard_violation` holds.
+    #[serde(default)]
+    pub bands: CorridorBands,
+    /// Hard corridor flag: true if any protected corridor (pets, human injury,
+    /// wildlife, bycatch) sits in the hard band.
     pub hard_violation: bool,
+    /// Log stratum the score was computed from.
+    #[serde(default)]
+    pub stratum: Stratum,
+    /// `KerConfig::config_id` the score was computed under; empty if unrecorded.
+    #[serde(default)]
+    pub config_id: String,
+    /// Recency weighting the score was computed under, with `as_of` set to the time
+    /// log ages were measured from; `None` if unrecorded.
+    #[serde(default)]
+    pub recency: Option<RecencyConfig>,
+}
+
+/// Model values shared by the unit tests of every module.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    pub const DAY: u64 = 86_400;
+
+    /// Signer `bostrom{n % 3}` at cell `PHX-{n % 2}`, `n` days after a fixed epoch.
+    pub fn meta(n: u64) -> EvidenceMeta {
+        EvidenceMeta {
+            bostrom_address: format!("bostrom{}", n % 3),
+            alt_address: None,
+            hex_stamp: format!("0x{n:04x}"),
+            location_cell: format!("PHX-{}", n % 2),
+            timestamp: UNIX_EPOCH + Duration::new(1_700_000_000 + n * DAY, 5),
+        }
+    }
+
+    pub fn method(id: &str, family: ControlFamily) -> ControlMethod {
+        ControlMethod {
+            id: id.to_string(),
+            family,
+            trap_type: None,
+            lure_type: LureType::None,
+            exclusion: None,
+            uses_disposable_electronics: false,
+            generates_persistent_plastic: false,
+            notes: None,
+            revised_at: None,
+        }
+    }
+
+    pub fn context(location_type: LocationType) -> PestContext {
+        PestContext {
+            location_type,
+            pest: PestSpecies::Rodent,
+            proximity: ProximityTags::default(),
+            hygiene: HygieneContext::default(),
+            building_has_gaps: true,
+            moisture_high: false,
+            food_waste_available: false,
+        }
+    }
+
+    /// A clean, highly effective two-week rodent deployment at a home.
+    pub fn log(method_id: &str, n: u64) -> OutcomeLog {
+        OutcomeLog {
+            method_id: method_id.to_string(),
+            context: context(LocationType::Home),
+            effectiveness: EffectivenessBand::High,
+            side_effects: SideEffects {
+                waste_burden: "low".to_string(),
+                ..Default::default()
+            },
+            target_count: 4,
+            observation_days: 14,
+            meta: meta(n),
+            recommendation_id: None,
+        }
+    }
 }
@@ -0,0 +1,491 @@
+#![forbid(unsafe_code)]
+
+//! Declarative method-eligibility policies.
+//!
+//! An `EligibilityPolicy` is a list of rules, each excluding the methods its
+//! predicate matches. Predicates test family, lure, materials, location,
+//! proximity and site conditions, and compose with `all`, `any` and `not`, so site
+//! rules can be changed through config instead of code. Every exclusion names its
+//! rule. Priority rules use the same predicates to rank matching methods first,
+//! with an E uplift (e.g. moisture control when moisture is high).
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    ControlFamily, ControlMethod, EvidenceMeta, LocationType, LureType, PestContext, ProximityTags,
+};
+use crate::shard::{content_digest, from_json_stamped, to_json, ShardError, ShardSchema};
+
+/// Material properties of a method that rules can test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Material {
+    DisposableElectronics,
+    PersistentPlastic,
+}
+
+/// Presence flags from `ProximityTags`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProximityFlag {
+    ChildrenPresent,
+    PetsPresent,
+    LivestockPresent,
+    WildlifeCorridor,
+}
+
+impl ProximityFlag {
+    fn is_set(self, tags: &ProximityTags) -> bool {
+        match self {
+            ProximityFlag::ChildrenPresent => tags.children_present,
+            ProximityFlag::PetsPresent => tags.pets_present,
+            ProximityFlag::LivestockPresent => tags.livestock_present,
+            ProximityFlag::WildlifeCorridor => tags.wildlife_corridor,
+        }
+    }
+}
+
+/// Site conditions from `PestContext` and its `HygieneContext`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SiteCondition {
+    BuildingHasGaps,
+    MoistureHigh,
+    FoodWasteAvailable,
+    FoodLeftOut,
+    OpenGarbage,
+    StandingWater,
+    OrganicDebris,
+}
+
+/// Where a method would be deployed.
+#[derive(Clone, Debug)]
+pub struct EligibilityContext {
+    pub location: LocationType,
+    /// `None` when unknown; proximity predicates then never match.
+    pub proximity: Option<ProximityTags>,
+    /// Conditions known to hold at the site; empty when unknown.
+    pub conditions: Vec<SiteCondition>,
+}
+
+impl EligibilityContext {
+    pub fn new(location: LocationType, proximity: Option<ProximityTags>) -> Self {
+        Self {
+            location,
+            proximity,
+            conditions: Vec::new(),
+        }
+    }
+
+    /// Location, proximity and every condition set in a full pest context.
+    pub fn from_pest_context(ctx: &PestContext) -> Self {
+        let h = &ctx.hygiene;
+        let conditions = [
+            (ctx.building_has_gaps, SiteCondition::BuildingHasGaps),
+            (ctx.moisture_high, SiteCondition::MoistureHigh),
+            (ctx.food_waste_available, SiteCondition::FoodWasteAvailable),
+            (h.food_left_out, SiteCondition::FoodLeftOut),
+            (h.open_garbage, SiteCondition::OpenGarbage),
+            (h.standing_water, SiteCondition::StandingWater),
+            (h.organic_debris, SiteCondition::OrganicDebris),
+        ]
+        .into_iter()
+        .filter_map(|(set, condition)| set.then_some(condition))
+        .collect();
+        Self {
+            location: ctx.location_type,
+            proximity: Some(ctx.proximity.clone()),
+            conditions,
+        }
+    }
+}
+
+/// Condition over a method and its deployment context.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Predicate {
+    /// Method family is one of these.
+    Family(Vec<ControlFamily>),
+    /// Method lure is one of these.
+    Lure(Vec<LureType>),
+    /// Method involves any of these materials.
+    Materials(Vec<Material>),
+    /// Deployment location is one of these.
+    Location(Vec<LocationType>),
+    /// Any of these flags is set at the site.
+    Proximity(Vec<ProximityFlag>),
+    /// Any of these conditions holds at the site.
+    Condition(Vec<SiteCondition>),
+    /// Every sub-predicate holds (true when empty).
+    All(Vec<Predicate>),
+    /// Some sub-predicate holds (false when empty).
+    Any(Vec<Predicate>),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    pub fn matches(&self, method: &ControlMethod, ctx: &EligibilityContext) -> bool {
+        match self {
+            Predicate::Family(families) => families.contains(&method.family),
+            Predicate::Lure(lures) => lures.contains(&method.lure_type),
+            Predicate::Materials(materials) => materials.iter().any(|m| match m {
+                Material::DisposableElectronics => method.uses_disposable_electronics,
+                Material::PersistentPlastic => method.generates_persistent_plastic,
+            }),
+            Predicate::Location(locations) => locations.contains(&ctx.location),
+            Predicate::Proximity(flags) => ctx
+                .proximity
+                .as_ref()
+                .is_some_and(|tags| flags.iter().any(|f| f.is_set(tags))),
+            Predicate::Condition(conditions) => {
+                conditions.iter().any(|c| ctx.conditions.contains(c))
+            }
+            Predicate::All(preds) => preds.iter().all(|p| p.matches(method, ctx)),
+            Predicate::Any(preds) => preds.iter().any(|p| p.matches(method, ctx)),
+            Predicate::Not(pred) => !pred.matches(method, ctx),
+        }
+    }
+}
+
+/// One exclusion rule.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EligibilityRule {
+    pub id: String,
+    /// Human-readable reason reported with each exclusion.
+    pub reason: String,
+    /// Methods matching this predicate are ineligible.
+    pub exclude_when: Predicate,
+}
+
+/// Methods a site calls for first; queries rank them ahead of the rest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PriorityRule {
+    pub id: String,
+    pub reason: String,
+    /// Methods matching this predicate get the uplift.
+    pub when: Predicate,
+    pub e_uplift: f64,
+}
+
+/// A method excluded by a rule.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Exclusion {
+    pub rule: String,
+    pub reason: String,
+}
+
+/// Exclusion rules plus context priorities.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EligibilityPolicy {
+    /// Identifier recorded with query results (e.g., "deadbugs-eligibility-default-v2").
+    pub policy_id: String,
+    pub rules: Vec<EligibilityRule>,
+    /// Context priorities; uplifts from every matching rule add up.
+    #[serde(default)]
+    pub priorities: Vec<PriorityRule>,
+}
+
+impl Default for EligibilityPolicy {
+    fn default() -> Self {
+        Self {
+            policy_id: "deadbugs-eligibility-default-v2".to_string(),
+            rules: vec![
+                EligibilityRule {
+                    id: "sensitive-location-materials".to_string(),
+                    reason:
+                        "no disposable electronics or persistent plastics in homes or hospitals"
+                            .to_string(),
+                    exclude_when: Predicate::All(vec![
+                        Predicate::Location(vec![LocationType::Home, LocationType::Hospital]),
+                        Predicate::Materials(vec![
+                            Material::DisposableElectronics,
+                            Material::PersistentPlastic,
+                        ]),
+                    ]),
+                },
+                EligibilityRule {
+                    id: "children-lethal-traps".to_string(),
+                    reason: "no lethal mechanical traps where children are present".to_string(),
+                    exclude_when: Predicate::All(vec![
+                        Predicate::Family(vec![ControlFamily::MechanicalKill]),
+                        Predicate::Proximity(vec![ProximityFlag::ChildrenPresent]),
+                    ]),
+                },
+            ],
+            priorities: vec![PriorityRule {
+                id: "moisture-control".to_string(),
+                reason: "moisture control first where moisture is high".to_string(),
+                when: Predicate::All(vec![
+                    Predicate::Condition(vec![SiteCondition::MoistureHigh]),
+                    Predicate::Family(vec![
+                        ControlFamily::Sanitation,
+                        ControlFamily::HabitatChange,
+                    ]),
+                ]),
+                e_uplift: 0.1,
+            }],
+        }
+    }
+}
+
+impl EligibilityPolicy {
+    /// A policy that admits every method.
+    pub fn permissive() -> Self {
+        Self {
+            policy_id: "deadbugs-eligibility-permissive".to_string(),
+            rules: Vec::new(),
+            priorities: Vec::new(),
+        }
+    }
+
+    /// Every rule that excludes `method` in `ctx`; empty when it is eligible.
+    pub fn exclusions(&self, method: &ControlMethod, ctx: &EligibilityContext) -> Vec<Exclusion> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.exclude_when.matches(method, ctx))
+            .map(|rule| Exclusion {
+                rule: rule.id.clone(),
+                reason: rule.reason.clone(),
+            })
+            .collect()
+    }
+
+    /// Priority rules that apply to `method` in `ctx`.
+    pub fn priorities(
+        &self,
+        method: &ControlMethod,
+        ctx: &EligibilityContext,
+    ) -> Vec<&PriorityRule> {
+        self.priorities
+            .iter()
+            .filter(|rule| rule.when.matches(method, ctx))
+            .collect()
+    }
+
+    /// Load a bare policy from a JSON file (no checksum; for local overrides and tests).
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, ShardError> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Eligibility policy as published by a governance body, with its publisher
+/// metadata. `meta.hex_stamp` is an integrity checksum of the policy, not a
+/// signature; it does not prove who published it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EligibilityPolicyShard {
+    pub policy: EligibilityPolicy,
+    pub meta: EvidenceMeta,
+}
+
+impl ShardSchema for EligibilityPolicyShard {
+    const SCHEMA: &'static str = "DeadbugsEligibilityPolicy";
+}
+
+impl EligibilityPolicyShard {
+    /// Stamp `meta.hex_stamp` with the content digest of `policy`.
+    pub fn new(policy: EligibilityPolicy, mut meta: EvidenceMeta) -> Result<Self, ShardError> {
+        meta.hex_stamp = content_digest(&policy)?;
+        Ok(Self { policy, meta })
+    }
+
+    /// Write the shard as a single-record JSON file.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), ShardError> {
+        fs::write(path, to_json(std::slice::from_ref(self))?)?;
+        Ok(())
+    }
+
+    /// Load the single policy record of a JSON shard file, rejecting it unless
+    /// `meta.hex_stamp` matches the policy's content digest.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ShardError> {
+        from_json_stamped(&fs::read_to_string(path)?, "policy")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures::{context, meta, method};
+
+    fn scratch_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "deadbugs-policy-{name}-{}.json",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn policy_shard_round_trips_with_its_stamp() {
+        let path = scratch_file("round-trip");
+        let shard = EligibilityPolicyShard::new(EligibilityPolicy::default(), meta(2)).unwrap();
+        shard.save_json(&path).unwrap();
+        let loaded = EligibilityPolicyShard::load(&path).unwrap();
+        assert_eq!(loaded.policy.policy_id, shard.policy.policy_id);
+        assert_eq!(loaded.policy.rules.len(), shard.policy.rules.len());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn policy_shard_rejects_a_dropped_rule() {
+        let path = scratch_file("tampered");
+        let shard = EligibilityPolicyShard::new(EligibilityPolicy::default(), meta(2)).unwrap();
+        let mut edited = shard.clone();
+        edited.policy.rules.pop();
+        fs::write(&path, to_json(&[edited]).unwrap()).unwrap();
+        assert!(matches!(
+            EligibilityPolicyShard::load(&path),
+            Err(ShardError::Integrity { .. })
+        ));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn policy_shard_reports_an_empty_file() {
+        let path = scratch_file("empty");
+        fs::write(&path, to_json::<EligibilityPolicyShard>(&[]).unwrap()).unwrap();
+        assert!(matches!(
+            EligibilityPolicyShard::load(&path),
+            Err(ShardError::RecordCount { found: 0, .. })
+        ));
+        let _ = fs::remove_file(path);
+    }
+
+    fn site(location: LocationType) -> EligibilityContext {
+        EligibilityContext::new(location, Some(ProximityTags::default()))
+    }
+
+    fn rule_ids(exclusions: &[Exclusion]) -> Vec<&str> {
+        exclusions.iter().map(|x| x.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn pest_context_conditions_reach_the_rules() {
+        let mut ctx = context(LocationType::Restaurant);
+        ctx.moisture_high = true;
+        ctx.hygiene.open_garbage = true;
+        ctx.proximity.pets_present = true;
+        let site = EligibilityContext::from_pest_context(&ctx);
+        assert_eq!(site.location, LocationType::Restaurant);
+        assert_eq!(
+            site.conditions,
+            [
+                SiteCondition::BuildingHasGaps,
+                SiteCondition::MoistureHigh,
+                SiteCondition::OpenGarbage
+            ]
+        );
+        assert!(site.proximity.is_some_and(|p| p.pets_present));
+    }
+
+    #[test]
+    fn children_bar_lethal_traps_only() {
+        let policy = EligibilityPolicy::default();
+        let snap = method("trap.snap", ControlFamily::MechanicalKill);
+        let live = method("trap.live", ControlFamily::LiveCapture);
+        let mut children = site(LocationType::Farm);
+        children.proximity.as_mut().unwrap().children_present = true;
+
+        assert_eq!(
+            rule_ids(&policy.exclusions(&snap, &children)),
+            ["children-lethal-traps"]
+        );
+        assert!(policy.exclusions(&live, &children).is_empty());
+        assert!(policy
+            .exclusions(&snap, &site(LocationType::Farm))
+            .is_empty());
+        // Unknown proximity matches no proximity predicate.
+        let unknown = EligibilityContext::new(LocationType::Farm, None);
+        assert!(policy.exclusions(&snap, &unknown).is_empty());
+    }
+
+    #[test]
+    fn materials_are_barred_at_sensitive_locations() {
+        let policy = EligibilityPolicy::default();
+        let mut camera = method("sensor.cam", ControlFamily::MonitoringOnly);
+        camera.uses_disposable_electronics = true;
+        for location in [LocationType::Home, LocationType::Hospital] {
+            assert_eq!(
+                rule_ids(&policy.exclusions(&camera, &site(location))),
+                ["sensitive-location-materials"]
+            );
+        }
+        assert!(policy
+            .exclusions(&camera, &site(LocationType::Farm))
+            .is_empty());
+        // Monitoring itself is eligible everywhere.
+        let plain = method("sensor.plain", ControlFamily::MonitoringOnly);
+        assert!(policy
+            .exclusions(&plain, &site(LocationType::Home))
+            .is_empty());
+        assert!(EligibilityPolicy::permissive()
+            .exclusions(&camera, &site(LocationType::Home))
+            .is_empty());
+    }
+
+    #[test]
+    fn moisture_prioritizes_sanitation_and_habitat_change() {
+        let policy = EligibilityPolicy::default();
+        let mut wet = context(LocationType::Home);
+        wet.moisture_high = true;
+        let wet = EligibilityContext::from_pest_context(&wet);
+        let dry = EligibilityContext::from_pest_context(&context(LocationType::Home));
+        for family in [ControlFamily::Sanitation, ControlFamily::HabitatChange] {
+            let m = method("dry.out", family);
+            let matched = policy.priorities(&m, &wet);
+            assert_eq!(matched.len(), 1);
+            assert_eq!(matched[0].id, "moisture-control");
+            assert!(policy.priorities(&m, &dry).is_empty());
+        }
+        let seal = method("exclusion.seal", ControlFamily::Exclusion);
+        assert!(policy.priorities(&seal, &wet).is_empty());
+    }
+
+    #[test]
+    fn predicates_compose() {
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let ctx = site(LocationType::Home);
+        let live = Predicate::Family(vec![ControlFamily::LiveCapture]);
+        assert!(Predicate::All(Vec::new()).matches(&m, &ctx));
+        assert!(!Predicate::Any(Vec::new()).matches(&m, &ctx));
+        assert!(!Predicate::Not(Box::new(live.clone())).matches(&m, &ctx));
+        let farm = Predicate::Location(vec![LocationType::Farm]);
+        assert!(!Predicate::All(vec![live.clone(), farm.clone()]).matches(&m, &ctx));
+        assert!(Predicate::Any(vec![live, farm]).matches(&m, &ctx));
+        assert!(Predicate::Lure(vec![LureType::None]).matches(&m, &ctx));
+    }
+
+    #[test]
+    fn policies_load_from_json() {
+        let path = scratch_file("bare");
+        fs::write(
+            &path,
+            r#"{
+                "policy_id": "site-no-live-traps",
+                "rules": [{
+                    "id": "no-live-traps",
+                    "reason": "site bans live capture",
+                    "exclude_when": {"all": [
+                        {"family": ["LiveCapture"]},
+                        {"not": {"location": ["Farm"]}}
+                    ]}
+                }]
+            }"#,
+        )
+        .unwrap();
+        let policy = EligibilityPolicy::load_json(&path).unwrap();
+        assert!(policy.priorities.is_empty());
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        assert_eq!(
+            policy.exclusions(&m, &site(LocationType::Home)),
+            [Exclusion {
+                rule: "no-live-traps".to_string(),
+                reason: "site bans live capture".to_string(),
+            }]
+        );
+        assert!(policy.exclusions(&m, &site(LocationType::Farm)).is_empty());
+        let _ = fs::remove_file(path);
+    }
+}
@@ -1,15 +1,149 @@
 #![forbid(unsafe_code)]
 
-use crate::ker::score_method;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::explain::{KerExplanation, Rejection};
+use crate::feedback::{feedback_report, predicted_success, MethodFeedback, RecommendationRecord};
+use crate::ker::KerConfig;
+use crate::lifecycle::{LifecycleError, LifecycleState, LifecycleTransition, MethodLifecycle};
 use crate::model::{
-    ControlFamily, ControlMethod, KerScore, LocationType, OutcomeLog, PestContext, PestSpecies,
+    ControlFamily, ControlMethod, Corridor, CorridorBand, EvidenceMeta, KerScore, LocationType,
+    OutcomeLog, PestContext, PestSpecies, ProximityTags, Stratum,
 };
+use crate::policy::{EligibilityContext, EligibilityPolicy, Exclusion};
+use crate::rank::{pareto_tiers, Objectives, Ranking};
+use crate::scoring::{method_disagreement, ModelComparison, ScoringModel};
+use crate::screen::{AnomalyReason, EvidenceScreen, QuarantinedLog, Screening, ScreeningConfig};
+use crate::snapshot::RegistrySnapshot;
+use crate::store::{RegistryStore, StoreError};
+use crate::strata::{explain_method_in_context, score_method_in_context, ScoringContext};
+
+/// How a store-backed registry holds its log corpus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadMode {
+    /// Load every log into memory at startup.
+    Resident,
+    /// Keep logs on disk and read only the ones each query needs.
+    OnDemand,
+}
+
+/// Tier-0 E uplift given to exclusion and sanitation methods in queries.
+pub const TIER0_E_UPLIFT: f64 = 0.05;
+
+/// Which end of the R posterior the `max_r` ceiling is checked against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RiskBound {
+    /// Posterior mean R.
+    Mean,
+    /// Upper credible bound of R (pessimistic; thin evidence is penalized).
+    #[default]
+    Upper,
+}
+
+/// Filters applied by `query_safest_methods_with`.
+#[derive(Clone, Debug)]
+pub struct QueryOptions {
+    /// Ceiling on R; methods above it are dropped.
+    pub max_r: f64,
+    pub risk_bound: RiskBound,
+    /// Who is present at the site; narrows scoring to logs from similar sites.
+    pub proximity: Option<ProximityTags>,
+    /// Order of the methods that pass the filters.
+    pub ranking: Ranking,
+    /// Also offer research-only methods; otherwise only approved ones.
+    pub research: bool,
+}
+
+impl QueryOptions {
+    pub fn new(max_r: f64) -> Self {
+        Self {
+            max_r,
+            risk_bound: RiskBound::default(),
+            proximity: None,
+            ranking: Ranking::default(),
+            research: false,
+        }
+    }
+
+    /// R value compared against `max_r` under the selected bound.
+    pub fn filtered_r(&self, ker: &KerScore) -> f64 {
+        match self.risk_bound {
+            RiskBound::Mean => ker.r,
+            RiskBound::Upper => ker.r_interval.upper,
+        }
+    }
+}
 
-/// In-memory registry; in production this would be backed by qpudatashards.
+/// A method that passed a query's filters, with its place in the ranking.
+#[derive(Clone, Debug, Serialize)]
+pub struct RankedMethod {
+    pub method: ControlMethod,
+    pub ker: KerScore,
+    /// (K, E, 1 − R) with R under the query's `risk_bound`.
+    pub objectives: Objectives,
+    /// Pareto tier among the accepted methods; 0 is the frontier.
+    pub tier: usize,
+    /// Weighted scalarization, under `Ranking::Weighted` only.
+    pub scalar: Option<f64>,
+    /// Context priority rules that matched; prioritized methods rank first.
+    pub priorities: Vec<String>,
+    /// Eligibility policy the query applied.
+    pub policy_id: String,
+}
+
+/// Per-(method, pest) posting lists into the resident log vector.
+#[derive(Default)]
+struct LogBucket {
+    all: Vec<usize>,
+    by_location: HashMap<LocationType, Vec<usize>>,
+}
+
+/// Where a new incident may have put a method in the hard band, and the log that
+/// signs the suspension if it did.
+struct HardBandCheck {
+    method_id: String,
+    pest: PestSpecies,
+    location: LocationType,
+    meta: EvidenceMeta,
+}
+
+impl HardBandCheck {
+    fn same_scope(&self, other: &HardBandCheck) -> bool {
+        self.method_id == other.method_id
+            && self.pest == other.pest
+            && self.location == other.location
+    }
+}
+
+/// Method/log registry; in-memory by default, optionally backed by a durable store.
 #[derive(Default)]
 pub struct MethodRegistry {
-    pub methods: Vec<ControlMethod>,
-    pub logs: Vec<OutcomeLog>,
+    /// Registered methods in insertion order; add through `add_method`.
+    methods: Vec<ControlMethod>,
+    /// Resident log corpus; append through `add_log` so the index stays in sync.
+    logs: Vec<OutcomeLog>,
+    /// method_id → pest → positions in `logs`.
+    index: HashMap<String, HashMap<PestSpecies, LogBucket>>,
+    /// Durable backend; `None` keeps the registry purely in memory.
+    store: Option<Box<dyn RegistryStore>>,
+    /// True when logs live only in `store` and are fetched per query.
+    logs_on_demand: bool,
+    /// Scoring constants applied by every query.
+    config: KerConfig,
+    /// Approved alternative scorer; `None` uses the core scorer with `config`.
+    model: Option<Box<dyn ScoringModel>>,
+    /// Rules deciding which methods a query may offer at all.
+    policy: EligibilityPolicy,
+    /// Recommendations made so far, in insertion order.
+    recommendations: Vec<RecommendationRecord>,
+    /// method_id → lifecycle; methods without an entry count as proposed.
+    lifecycle: HashMap<String, MethodLifecycle>,
+    /// Checks every log `add_log` receives; seeded with the logs already held.
+    screen: EvidenceScreen,
 }
 
 impl MethodRegistry {
@@ -17,80 +151,832 @@ impl MethodRegistry {
         Self {
             methods: Vec::new(),
             logs: Vec::new(),
+            index: HashMap::new(),
+            store: None,
+            logs_on_demand: false,
+            config: KerConfig::default(),
+            model: None,
+            policy: EligibilityPolicy::default(),
+            recommendations: Vec::new(),
+            lifecycle: HashMap::new(),
+            screen: EvidenceScreen::default(),
         }
     }
 
-    pub fn add_method(&mut self, method: ControlMethod) {
+    /// Scoring constants currently applied by queries.
+    pub fn config(&self) -> &KerConfig {
+        &self.config
+    }
+
+    /// Replace the scoring constants (e.g. with a governance `KerConfigShard`).
+    /// An approved model stays in place: one that scores under a `KerConfig` is
+    /// rebuilt with `config` (see [`ScoringModel::with_config`]); any other keeps
+    /// scoring as approved, and `config` then applies to snapshots only.
+    pub fn set_config(&mut self, config: KerConfig) {
+        if let Some(model) = self.model.as_ref().and_then(|m| m.with_config(&config)) {
+            self.model = Some(model);
+        }
+        self.config = config;
+    }
+
+    /// Score queries with an approved model instead of the core scorer.
+    pub fn set_model(&mut self, model: Box<dyn ScoringModel>) {
+        self.model = Some(model);
+    }
+
+    /// Eligibility rules currently applied by queries.
+    pub fn policy(&self) -> &EligibilityPolicy {
+        &self.policy
+    }
+
+    /// Replace the eligibility rules (e.g. with a governance `EligibilityPolicyShard`).
+    pub fn set_policy(&mut self, policy: EligibilityPolicy) {
+        self.policy = policy;
+    }
+
+    /// Identifier of the model queries currently score with.
+    pub fn model_id(&self) -> String {
+        match &self.model {
+            Some(model) => model.model_id(),
+            None => self.config.config_id.clone(),
+        }
+    }
+
+    /// Score one method in context with the active model; with `explain`, also
+    /// return a trace if the model provides one.
+    fn score_with_model(
+        &self,
+        method: &ControlMethod,
+        logs: &[&OutcomeLog],
+        ctx: &ScoringContext,
+        explain: bool,
+    ) -> (KerScore, Option<KerExplanation>) {
+        match (&self.model, explain) {
+            (Some(model), true) => match model.explain(method, logs, ctx) {
+                Some((ker, explanation)) => (ker, Some(explanation)),
+                None => (model.score(method, logs, ctx), None),
+            },
+            (Some(model), false) => (model.score(method, logs, ctx), None),
+            (None, true) => {
+                let (ker, explanation) = explain_method_in_context(method, logs, ctx, &self.config);
+                (ker, Some(explanation))
+            }
+            (None, false) => (
+                score_method_in_context(method, logs, ctx, &self.config),
+                None,
+            ),
+        }
+    }
+
+    /// Open a registry over a durable store, loading methods (and logs if resident).
+    pub fn open(store: Box<dyn RegistryStore>, mode: LoadMode) -> Result<Self, StoreError> {
+        let mut registry = Self::new();
+        registry.methods = store.load_methods()?;
+        registry.recommendations = store.load_recommendations()?;
+        for transition in store.load_transitions()? {
+            registry
+                .lifecycle
+                .entry(transition.method_id.clone())
+                .or_default()
+                .apply(transition);
+        }
+        registry.screen.seed_stamps(store.log_stamps()?);
+        for held in store.load_quarantined()? {
+            registry.screen.hold(held.log, held.reasons);
+        }
+        if mode == LoadMode::Resident {
+            for log in store.load_logs()? {
+                registry.push_resident(log);
+            }
+        }
+        registry.store = Some(store);
+        registry.logs_on_demand = mode == LoadMode::OnDemand;
+        Ok(registry)
+    }
+
+    /// Freeze the registry as it stands at `taken_at`. Recency is pinned to
+    /// `taken_at` unless the config already pins it; snapshots score with the core
+    /// scorer under `config`, not with an alternative model.
+    /// Logs are gathered per registered method and pest through `with_logs`, so
+    /// both load modes give the same snapshot; logs for methods that were never
+    /// registered are left out, as no snapshot query could score them.
+    pub fn snapshot(
+        &self,
+        label: &str,
+        taken_at: SystemTime,
+    ) -> Result<RegistrySnapshot, StoreError> {
+        let mut logs = Vec::new();
+        for m in &self.methods {
+            for pest in PestSpecies::ALL {
+                self.with_logs(&m.id, pest, None, |found| {
+                    logs.extend(found.iter().map(|&l| l.clone()))
+                })?;
+            }
+        }
+        let lifecycle = self
+            .methods
+            .iter()
+            .map(|m| (m.id.clone(), self.lifecycle_state(&m.id)))
+            .collect();
+        let mut config = self.config.clone();
+        config.recency.as_of.get_or_insert(taken_at);
+        Ok(RegistrySnapshot::new(
+            label,
+            taken_at,
+            config,
+            self.policy.clone(),
+            self.methods.clone(),
+            lifecycle,
+            logs,
+        )?)
+    }
+
+    /// In-memory registry holding exactly a snapshot's contents.
+    pub fn from_snapshot(snapshot: &RegistrySnapshot) -> Self {
+        let mut registry = Self::new();
+        registry.config = snapshot.config().clone();
+        registry.policy = snapshot.policy().clone();
+        registry.methods = snapshot.methods().to_vec();
+        for (id, &state) in snapshot.lifecycle() {
+            registry.lifecycle.insert(
+                id.clone(),
+                MethodLifecycle {
+                    state,
+                    history: Vec::new(),
+                },
+            );
+        }
+        registry.screen.seed(snapshot.logs());
+        for log in snapshot.logs() {
+            registry.push_resident(log.clone());
+        }
+        registry
+    }
+
+    /// Resident logs in insertion order (empty for on-demand registries).
+    pub fn logs(&self) -> &[OutcomeLog] {
+        &self.logs
+    }
+
+    /// Register a method as proposed; with a store attached, the write is durable
+    /// before this returns. Queries offer it only once it is approved.
+    pub fn add_method(
+        &mut self,
+        method: ControlMethod,
+        meta: EvidenceMeta,
+    ) -> Result<(), LifecycleError> {
+        if self.methods.iter().any(|m| m.id == method.id) {
+            return Err(LifecycleError::DuplicateMethod(method.id));
+        }
+        // Method first: a transition stored without its method would name an
+        // unknown ID, while a method stored without history loads as proposed.
+        if let Some(store) = self.store.as_mut() {
+            store.append_method(&method)?;
+        }
+        let method_id = method.id.clone();
         self.methods.push(method);
+        self.record_transition(LifecycleTransition {
+            method_id,
+            from: None,
+            to: LifecycleState::Proposed,
+            reason: "registered".to_string(),
+            meta,
+        })?;
+        Ok(())
     }
 
-    pub fn add_log(&mut self, log: OutcomeLog) {
-        self.logs.push(log);
+    /// Registered methods in insertion order.
+    pub fn methods(&self) -> &[ControlMethod] {
+        &self.methods
+    }
+
+    /// Lifecycle state of a method; methods without recorded history are proposed.
+    pub fn lifecycle_state(&self, method_id: &str) -> LifecycleState {
+        self.lifecycle
+            .get(method_id)
+            .map_or(LifecycleState::Proposed, |l| l.state)
+    }
+
+    /// State and transition history of a method, if any was recorded.
+    pub fn lifecycle(&self, method_id: &str) -> Option<&MethodLifecycle> {
+        self.lifecycle.get(method_id)
+    }
+
+    /// Move a method to `to`, recording who, when and why.
+    pub fn transition(
+        &mut self,
+        method_id: &str,
+        to: LifecycleState,
+        reason: &str,
+        meta: EvidenceMeta,
+    ) -> Result<(), LifecycleError> {
+        if !self.methods.iter().any(|m| m.id == method_id) {
+            return Err(LifecycleError::UnknownMethod(method_id.to_string()));
+        }
+        let from = self.lifecycle_state(method_id);
+        if !from.can_transition_to(to) {
+            return Err(LifecycleError::InvalidTransition {
+                method_id: method_id.to_string(),
+                from,
+                to,
+            });
+        }
+        self.record_transition(LifecycleTransition {
+            method_id: method_id.to_string(),
+            from: Some(from),
+            to,
+            reason: reason.to_string(),
+            meta,
+        })?;
+        Ok(())
+    }
+
+    fn record_transition(&mut self, transition: LifecycleTransition) -> Result<(), StoreError> {
+        if let Some(store) = self.store.as_mut() {
+            store.append_transition(&transition)?;
+        }
+        self.lifecycle
+            .entry(transition.method_id.clone())
+            .or_default()
+            .apply(transition);
+        Ok(())
+    }
+
+    /// Screening thresholds applied by `add_log`.
+    pub fn screening(&self) -> &ScreeningConfig {
+        self.screen.config()
+    }
+
+    /// Replace the screening thresholds. The screen is rebuilt from the logs the
+    /// registry holds; logs already in quarantine stay there.
+    pub fn set_screening(&mut self, cfg: ScreeningConfig) -> Result<(), StoreError> {
+        let mut screen = EvidenceScreen::new(cfg);
+        match self.store.as_ref() {
+            Some(store) if self.logs_on_demand => screen.seed_stamps(store.log_stamps()?),
+            _ => screen.seed(&self.logs),
+        }
+        for held in self.screen.take_quarantined() {
+            screen.hold(held.log, held.reasons);
+        }
+        self.screen = screen;
+        Ok(())
     }
 
-    /// Get all logs for a given method ID and pest.
-    fn logs_for_method(&self, method_id: &str, pest: PestSpecies) -> Vec<OutcomeLog> {
-        self.logs
+    /// Logs `add_log` held back, oldest first, including those held before a
+    /// store was reopened.
+    pub fn quarantined_logs(&self) -> &[QuarantinedLog] {
+        self.screen.quarantined()
+    }
+
+    /// Hand over the held-back logs (e.g. for review), leaving the quarantine empty;
+    /// with a store attached, the release is durable before this returns. Replay
+    /// the logs that pass review through `add_log_unscreened`.
+    pub fn take_quarantined_logs(&mut self) -> Result<Vec<QuarantinedLog>, StoreError> {
+        if let Some(store) = self.store.as_mut() {
+            store.release_quarantined()?;
+        }
+        Ok(self.screen.take_quarantined())
+    }
+
+    /// Screen a log and record it unless the screen quarantines it; with a store
+    /// attached, the write is durable before this returns. Quarantined logs are
+    /// held in `quarantined_logs` (and the store's quarantine) and never reach the
+    /// log corpus or the scores. If the new evidence puts one of the method's
+    /// protected corridors in the hard band, an approved or research-only method
+    /// is suspended.
+    pub fn add_log(&mut self, log: OutcomeLog) -> Result<Screening, StoreError> {
+        Ok(self.add_logs([log])?.remove(0))
+    }
+
+    /// `add_log` for a batch: each log is screened and stored in order, then the
+    /// hard band is checked once per method, pest and location the batch touched,
+    /// signed by the last log that reported an incident there. Use it for bulk
+    /// imports, which would otherwise rescore a method once per log. If a write
+    /// fails, the logs stored before it are still checked.
+    pub fn add_logs(
+        &mut self,
+        logs: impl IntoIterator<Item = OutcomeLog>,
+    ) -> Result<Vec<Screening>, StoreError> {
+        let now = SystemTime::now();
+        let mut screenings = Vec::new();
+        let mut checks: Vec<HardBandCheck> = Vec::new();
+        let mut failure = None;
+        for log in logs {
+            let screening = self.screen.check_at(&log, now);
+            if screening.quarantined() {
+                if let Err(e) = self.hold(log, screening.reasons.clone()) {
+                    failure = Some(e);
+                    break;
+                }
+            } else {
+                match self.admit(log) {
+                    Ok(Some(check)) => match checks.iter_mut().find(|c| c.same_scope(&check)) {
+                        Some(pending) => *pending = check,
+                        None => checks.push(check),
+                    },
+                    Ok(None) => {}
+                    Err(e) => {
+                        failure = Some(e);
+                        break;
+                    }
+                }
+            }
+            screenings.push(screening);
+        }
+        for check in checks {
+            self.quarantine_on_hard_band(check)?;
+        }
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(screenings),
+        }
+    }
+
+    /// Record a log without screening it, for imports already vetted elsewhere
+    /// (e.g. replaying a reviewed quarantine). The log still enters the screen's
+    /// history, and the hard-band quarantine still applies.
+    pub fn add_log_unscreened(&mut self, log: OutcomeLog) -> Result<(), StoreError> {
+        match self.admit(log)? {
+            Some(check) => self.quarantine_on_hard_band(check),
+            None => Ok(()),
+        }
+    }
+
+    /// Quarantine a log, durably first when a store is attached.
+    fn hold(&mut self, log: OutcomeLog, reasons: Vec<AnomalyReason>) -> Result<(), StoreError> {
+        let held = QuarantinedLog { log, reasons };
+        if let Some(store) = self.store.as_mut() {
+            store.append_quarantined(&held)?;
+        }
+        self.screen.hold(held.log, held.reasons);
+        Ok(())
+    }
+
+    /// Store an admitted log and index it. Returns the hard-band check it calls
+    /// for: only a log reporting a protected-corridor incident can raise a band.
+    fn admit(&mut self, log: OutcomeLog) -> Result<Option<HardBandCheck>, StoreError> {
+        if let Some(store) = self.store.as_mut() {
+            store.append_log(&log)?;
+        }
+        self.screen.record(&log);
+        let s = &log.side_effects;
+        let check = (s.pet_incident
+            || s.wildlife_incident
+            || s.human_injury
+            || s.non_target_kill_count > 0)
+            .then(|| HardBandCheck {
+                method_id: log.method_id.clone(),
+                pest: log.context.pest,
+                location: log.context.location_type,
+                meta: log.meta.clone(),
+            });
+        if !self.logs_on_demand {
+            self.push_resident(log);
+        }
+        Ok(check)
+    }
+
+    /// Suspend an offered method whose score in the check's scope has a protected
+    /// corridor in the hard band.
+    fn quarantine_on_hard_band(&mut self, check: HardBandCheck) -> Result<(), StoreError> {
+        let HardBandCheck {
+            method_id,
+            pest,
+            location,
+            meta,
+        } = check;
+        let state = self.lifecycle_state(&method_id);
+        if !state.queryable(true) {
+            return Ok(());
+        }
+        let Some(method) = self.methods.iter().find(|m| m.id == method_id) else {
+            return Ok(());
+        };
+        let ctx = ScoringContext::new(location);
+        let (ker, _) = self.with_scoring_logs(&method_id, pest, &ctx, |logs| {
+            self.score_with_model(method, logs, &ctx, false)
+        })?;
+        if !ker.hard_violation {
+            return Ok(());
+        }
+        let corridors: Vec<&str> = Corridor::PROTECTED
+            .into_iter()
+            .filter(|&c| ker.bands.get(c) == CorridorBand::Hard)
+            .map(Corridor::as_str)
+            .collect();
+        self.record_transition(LifecycleTransition {
+            method_id,
+            from: Some(state),
+            to: LifecycleState::Suspended,
+            reason: format!(
+                "automatic quarantine: hard band on {}",
+                corridors.join(", ")
+            ),
+            meta,
+        })
+    }
+
+    /// Recommendations in insertion order.
+    pub fn recommendations(&self) -> &[RecommendationRecord] {
+        &self.recommendations
+    }
+
+    /// Store a recommendation record; with a store attached, the write is durable
+    /// before this returns. A record whose ID is already recorded is rejected, so
+    /// follow-up logs always link to a single decision.
+    pub fn add_recommendation(&mut self, record: RecommendationRecord) -> Result<(), StoreError> {
+        if self
+            .recommendations
             .iter()
-            .filter(|l| l.method_id == method_id && l.context.pest == pest)
-            .cloned()
-            .collect()
+            .any(|r| r.recommendation_id == record.recommendation_id)
+        {
+            return Err(StoreError::DuplicateRecommendation(
+                record.recommendation_id,
+            ));
+        }
+        if let Some(store) = self.store.as_mut() {
+            store.append_recommendation(&record)?;
+        }
+        self.recommendations.push(record);
+        Ok(())
+    }
+
+    /// Record that `method_id` was recommended for `context` under `snapshot`
+    /// (as returned by a query). The predicted success rate is taken from the
+    /// logs `snapshot` was scored from: its stratum and recency weighting.
+    /// Returns the recommendation ID that follow-up logs should carry in
+    /// `recommendation_id`.
+    pub fn recommend(
+        &mut self,
+        method_id: &str,
+        context: PestContext,
+        meta: EvidenceMeta,
+        snapshot: KerScore,
+    ) -> Result<String, StoreError> {
+        let ctx = ScoringContext {
+            location: context.location_type,
+            proximity: Some(context.proximity.clone()),
+        };
+        let location = (snapshot.stratum != Stratum::Pooled).then_some(ctx.location);
+        let predicted_success = self.with_logs(method_id, context.pest, location, |logs| {
+            predicted_success(logs, &ctx, &snapshot, &self.config.recency)
+        })?;
+        let record =
+            RecommendationRecord::new(method_id, context, meta, snapshot, predicted_success);
+        let id = record.recommendation_id.clone();
+        self.add_recommendation(record)?;
+        Ok(id)
+    }
+
+    /// Predicted vs realized outcomes for every recommended method. Follow-up logs
+    /// are read through `with_logs` for each recommended method and pest.
+    pub fn feedback_report(&self) -> Result<Vec<MethodFeedback>, StoreError> {
+        let mut scopes: Vec<(&str, PestSpecies)> = Vec::new();
+        for r in &self.recommendations {
+            let scope = (r.method_id.as_str(), r.context.pest);
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        let mut linked = Vec::new();
+        for (method_id, pest) in scopes {
+            self.with_logs(method_id, pest, None, |logs| {
+                linked.extend(
+                    logs.iter()
+                        .filter(|l| l.recommendation_id.is_some())
+                        .map(|&l| l.clone()),
+                )
+            })?;
+        }
+        let linked: Vec<&OutcomeLog> = linked.iter().collect();
+        Ok(feedback_report(&self.recommendations, &linked))
+    }
+
+    fn push_resident(&mut self, log: OutcomeLog) {
+        let pos = self.logs.len();
+        let bucket = self
+            .index
+            .entry(log.method_id.clone())
+            .or_default()
+            .entry(log.context.pest)
+            .or_default();
+        bucket.all.push(pos);
+        bucket
+            .by_location
+            .entry(log.context.location_type)
+            .or_default()
+            .push(pos);
+        self.logs.push(log);
+    }
+
+    /// Run `f` over the logs for a method and pest, optionally narrowed to one location.
+    /// Resident logs are borrowed through the index; on-demand logs are read from the store.
+    pub fn with_logs<R>(
+        &self,
+        method_id: &str,
+        pest: PestSpecies,
+        location: Option<LocationType>,
+        f: impl FnOnce(&[&OutcomeLog]) -> R,
+    ) -> Result<R, StoreError> {
+        if self.logs_on_demand {
+            if let Some(store) = self.store.as_ref() {
+                let loaded = store.logs_for_method(method_id, pest)?;
+                let refs: Vec<&OutcomeLog> = loaded
+                    .iter()
+                    .filter(|l| location.is_none_or(|loc| l.context.location_type == loc))
+                    .collect();
+                return Ok(f(&refs));
+            }
+        }
+
+        let bucket = self
+            .index
+            .get(method_id)
+            .and_then(|by_pest| by_pest.get(&pest));
+        let positions: &[usize] = match (bucket, location) {
+            (Some(b), None) => &b.all,
+            (Some(b), Some(loc)) => b.by_location.get(&loc).map_or(&[], |p| p.as_slice()),
+            (None, _) => &[],
+        };
+        let refs: Vec<&OutcomeLog> = positions.iter().map(|&i| &self.logs[i]).collect();
+        Ok(f(&refs))
+    }
+
+    /// `with_logs` for scoring under `ctx`. The core scorer only pools logs from
+    /// other locations when the location stratum is too thin, so once the location
+    /// index holds enough logs, only those are handed over.
+    fn with_scoring_logs<R>(
+        &self,
+        method_id: &str,
+        pest: PestSpecies,
+        ctx: &ScoringContext,
+        f: impl FnOnce(&[&OutcomeLog]) -> R,
+    ) -> Result<R, StoreError> {
+        let at_location = self
+            .index
+            .get(method_id)
+            .and_then(|by_pest| by_pest.get(&pest))
+            .and_then(|b| b.by_location.get(&ctx.location))
+            .map_or(0, Vec::len);
+        let location = (self.model.is_none()
+            && !self.logs_on_demand
+            && at_location >= self.config.strata.min_logs)
+            .then_some(ctx.location);
+        self.with_logs(method_id, pest, location, f)
     }
 
     /// Query safest high-E methods for a pest and context, filtered by R ceiling and hard invariants.
+    /// The ceiling is checked against the pessimistic (upper credible) R.
     pub fn query_safest_methods(
         &self,
         pest: PestSpecies,
         location: LocationType,
         max_r: f64,
-    ) -> Vec<(ControlMethod, KerScore)> {
-        let mut scored: Vec<(ControlMethod, KerScore)> = self
-            .methods
-            .iter()
-            .filter(|m| {
-                // Virtual-only: ensure we never consider chemical / biocide families.
-                !matches!(m.family, ControlFamily::MonitoringOnly) // monitoring is allowed; example guard kept simple
+    ) -> Result<Vec<(ControlMethod, KerScore)>, StoreError> {
+        self.query_safest_methods_with(pest, location, &QueryOptions::new(max_r))
+    }
+
+    /// `query_safest_methods` with explicit filter options, ordered by `opts.ranking`.
+    pub fn query_safest_methods_with(
+        &self,
+        pest: PestSpecies,
+        location: LocationType,
+        opts: &QueryOptions,
+    ) -> Result<Vec<(ControlMethod, KerScore)>, StoreError> {
+        Ok(self
+            .rank_safest_methods(pest, location, opts)?
+            .into_iter()
+            .map(|ranked| (ranked.method, ranked.ker))
+            .collect())
+    }
+
+    /// `query_safest_methods_with` plus each method's objectives and Pareto tier.
+    pub fn rank_safest_methods(
+        &self,
+        pest: PestSpecies,
+        location: LocationType,
+        opts: &QueryOptions,
+    ) -> Result<Vec<RankedMethod>, StoreError> {
+        let site = EligibilityContext::new(location, opts.proximity.clone());
+        self.rank_at(pest, &site, opts)
+    }
+
+    /// Query for a full pest context: proximity, hygiene and building conditions
+    /// feed the eligibility and priority rules (`opts.proximity` is ignored).
+    pub fn query_safest_methods_in_context(
+        &self,
+        ctx: &PestContext,
+        opts: &QueryOptions,
+    ) -> Result<Vec<(ControlMethod, KerScore)>, StoreError> {
+        Ok(self
+            .rank_safest_methods_in_context(ctx, opts)?
+            .into_iter()
+            .map(|ranked| (ranked.method, ranked.ker))
+            .collect())
+    }
+
+    /// `query_safest_methods_in_context` plus each method's objectives and Pareto tier.
+    pub fn rank_safest_methods_in_context(
+        &self,
+        ctx: &PestContext,
+        opts: &QueryOptions,
+    ) -> Result<Vec<RankedMethod>, StoreError> {
+        self.rank_at(ctx.pest, &EligibilityContext::from_pest_context(ctx), opts)
+    }
+
+    fn rank_at(
+        &self,
+        pest: PestSpecies,
+        site: &EligibilityContext,
+        opts: &QueryOptions,
+    ) -> Result<Vec<RankedMethod>, StoreError> {
+        let accepted: Vec<Candidate<'_>> = self
+            .evaluate(pest, site, opts, false)?
+            .into_iter()
+            .filter(|c| c.rejections.is_empty())
+            .collect();
+        let (order, tiers) = rank_indices(&accepted, opts);
+
+        let mut slots: Vec<Option<Candidate<'_>>> = accepted.into_iter().map(Some).collect();
+        Ok(order
+            .into_iter()
+            .filter_map(|i| {
+                let c = slots[i].take()?;
+                let objectives = Objectives::new(c.ker.k, c.ker.e, opts.filtered_r(&c.ker));
+                let scalar = match opts.ranking {
+                    Ranking::Weighted(w) => Some(w.scalarize(&objectives)),
+                    _ => None,
+                };
+                Some(RankedMethod {
+                    method: c.method.clone(),
+                    ker: c.ker,
+                    objectives,
+                    tier: tiers[i],
+                    scalar,
+                    priorities: c.priorities,
+                    policy_id: self.policy.policy_id.clone(),
+                })
             })
-            .map(|m| {
-                let logs = self.logs_for_method(&m.id, pest);
-                let mut ker = score_method(m, &logs);
+            .collect())
+    }
 
-                // Prioritize exclusion & hygiene: small E uplift in tier-0 domains.
-                if matches!(m.family, ControlFamily::Exclusion | ControlFamily::Sanitation) {
-                    ker.e = (ker.e + 0.05).min(1.0);
-                }
+    /// Explanation traces for every method `query_safest_methods_with` considered:
+    /// accepted methods first in rank order, then rejected ones with their reasons.
+    /// Empty when the active model does not produce traces.
+    pub fn explain_safest_methods(
+        &self,
+        pest: PestSpecies,
+        location: LocationType,
+        opts: &QueryOptions,
+    ) -> Result<Vec<KerExplanation>, StoreError> {
+        let site = EligibilityContext::new(location, opts.proximity.clone());
+        self.explain_at(pest, &site, opts)
+    }
+
+    /// `explain_safest_methods` for a full pest context.
+    pub fn explain_safest_methods_in_context(
+        &self,
+        ctx: &PestContext,
+        opts: &QueryOptions,
+    ) -> Result<Vec<KerExplanation>, StoreError> {
+        self.explain_at(ctx.pest, &EligibilityContext::from_pest_context(ctx), opts)
+    }
+
+    fn explain_at(
+        &self,
+        pest: PestSpecies,
+        site: &EligibilityContext,
+        opts: &QueryOptions,
+    ) -> Result<Vec<KerExplanation>, StoreError> {
+        let (accepted, mut rejected): (Vec<_>, Vec<_>) = self
+            .evaluate(pest, site, opts, true)?
+            .into_iter()
+            .partition(|c| c.rejections.is_empty());
+        rejected.sort_by(|a, b| rank_order(&a.ker, &b.ker));
+
+        let (order, _) = rank_indices(&accepted, opts);
+        let mut slots: Vec<Option<Candidate<'_>>> = accepted.into_iter().map(Some).collect();
+        let ranked = order.into_iter().filter_map(|i| slots[i].take());
 
-                (m.clone(), ker)
+        Ok(ranked
+            .chain(rejected)
+            .filter_map(|c| {
+                c.explanation.map(|mut explanation| {
+                    explanation.rejections = c.rejections;
+                    explanation.policy_id = self.policy.policy_id.clone();
+                    explanation
+                })
             })
-            .filter(|(m, ker)| {
-                // Enforce context-aware risk: disallow methods if R > max_r or if hard_violation.
-                if ker.hard_violation || ker.r > max_r {
-                    return false;
-                }
+            .collect())
+    }
 
-                // Additional guardrails for sensitive locations.
-                match location {
-                    LocationType::Home | LocationType::Hospital => {
-                        // No disposable electronics or persistent plastics in sensitive settings.
-                        !(m.uses_disposable_electronics || m.generates_persistent_plastic)
-                    }
-                    _ => true,
+    /// Score every method and record which filters reject it.
+    /// Without `explain`, methods rejected before scoring are skipped entirely.
+    fn evaluate(
+        &self,
+        pest: PestSpecies,
+        site: &EligibilityContext,
+        opts: &QueryOptions,
+        explain: bool,
+    ) -> Result<Vec<Candidate<'_>>, StoreError> {
+        let ctx = ScoringContext {
+            location: site.location,
+            proximity: site.proximity.clone(),
+        };
+        let mut candidates = Vec::new();
+        for m in &self.methods {
+            let state = self.lifecycle_state(&m.id);
+            let offered = state.queryable(opts.research);
+            let exclusions = self.policy.exclusions(m, site);
+            if (!offered || !exclusions.is_empty()) && !explain {
+                continue;
+            }
+            let mut rejections = Vec::new();
+            if !offered {
+                rejections.push(Rejection::NotOffered { state });
+            }
+            rejections.extend(exclusions.into_iter().map(Rejection::Ineligible));
+
+            let (mut ker, mut explanation) = self.with_scoring_logs(&m.id, pest, &ctx, |logs| {
+                self.score_with_model(m, logs, &ctx, explain)
+            })?;
+
+            // Prioritize exclusion & hygiene: small E uplift in tier-0 domains.
+            if matches!(m.family, ControlFamily::Exclusion | ControlFamily::Sanitation) {
+                ker.e = (ker.e + TIER0_E_UPLIFT).min(1.0);
+                if let Some(explanation) = explanation.as_mut() {
+                    explanation.apply_tier0_uplift(TIER0_E_UPLIFT, ker.e);
                 }
-            })
-            .collect();
+            }
 
-        // Sort by K high → low, then E high → low.
-        scored.sort_by(|(_, a), (_, b)| {
-            b.k
-                .partial_cmp(&a.k)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| {
-                    b.e.partial_cmp(&a.e)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                })
-        });
+            // Context priorities, e.g. moisture control where moisture is high.
+            let matched = self.policy.priorities(m, site);
+            let uplift: f64 = matched.iter().map(|p| p.e_uplift).sum();
+            let priorities: Vec<String> = matched.iter().map(|p| p.id.clone()).collect();
+            if uplift > 0.0 {
+                ker.e = (ker.e + uplift).min(1.0);
+            }
+            if let (Some(explanation), false) = (explanation.as_mut(), priorities.is_empty()) {
+                explanation.apply_context_uplift(priorities.clone(), uplift, ker.e);
+            }
+
+            // Enforce context-aware risk: disallow methods if R > max_r or if hard_violation.
+            if ker.hard_violation {
+                rejections.push(Rejection::HardViolation {
+                    corridors: Corridor::PROTECTED
+                        .into_iter()
+                        .filter(|&c| ker.bands.get(c) == CorridorBand::Hard)
+                        .collect(),
+                });
+            }
+            let r = opts.filtered_r(&ker);
+            if r > opts.max_r {
+                rejections.push(Rejection::RiskCeiling {
+                    r,
+                    bound: opts.risk_bound,
+                    max_r: opts.max_r,
+                });
+            }
+
+            candidates.push(Candidate {
+                method: m,
+                ker,
+                rejections,
+                explanation,
+                priorities,
+            });
+        }
+        Ok(candidates)
+    }
+
+    /// Methods the eligibility policy excludes at `location`, with the reasons.
+    pub fn ineligible_methods(
+        &self,
+        location: LocationType,
+        opts: &QueryOptions,
+    ) -> Vec<(&ControlMethod, Vec<Exclusion>)> {
+        let ctx = EligibilityContext::new(location, opts.proximity.clone());
+        self.methods
+            .iter()
+            .map(|m| (m, self.policy.exclusions(m, &ctx)))
+            .filter(|(_, exclusions)| !exclusions.is_empty())
+            .collect()
+    }
 
-        scored
+    /// Score every registered method under each model and report disagreements.
+    pub fn compare_models(
+        &self,
+        models: &[&dyn ScoringModel],
+        pest: PestSpecies,
+        ctx: &ScoringContext,
+    ) -> Result<ModelComparison, StoreError> {
+        let mut rows = Vec::with_capacity(self.methods.len());
+        for m in &self.methods {
+            rows.push(self.with_logs(&m.id, pest, None, |logs| {
+                method_disagreement(models, m, logs, ctx)
+            })?);
+        }
+        Ok(ModelComparison::new(models, rows))
     }
 
     /// Convenience: filter to purely exclusion & hygiene tier-0 actions.
@@ -98,28 +984,588 @@ impl MethodRegistry {
         &self,
         pest: PestSpecies,
         location: LocationType,
-    ) -> Vec<(ControlMethod, KerScore)> {
+    ) -> Result<Vec<(ControlMethod, KerScore)>, StoreError> {
+        let ctx = ScoringContext::new(location);
         let mut out = Vec::new();
         for m in &self.methods {
-            if !matches!(m.family, ControlFamily::Exclusion | ControlFamily::Sanitation) {
+            if !matches!(m.family, ControlFamily::Exclusion | ControlFamily::Sanitation)
+                || !self.lifecycle_state(&m.id).queryable(false)
+            {
                 continue;
             }
-            let logs = self.logs_for_method(&m.id, pest);
-            let ker = score_method(m, &logs);
+            let (ker, _) = self.with_scoring_logs(&m.id, pest, &ctx, |logs| {
+                self.score_with_model(m, logs, &ctx, false)
+            })?;
             // Require low risk for tier-0 recommendation.
             if !ker.hard_violation && ker.r <= 0.2 {
                 out.push((m.clone(), ker));
             }
         }
-        out.sort_by(|(_, a), (_, b)| {
-            b.k
-                .partial_cmp(&a.k)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| {
-                    b.e.partial_cmp(&a.e)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                })
+        out.sort_by(|(_, a), (_, b)| rank_order(a, b));
+        Ok(out)
+    }
+
+    /// `tier0_exclusion_hygiene` for a full pest context: the same R limit, plus the
+    /// eligibility policy and context priorities.
+    pub fn tier0_exclusion_hygiene_in_context(
+        &self,
+        ctx: &PestContext,
+    ) -> Result<Vec<(ControlMethod, KerScore)>, StoreError> {
+        let mut opts = QueryOptions::new(0.2);
+        opts.risk_bound = RiskBound::Mean;
+        let site = EligibilityContext::from_pest_context(ctx);
+        let mut accepted: Vec<Candidate<'_>> = self
+            .evaluate(ctx.pest, &site, &opts, false)?
+            .into_iter()
+            .filter(|c| {
+                c.rejections.is_empty()
+                    && matches!(
+                        c.method.family,
+                        ControlFamily::Exclusion | ControlFamily::Sanitation
+                    )
+            })
+            .collect();
+        accepted.sort_by(|a, b| {
+            a.priorities
+                .is_empty()
+                .cmp(&b.priorities.is_empty())
+                .then_with(|| rank_order(&a.ker, &b.ker))
         });
-        out
+        Ok(accepted
+            .into_iter()
+            .map(|c| (c.method.clone(), c.ker))
+            .collect())
+    }
+}
+
+/// One method as seen by a query: its score, rejection reasons, and optional trace.
+struct Candidate<'a> {
+    method: &'a ControlMethod,
+    ker: KerScore,
+    rejections: Vec<Rejection>,
+    explanation: Option<KerExplanation>,
+    /// Ids of matching context priority rules.
+    priorities: Vec<String>,
+}
+
+/// Positions of `candidates` in ranking order, and the Pareto tier of each position.
+/// Candidates matching a context priority rule come first under every ranking.
+fn rank_indices(candidates: &[Candidate<'_>], opts: &QueryOptions) -> (Vec<usize>, Vec<usize>) {
+    let kers: Vec<&KerScore> = candidates.iter().map(|c| &c.ker).collect();
+    let objectives: Vec<Objectives> = kers
+        .iter()
+        .map(|ker| Objectives::new(ker.k, ker.e, opts.filtered_r(ker)))
+        .collect();
+    let tiers = pareto_tiers(&objectives);
+
+    let mut order: Vec<usize> = (0..kers.len()).collect();
+    match opts.ranking {
+        Ranking::Lexicographic => order.sort_by(|&a, &b| rank_order(kers[a], kers[b])),
+        Ranking::Pareto => order.sort_by(|&a, &b| {
+            tiers[a]
+                .cmp(&tiers[b])
+                .then_with(|| rank_order(kers[a], kers[b]))
+        }),
+        Ranking::Weighted(w) => order.sort_by(|&a, &b| {
+            w.scalarize(&objectives[b])
+                .partial_cmp(&w.scalarize(&objectives[a]))
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| rank_order(kers[a], kers[b]))
+        }),
+    }
+    order.sort_by_key(|&i| candidates[i].priorities.is_empty());
+    (order, tiers)
+}
+
+/// Sort by K high → low, then E high → low.
+fn rank_order(a: &KerScore, b: &KerScore) -> Ordering {
+    b.k.partial_cmp(&a.k)
+        .unwrap_or(Ordering::Equal)
+        .then_with(|| b.e.partial_cmp(&a.e).unwrap_or(Ordering::Equal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures::{log, meta, method};
+    use crate::model::{CredibleInterval, EffectivenessBand};
+    use crate::rank::ObjectiveWeights;
+    use crate::scoring::CoreKerModel;
+
+    /// Register `method` and approve it so queries offer it.
+    fn approve(registry: &mut MethodRegistry, method: ControlMethod) {
+        let id = method.id.clone();
+        registry.add_method(method, meta(0)).unwrap();
+        registry
+            .transition(&id, LifecycleState::Approved, "reviewed", meta(0))
+            .unwrap();
+    }
+
+    /// Default scoring with recency aged from a fixed time after every fixture log.
+    fn pinned() -> MethodRegistry {
+        let mut config = KerConfig::default();
+        config.recency.as_of = Some(meta(1000).timestamp);
+        let mut registry = MethodRegistry::new();
+        registry.set_config(config);
+        registry
+    }
+
+    fn farm_log(method_id: &str, n: u64) -> OutcomeLog {
+        let mut log = log(method_id, n);
+        log.context.location_type = LocationType::Farm;
+        log.effectiveness = EffectivenessBand::Low;
+        log
+    }
+
+    fn score_at(registry: &MethodRegistry, location: LocationType) -> KerScore {
+        let ranked = registry
+            .rank_safest_methods(PestSpecies::Rodent, location, &QueryOptions::new(1.0))
+            .unwrap();
+        ranked.into_iter().next().unwrap().ker
+    }
+
+    #[test]
+    fn location_index_scores_like_the_full_corpus() {
+        let mut registry = pinned();
+        approve(
+            &mut registry,
+            method("trap.live", ControlFamily::LiveCapture),
+        );
+        for n in 0..6 {
+            registry.add_log(log("trap.live", n)).unwrap();
+        }
+        for n in 6..9 {
+            registry.add_log(farm_log("trap.live", n)).unwrap();
+        }
+        let all: Vec<&OutcomeLog> = registry.logs().iter().collect();
+        let m = &registry.methods()[0];
+
+        let home = score_at(&registry, LocationType::Home);
+        let direct = score_method_in_context(
+            m,
+            &all,
+            &ScoringContext::new(LocationType::Home),
+            registry.config(),
+        );
+        assert_eq!(home.stratum, Stratum::Location);
+        assert_eq!(
+            serde_json::to_value(&home).unwrap(),
+            serde_json::to_value(&direct).unwrap()
+        );
+
+        // Three farm logs are too few for a stratum, so the whole corpus is pooled.
+        let farm = score_at(&registry, LocationType::Farm);
+        let direct = score_method_in_context(
+            m,
+            &all,
+            &ScoringContext::new(LocationType::Farm),
+            registry.config(),
+        );
+        assert_eq!(farm.stratum, Stratum::Pooled);
+        assert_eq!(
+            serde_json::to_value(&farm).unwrap(),
+            serde_json::to_value(&direct).unwrap()
+        );
+    }
+
+    #[test]
+    fn with_logs_narrows_to_one_location() {
+        let mut registry = MethodRegistry::new();
+        approve(
+            &mut registry,
+            method("trap.live", ControlFamily::LiveCapture),
+        );
+        registry.add_log(log("trap.live", 1)).unwrap();
+        registry.add_log(farm_log("trap.live", 2)).unwrap();
+        registry.add_log(log("other", 3)).unwrap();
+
+        let count = |location| {
+            registry
+                .with_logs("trap.live", PestSpecies::Rodent, location, |logs| {
+                    logs.len()
+                })
+                .unwrap()
+        };
+        assert_eq!(count(None), 2);
+        assert_eq!(count(Some(LocationType::Farm)), 1);
+        assert_eq!(count(Some(LocationType::Hospital)), 0);
+    }
+
+    #[test]
+    fn add_log_holds_back_quarantined_logs() {
+        let dir =
+            std::env::temp_dir().join(format!("deadbugs-query-screened-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = crate::store::JsonlStore::open(&dir).unwrap();
+        let mut registry = MethodRegistry::open(Box::new(store), LoadMode::OnDemand).unwrap();
+        approve(
+            &mut registry,
+            method("trap.live", ControlFamily::LiveCapture),
+        );
+        assert!(!registry.add_log(log("trap.live", 1)).unwrap().quarantined());
+        let copy = registry.add_log(log("exclusion.seal", 1)).unwrap();
+        assert!(copy.quarantined());
+        assert_eq!(registry.quarantined_logs().len(), 1);
+        let count = |registry: &MethodRegistry, method_id: &str| {
+            registry
+                .with_logs(method_id, PestSpecies::Rodent, None, |logs| logs.len())
+                .unwrap()
+        };
+        assert_eq!(count(&registry, "exclusion.seal"), 0);
+
+        // Reopening seeds the screen from the stored logs and keeps the quarantine.
+        drop(registry);
+        let reopen = || {
+            let store = crate::store::JsonlStore::open(&dir).unwrap();
+            MethodRegistry::open(Box::new(store), LoadMode::OnDemand).unwrap()
+        };
+        let mut registry = reopen();
+        assert_eq!(registry.quarantined_logs().len(), 1);
+        assert_eq!(registry.quarantined_logs()[0].reasons, copy.reasons);
+        assert!(registry.add_log(log("trap.live", 1)).unwrap().quarantined());
+
+        // A reviewed log can still go in, past the screen.
+        let mut held = registry.take_quarantined_logs().unwrap();
+        assert_eq!(held.len(), 2);
+        registry.add_log_unscreened(held.remove(1).log).unwrap();
+        assert!(registry.quarantined_logs().is_empty());
+        assert_eq!(count(&registry, "trap.live"), 2);
+
+        // The release is durable, too.
+        drop(registry);
+        assert!(reopen().quarantined_logs().is_empty());
+    }
+
+    #[test]
+    fn on_demand_snapshots_and_feedback_match_resident() {
+        let dir = std::env::temp_dir().join(format!("deadbugs-query-modes-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = crate::store::JsonlStore::open(&dir).unwrap();
+        let mut registry = MethodRegistry::open(Box::new(store), LoadMode::OnDemand).unwrap();
+        approve(
+            &mut registry,
+            method("trap.live", ControlFamily::LiveCapture),
+        );
+        approve(
+            &mut registry,
+            method("exclusion.seal", ControlFamily::Exclusion),
+        );
+        for n in 0..6 {
+            let id = if n % 2 == 0 {
+                "trap.live"
+            } else {
+                "exclusion.seal"
+            };
+            registry.add_log(log(id, n)).unwrap();
+        }
+        registry.add_log(log("bait.unlisted", 6)).unwrap();
+        let ker = score_at(&registry, LocationType::Home);
+        let rec = registry
+            .recommend("trap.live", log("trap.live", 0).context, meta(7), ker)
+            .unwrap();
+        let mut follow_up = log("trap.live", 8);
+        follow_up.recommendation_id = Some(rec);
+        registry.add_log(follow_up).unwrap();
+
+        let resident = MethodRegistry::open(
+            Box::new(crate::store::JsonlStore::open(&dir).unwrap()),
+            LoadMode::Resident,
+        )
+        .unwrap();
+        let on_demand = registry.snapshot("v1", meta(9).timestamp).unwrap();
+        let loaded = resident.snapshot("v1", meta(9).timestamp).unwrap();
+        assert_eq!(on_demand.snapshot_id(), loaded.snapshot_id());
+        // Only registered methods' logs are frozen.
+        assert_eq!(on_demand.logs().len(), 7);
+
+        for registry in [&registry, &resident] {
+            let report = registry.feedback_report().unwrap();
+            assert_eq!(report.len(), 1);
+            assert_eq!(report[0].followed_up, 1);
+            assert_eq!(report[0].outcome_logs, 1);
+        }
+    }
+
+    /// Fixed K, E and R per method ID, whatever the logs.
+    struct Fixed(Vec<(&'static str, f64, f64, f64)>);
+
+    impl ScoringModel for Fixed {
+        fn model_id(&self) -> String {
+            "fixed".to_string()
+        }
+
+        fn score(
+            &self,
+            method: &ControlMethod,
+            logs: &[&OutcomeLog],
+            ctx: &ScoringContext,
+        ) -> KerScore {
+            let mut ker = score_method_in_context(method, logs, ctx, &KerConfig::default());
+            let &(_, k, e, r) = self.0.iter().find(|(id, ..)| *id == method.id).unwrap();
+            ker.k = k;
+            ker.e = e;
+            ker.r = r;
+            ker.r_interval = CredibleInterval { lower: r, upper: r };
+            ker
+        }
+    }
+
+    /// Registry whose methods trade K, E and R off against each other.
+    fn trade_offs() -> MethodRegistry {
+        let mut registry = MethodRegistry::new();
+        let scores = vec![
+            ("a", 0.9, 0.5, 0.3),
+            ("b", 0.8, 0.6, 0.1),
+            ("c", 0.7, 0.4, 0.4),
+            ("d", 0.85, 0.3, 0.5),
+        ];
+        for &(id, ..) in &scores {
+            approve(&mut registry, method(id, ControlFamily::LiveCapture));
+        }
+        registry.set_model(Box::new(Fixed(scores)));
+        registry
+    }
+
+    #[test]
+    fn new_configs_keep_the_approved_model() {
+        let mut registry = trade_offs();
+        let config = KerConfig {
+            config_id: "cfg-next".to_string(),
+            ..KerConfig::default()
+        };
+        registry.set_config(config.clone());
+        assert_eq!(registry.model_id(), "fixed");
+        assert_eq!(registry.config().config_id, "cfg-next");
+        assert_eq!(ranked(&registry, Ranking::default()).len(), 4);
+
+        registry.set_model(Box::new(CoreKerModel::new(KerConfig::default())));
+        registry.set_config(config);
+        assert_eq!(registry.model_id(), "cfg-next");
+    }
+
+    fn ranked(registry: &MethodRegistry, ranking: Ranking) -> Vec<(String, usize)> {
+        let opts = QueryOptions {
+            ranking,
+            ..QueryOptions::new(1.0)
+        };
+        registry
+            .rank_safest_methods(PestSpecies::Rodent, LocationType::Home, &opts)
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.method.id, m.tier))
+            .collect()
+    }
+
+    #[test]
+    fn ranking_modes_order_the_same_candidates() {
+        let registry = trade_offs();
+        let order = |ranking| -> Vec<String> {
+            ranked(&registry, ranking)
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect()
+        };
+        assert_eq!(order(Ranking::Lexicographic), ["a", "d", "b", "c"]);
+        // The safer `b` is on the frontier, so it overtakes the dominated `d`.
+        assert_eq!(order(Ranking::Pareto), ["a", "b", "d", "c"]);
+        let safety_first = ObjectiveWeights {
+            k: 0.0,
+            e: 0.0,
+            safety: 1.0,
+        };
+        assert_eq!(order(Ranking::Weighted(safety_first)), ["b", "a", "c", "d"]);
+
+        // Tiers are reported under every mode.
+        let tiers: HashMap<String, usize> = ranked(&registry, Ranking::Lexicographic)
+            .into_iter()
+            .collect();
+        assert_eq!(tiers["a"], 0);
+        assert_eq!(tiers["b"], 0);
+        assert_eq!(tiers["c"], 1);
+        assert_eq!(tiers["d"], 1);
+    }
+
+    #[test]
+    fn tiers_count_accepted_methods_only() {
+        let mut registry = trade_offs();
+        // `d` was dominated by `a` alone.
+        registry
+            .transition("a", LifecycleState::Suspended, "review", meta(0))
+            .unwrap();
+        assert_eq!(
+            ranked(&registry, Ranking::Pareto),
+            [
+                ("d".to_string(), 0),
+                ("b".to_string(), 0),
+                ("c".to_string(), 1)
+            ]
+        );
+    }
+
+    /// Approved snap trap, live trap, seal and sanitation, each with clean logs.
+    fn mixed_registry() -> MethodRegistry {
+        let mut registry = pinned();
+        let methods = [
+            ("trap.snap", ControlFamily::MechanicalKill),
+            ("trap.live", ControlFamily::LiveCapture),
+            ("exclusion.seal", ControlFamily::Exclusion),
+            ("sanitation.dry", ControlFamily::Sanitation),
+        ];
+        for (i, (id, family)) in methods.into_iter().enumerate() {
+            approve(&mut registry, method(id, family));
+            for n in 0..6 {
+                registry.add_log(log(id, i as u64 * 10 + n)).unwrap();
+            }
+        }
+        registry
+    }
+
+    #[test]
+    fn context_queries_apply_proximity_and_site_conditions() {
+        let registry = mixed_registry();
+        let mut ctx = log("any", 0).context;
+        ctx.proximity.children_present = true;
+        ctx.moisture_high = true;
+
+        let ranked = registry
+            .rank_safest_methods_in_context(&ctx, &QueryOptions::new(1.0))
+            .unwrap();
+        let ids: Vec<&str> = ranked.iter().map(|m| m.method.id.as_str()).collect();
+        assert!(!ids.contains(&"trap.snap"));
+        assert_eq!(ids[0], "sanitation.dry");
+        assert_eq!(ranked[0].priorities, ["moisture-control"]);
+        assert!(ranked[1..].iter().all(|m| m.priorities.is_empty()));
+
+        // The location-only query knows neither the children nor the moisture.
+        let plain = registry
+            .query_safest_methods(PestSpecies::Rodent, LocationType::Home, 1.0)
+            .unwrap();
+        assert!(plain.iter().any(|(m, _)| m.id == "trap.snap"));
+        let sanitation = plain
+            .iter()
+            .find(|(m, _)| m.id == "sanitation.dry")
+            .unwrap();
+        assert!(ranked[0].ker.e > sanitation.1.e);
+    }
+
+    #[test]
+    fn context_tier0_puts_priorities_first() {
+        let registry = mixed_registry();
+        let mut ctx = log("any", 0).context;
+        let ids = |ctx: &PestContext| -> Vec<String> {
+            registry
+                .tier0_exclusion_hygiene_in_context(ctx)
+                .unwrap()
+                .into_iter()
+                .map(|(m, _)| m.id)
+                .collect()
+        };
+        let dry = ids(&ctx);
+        assert_eq!(dry.len(), 2);
+        assert!(dry.iter().all(|id| id != "trap.live"));
+        ctx.moisture_high = true;
+        assert_eq!(ids(&ctx), ["sanitation.dry", "exclusion.seal"]);
+    }
+
+    fn offered(registry: &MethodRegistry) -> Vec<RankedMethod> {
+        registry
+            .rank_safest_methods(
+                PestSpecies::Rodent,
+                LocationType::Home,
+                &QueryOptions::new(1.0),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn methods_without_history_are_proposed() {
+        let dir =
+            std::env::temp_dir().join(format!("deadbugs-query-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut store = crate::store::JsonlStore::open(&dir).unwrap();
+        store
+            .append_method(&method("trap.bare", ControlFamily::LiveCapture))
+            .unwrap();
+        let mut registry = MethodRegistry::open(Box::new(store), LoadMode::Resident).unwrap();
+        assert_eq!(
+            registry.lifecycle_state("trap.bare"),
+            LifecycleState::Proposed
+        );
+        assert!(registry.lifecycle("trap.bare").is_none());
+        registry.add_log(log("trap.bare", 1)).unwrap();
+        assert!(offered(&registry).is_empty());
+
+        registry
+            .add_method(method("trap.live", ControlFamily::LiveCapture), meta(0))
+            .unwrap();
+        drop(registry);
+        let store = crate::store::JsonlStore::open(&dir).unwrap();
+        let registry = MethodRegistry::open(Box::new(store), LoadMode::Resident).unwrap();
+        let ids: Vec<&str> = registry.methods().iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["trap.bare", "trap.live"]);
+        let history = &registry.lifecycle("trap.live").unwrap().history;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].to, LifecycleState::Proposed);
+    }
+
+    #[test]
+    fn bulk_imports_check_the_hard_band_once_per_scope() {
+        let mut registry = pinned();
+        approve(
+            &mut registry,
+            method("trap.live", ControlFamily::LiveCapture),
+        );
+        let logs: Vec<OutcomeLog> = (0..10)
+            .map(|n| {
+                let mut l = log("trap.live", n);
+                l.side_effects.pet_incident = n == 2 || n == 5;
+                l
+            })
+            .collect();
+        let screenings = registry.add_logs(logs).unwrap();
+        assert_eq!(screenings.len(), 10);
+        assert_eq!(registry.logs().len(), 10);
+
+        let history = &registry.lifecycle("trap.live").unwrap().history;
+        assert_eq!(history.len(), 3);
+        let suspension = &history[2];
+        assert_eq!(suspension.to, LifecycleState::Suspended);
+        assert_eq!(suspension.reason, "automatic quarantine: hard band on pets");
+        // Signed by the last incident in the batch.
+        assert_eq!(suspension.meta.hex_stamp, "0x0005");
+    }
+
+    #[test]
+    fn clean_logs_leave_a_reviewed_method_alone() {
+        let mut registry = pinned();
+        approve(
+            &mut registry,
+            method("trap.live", ControlFamily::LiveCapture),
+        );
+        let mut incident = log("trap.live", 0);
+        incident.side_effects.pet_incident = true;
+        registry.add_log(incident).unwrap();
+        assert_eq!(
+            registry.lifecycle_state("trap.live"),
+            LifecycleState::Suspended
+        );
+
+        // Reinstated on review: a clean log cannot raise a band, so it does not
+        // re-trigger the quarantine, while a new incident does.
+        registry
+            .transition("trap.live", LifecycleState::Approved, "reviewed", meta(0))
+            .unwrap();
+        registry.add_log(log("trap.live", 1)).unwrap();
+        assert_eq!(
+            registry.lifecycle_state("trap.live"),
+            LifecycleState::Approved
+        );
+        let mut again = log("trap.live", 2);
+        again.side_effects.human_injury = true;
+        registry.add_log(again).unwrap();
+        assert_eq!(
+            registry.lifecycle_state("trap.live"),
+            LifecycleState::Suspended
+        );
     }
 }
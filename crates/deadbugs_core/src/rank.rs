@@ -0,0 +1,168 @@
+#![forbid(unsafe_code)]
+
+//! Multi-objective ranking over (K, E, 1 − R).
+//!
+//! Methods are sorted into Pareto tiers by non-dominated sorting: tier 0 is the
+//! frontier (no other method is at least as good on all three objectives and better
+//! on one), tier 1 is the frontier once tier 0 is removed, and so on. A weighted
+//! scalarization gives a single score when callers want a total order.
+
+use serde::{Deserialize, Serialize};
+
+/// The three objectives, all oriented so higher is better.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Objectives {
+    pub k: f64,
+    pub e: f64,
+    /// 1 − R.
+    pub safety: f64,
+}
+
+impl Objectives {
+    pub fn new(k: f64, e: f64, r: f64) -> Self {
+        Self {
+            k,
+            e,
+            safety: 1.0 - r,
+        }
+    }
+
+    /// At least as good on every objective and strictly better on one.
+    pub fn dominates(&self, other: &Objectives) -> bool {
+        self.k >= other.k
+            && self.e >= other.e
+            && self.safety >= other.safety
+            && (self.k > other.k || self.e > other.e || self.safety > other.safety)
+    }
+}
+
+/// Relative weights for scalarizing (K, E, 1 − R).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObjectiveWeights {
+    pub k: f64,
+    pub e: f64,
+    pub safety: f64,
+}
+
+impl Default for ObjectiveWeights {
+    fn default() -> Self {
+        Self {
+            k: 1.0,
+            e: 1.0,
+            safety: 1.0,
+        }
+    }
+}
+
+impl ObjectiveWeights {
+    /// Weighted mean of the objectives, in [0,1]; 0 when every weight is 0.
+    pub fn scalarize(&self, o: &Objectives) -> f64 {
+        let total = self.k + self.e + self.safety;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        (self.k * o.k + self.e * o.e + self.safety * o.safety) / total
+    }
+}
+
+/// How queries order the methods that pass their filters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Ranking {
+    /// K high → low, then E high → low; R only filters.
+    #[default]
+    Lexicographic,
+    /// Pareto tier first, then K and E within a tier.
+    Pareto,
+    /// Weighted scalarization, high → low.
+    Weighted(ObjectiveWeights),
+}
+
+/// Pareto tier of each point (0 = frontier), by repeated non-dominated sorting.
+pub fn pareto_tiers(points: &[Objectives]) -> Vec<usize> {
+    let mut tiers = vec![usize::MAX; points.len()];
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    let mut tier = 0;
+    while !remaining.is_empty() {
+        let front: Vec<usize> = remaining
+            .iter()
+            .copied()
+            .filter(|&i| {
+                !remaining
+                    .iter()
+                    .any(|&j| j != i && points[j].dominates(&points[i]))
+            })
+            .collect();
+        for &i in &front {
+            tiers[i] = tier;
+        }
+        remaining.retain(|i| !front.contains(i));
+        tier += 1;
+    }
+    tiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn o(k: f64, e: f64, r: f64) -> Objectives {
+        Objectives::new(k, e, r)
+    }
+
+    #[test]
+    fn dominance_needs_one_strict_gain() {
+        let base = o(0.8, 0.6, 0.2);
+        assert!(!base.dominates(&base));
+        assert!(o(0.8, 0.6, 0.1).dominates(&base));
+        assert!(o(0.9, 0.6, 0.2).dominates(&base));
+        // A trade-off dominates neither way.
+        let traded = o(0.9, 0.5, 0.2);
+        assert!(!traded.dominates(&base));
+        assert!(!base.dominates(&traded));
+    }
+
+    #[test]
+    fn tiers_peel_off_successive_fronts() {
+        let points = [
+            o(0.9, 0.5, 0.3),  // Frontier: best K.
+            o(0.8, 0.6, 0.1),  // Frontier: best E and safety.
+            o(0.85, 0.3, 0.5), // Dominated by the first only.
+            o(0.7, 0.4, 0.4),  // Dominated by both frontier points.
+            o(0.6, 0.2, 0.6),  // Dominated by both tier-1 points.
+            o(0.8, 0.6, 0.1),  // Ties share a tier.
+        ];
+        assert_eq!(pareto_tiers(&points), [0, 0, 1, 1, 2, 0]);
+        assert!(pareto_tiers(&[]).is_empty());
+    }
+
+    #[test]
+    fn incomparable_points_still_get_a_tier() {
+        let points = [o(f64::NAN, 0.5, 0.2), o(0.8, 0.6, 0.1)];
+        assert_eq!(pareto_tiers(&points), [0, 0]);
+    }
+
+    #[test]
+    fn scalarize_is_a_weighted_mean() {
+        let point = o(0.9, 0.5, 0.3);
+        let even = ObjectiveWeights::default();
+        assert!((even.scalarize(&point) - 0.7).abs() < 1e-12);
+        let safety = ObjectiveWeights {
+            k: 0.0,
+            e: 0.0,
+            safety: 2.0,
+        };
+        assert!((safety.scalarize(&point) - 0.7).abs() < 1e-12);
+        let k_heavy = ObjectiveWeights {
+            k: 3.0,
+            e: 1.0,
+            safety: 0.0,
+        };
+        assert!((k_heavy.scalarize(&point) - 0.8).abs() < 1e-12);
+        let none = ObjectiveWeights {
+            k: 0.0,
+            e: 0.0,
+            safety: 0.0,
+        };
+        assert_eq!(none.scalarize(&point), 0.0);
+    }
+}
@@ -0,0 +1,143 @@
+#![forbid(unsafe_code)]
+
+//! Recency weighting of evidence by `EvidenceMeta::timestamp`.
+//!
+//! Ages are measured on the UTC epoch timeline (the same one shards store), so
+//! weights do not depend on the local time zone of the scorer. Shared by the core
+//! scorer and the ker-engine.
+//!
+//! The default weighs every log the same, so a score does not change with the clock
+//! it is computed on. Decay and the window are opt-in; pin `as_of` with them when
+//! a score has to be reproducible.
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// Half-life decay and hard evidence window.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecencyConfig {
+    /// Age in days at which a log counts half; `None` disables decay.
+    pub half_life_days: Option<f64>,
+    /// Logs older than this many days are ignored; `None` keeps every log.
+    pub window_days: Option<f64>,
+    /// Time ages are measured from; `None` means the moment of scoring.
+    /// Pin it to reproduce a past score exactly.
+    pub as_of: Option<SystemTime>,
+}
+
+impl Default for RecencyConfig {
+    fn default() -> Self {
+        Self::uniform()
+    }
+}
+
+impl RecencyConfig {
+    /// No decay and no window: every log weighs 1.
+    pub fn uniform() -> Self {
+        Self {
+            half_life_days: None,
+            window_days: None,
+            as_of: None,
+        }
+    }
+
+    /// A one-year half-life with a five-year window.
+    pub fn decay() -> Self {
+        Self {
+            half_life_days: Some(365.0),
+            window_days: Some(5.0 * 365.0),
+            as_of: None,
+        }
+    }
+
+    pub fn reference_time(&self) -> SystemTime {
+        self.as_of.unwrap_or_else(SystemTime::now)
+    }
+
+    /// Weight in [0,1] of evidence recorded at `timestamp`, aged against `as_of`.
+    /// Timestamps after `as_of` (clock skew) count as brand new.
+    pub fn weight(&self, timestamp: SystemTime, as_of: SystemTime) -> f64 {
+        let age_days = as_of
+            .duration_since(timestamp)
+            .map_or(0.0, |d| d.as_secs_f64() / SECS_PER_DAY);
+
+        if self.window_days.is_some_and(|window| age_days > window) {
+            return 0.0;
+        }
+        match self.half_life_days {
+            Some(half_life) if half_life > 0.0 => 0.5_f64.powf(age_days / half_life),
+            _ => 1.0,
+        }
+    }
+}
+
+/// Kish effective sample size, (Σw)² / Σw²; equals the count for uniform weights.
+pub fn effective_sample_size(weights: impl IntoIterator<Item = f64>) -> f64 {
+    let (sum, sum_sq) = weights
+        .into_iter()
+        .fold((0.0, 0.0), |(s, s2), w| (s + w, s2 + w * w));
+    if sum_sq > 0.0 {
+        sum * sum / sum_sq
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(86_400);
+
+    fn t0() -> SystemTime {
+        SystemTime::UNIX_EPOCH + 20_000 * DAY
+    }
+
+    #[test]
+    fn default_weighs_every_log_the_same() {
+        let cfg = RecencyConfig::default();
+        assert_eq!(cfg.weight(t0() - 10_000 * DAY, t0()), 1.0);
+        assert_eq!(cfg.weight(t0(), t0()), 1.0);
+        assert!(cfg.as_of.is_none());
+    }
+
+    #[test]
+    fn decay_halves_per_half_life() {
+        let cfg = RecencyConfig::decay();
+        assert_eq!(cfg.weight(t0(), t0()), 1.0);
+        assert!((cfg.weight(t0() - 365 * DAY, t0()) - 0.5).abs() < 1e-12);
+        assert!((cfg.weight(t0() - 730 * DAY, t0()) - 0.25).abs() < 1e-12);
+        // Clock skew: a log from the future counts as new.
+        assert_eq!(cfg.weight(t0() + DAY, t0()), 1.0);
+    }
+
+    #[test]
+    fn window_drops_old_logs_outright() {
+        let cfg = RecencyConfig::decay();
+        let edge = t0() - 5 * 365 * DAY;
+        assert!((cfg.weight(edge, t0()) - 0.5_f64.powi(5)).abs() < 1e-12);
+        assert_eq!(cfg.weight(edge - DAY, t0()), 0.0);
+    }
+
+    #[test]
+    fn reference_time_is_the_pinned_as_of() {
+        let cfg = RecencyConfig {
+            as_of: Some(t0()),
+            ..RecencyConfig::decay()
+        };
+        assert_eq!(cfg.reference_time(), t0());
+    }
+
+    #[test]
+    fn effective_sample_size_matches_the_count_for_equal_weights() {
+        assert_eq!(effective_sample_size([1.0; 4]), 4.0);
+        assert_eq!(effective_sample_size([]), 0.0);
+        assert!((effective_sample_size([1.0, 0.5]) - 1.8).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,205 @@
+#![forbid(unsafe_code)]
+
+//! Pluggable K/E/R scoring models and a harness for comparing them.
+//!
+//! `ScoringModel` is the common surface over the core scorer (`CoreKerModel`) and
+//! any other engine that can score a `ControlMethod` against `OutcomeLog`s. The
+//! registry scores with whichever model governance has approved; `compare_models`
+//! scores one corpus under several models and reports where they disagree.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::explain::KerExplanation;
+use crate::ker::KerConfig;
+use crate::model::{ControlMethod, KerScore, OutcomeLog, PestSpecies};
+use crate::strata::{explain_method_in_context, score_method_in_context, ScoringContext};
+
+/// A K/E/R scorer.
+pub trait ScoringModel: Send + Sync {
+    /// Stable identifier recorded with results (e.g. the config or engine version).
+    fn model_id(&self) -> String;
+
+    /// Score a method against the logs for one pest, conditioned on `ctx`.
+    fn score(&self, method: &ControlMethod, logs: &[&OutcomeLog], ctx: &ScoringContext)
+        -> KerScore;
+
+    /// Score plus explanation trace; `None` when the model cannot explain itself.
+    fn explain(
+        &self,
+        _method: &ControlMethod,
+        _logs: &[&OutcomeLog],
+        _ctx: &ScoringContext,
+    ) -> Option<(KerScore, KerExplanation)> {
+        None
+    }
+
+    /// The same model under different scoring constants; `None` when the model
+    /// does not score with a `KerConfig`, so a new config leaves it unchanged.
+    fn with_config(&self, _config: &KerConfig) -> Option<Box<dyn ScoringModel>> {
+        None
+    }
+}
+
+/// The core corridor-based scorer under a fixed `KerConfig`.
+#[derive(Clone, Debug, Default)]
+pub struct CoreKerModel {
+    pub config: KerConfig,
+}
+
+impl CoreKerModel {
+    pub fn new(config: KerConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl ScoringModel for CoreKerModel {
+    fn model_id(&self) -> String {
+        self.config.config_id.clone()
+    }
+
+    fn score(
+        &self,
+        method: &ControlMethod,
+        logs: &[&OutcomeLog],
+        ctx: &ScoringContext,
+    ) -> KerScore {
+        score_method_in_context(method, logs, ctx, &self.config)
+    }
+
+    fn explain(
+        &self,
+        method: &ControlMethod,
+        logs: &[&OutcomeLog],
+        ctx: &ScoringContext,
+    ) -> Option<(KerScore, KerExplanation)> {
+        Some(explain_method_in_context(method, logs, ctx, &self.config))
+    }
+
+    fn with_config(&self, config: &KerConfig) -> Option<Box<dyn ScoringModel>> {
+        Some(Box::new(CoreKerModel::new(config.clone())))
+    }
+}
+
+/// One model's headline numbers for one method.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModelScore {
+    pub model_id: String,
+    pub k: f64,
+    pub e: f64,
+    pub r: f64,
+}
+
+/// Every model's score for one method and the spread between them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MethodDisagreement {
+    pub method_id: String,
+    /// In the order the models were given.
+    pub scores: Vec<ModelScore>,
+    /// Max − min across models.
+    pub k_spread: f64,
+    pub e_spread: f64,
+    pub r_spread: f64,
+}
+
+impl MethodDisagreement {
+    /// Largest spread over K, E and R.
+    pub fn max_spread(&self) -> f64 {
+        self.k_spread.max(self.e_spread).max(self.r_spread)
+    }
+}
+
+/// Result of scoring a corpus under several models.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModelComparison {
+    pub model_ids: Vec<String>,
+    /// Most disputed method first.
+    pub methods: Vec<MethodDisagreement>,
+}
+
+impl ModelComparison {
+    /// Assemble a comparison from per-method rows, most disputed first.
+    pub fn new(models: &[&dyn ScoringModel], mut methods: Vec<MethodDisagreement>) -> Self {
+        methods.sort_by(|a, b| {
+            b.max_spread()
+                .partial_cmp(&a.max_spread())
+                .unwrap_or(Ordering::Equal)
+        });
+        Self {
+            model_ids: models.iter().map(|m| m.model_id()).collect(),
+            methods,
+        }
+    }
+
+    /// Methods where some model differs from another by more than `tolerance`.
+    pub fn disputed(&self, tolerance: f64) -> impl Iterator<Item = &MethodDisagreement> {
+        self.methods
+            .iter()
+            .filter(move |m| m.max_spread() > tolerance)
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Score one method under each model.
+pub fn method_disagreement(
+    models: &[&dyn ScoringModel],
+    method: &ControlMethod,
+    logs: &[&OutcomeLog],
+    ctx: &ScoringContext,
+) -> MethodDisagreement {
+    let scores: Vec<ModelScore> = models
+        .iter()
+        .map(|model| {
+            let ker = model.score(method, logs, ctx);
+            ModelScore {
+                model_id: model.model_id(),
+                k: ker.k,
+                e: ker.e,
+                r: ker.r,
+            }
+        })
+        .collect();
+    MethodDisagreement {
+        method_id: method.id.clone(),
+        k_spread: spread(scores.iter().map(|s| s.k)),
+        e_spread: spread(scores.iter().map(|s| s.e)),
+        r_spread: spread(scores.iter().map(|s| s.r)),
+        scores,
+    }
+}
+
+/// Score every method against its logs for `pest` under each model.
+pub fn compare_models(
+    models: &[&dyn ScoringModel],
+    methods: &[ControlMethod],
+    logs: &[OutcomeLog],
+    pest: PestSpecies,
+    ctx: &ScoringContext,
+) -> ModelComparison {
+    let rows = methods
+        .iter()
+        .map(|method| {
+            let method_logs: Vec<&OutcomeLog> = logs
+                .iter()
+                .filter(|l| l.method_id == method.id && l.context.pest == pest)
+                .collect();
+            method_disagreement(models, method, &method_logs, ctx)
+        })
+        .collect();
+    ModelComparison::new(models, rows)
+}
+
+fn spread(values: impl Iterator<Item = f64>) -> f64 {
+    let (lo, hi) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+    if hi >= lo {
+        hi - lo
+    } else {
+        0.0
+    }
+}
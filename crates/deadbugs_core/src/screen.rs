@@ -0,0 +1,539 @@
+#![forbid(unsafe_code)]
+
+//! Evidence screening for incoming `OutcomeLog`s.
+//!
+//! Every `MethodRegistry` owns an `EvidenceScreen`, and `MethodRegistry::add_log`
+//! passes each log through it. The screen checks for duplicated `hex_stamp`s,
+//! impossible timestamps, bursts of identical logs from one operator, and operators
+//! who never report a side effect. Each finding carries a reason; the configured
+//! severity decides whether the log is admitted with a flag or held in quarantine
+//! instead of reaching the registry. Only admitted logs update the screen's history,
+//! so a quarantined log cannot shadow a later genuine one. Burst history is kept
+//! for one burst window behind the newest admitted log, so it does not grow with
+//! the corpus. A registry with a store persists its quarantine there.
+//! The history keeps a `LogStamp` per admitted log rather than the log itself, so
+//! an on-demand registry can seed its screen from the store's stamp index.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::OutcomeLog;
+
+/// What to do with a log that trips a check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Ignore,
+    /// Admit, but report the finding.
+    Flag,
+    /// Hold back from the registry.
+    Quarantine,
+}
+
+/// Thresholds and severities for each check.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScreeningConfig {
+    pub duplicate_stamp: Severity,
+    pub impossible_timestamp: Severity,
+    pub burst: Severity,
+    pub zero_side_effects: Severity,
+    /// Earliest credible timestamp, in UTC seconds since the epoch.
+    pub earliest_timestamp_secs: u64,
+    /// Allowed clock skew into the future.
+    pub max_future_skew_secs: u64,
+    /// Identical logs from one operator within this window count as one burst.
+    pub burst_window_secs: u64,
+    /// Identical logs allowed per burst window before the rest are anomalous.
+    pub burst_max_identical: usize,
+    /// Operators with at least this many admitted logs and no side effect at all
+    /// are suspicious.
+    pub zero_side_effect_min_logs: usize,
+}
+
+impl Default for ScreeningConfig {
+    fn default() -> Self {
+        Self {
+            duplicate_stamp: Severity::Quarantine,
+            impossible_timestamp: Severity::Quarantine,
+            burst: Severity::Quarantine,
+            zero_side_effects: Severity::Flag,
+            // 2000-01-01T00:00:00Z.
+            earliest_timestamp_secs: 946_684_800,
+            max_future_skew_secs: 300,
+            burst_window_secs: 3_600,
+            burst_max_identical: 3,
+            zero_side_effect_min_logs: 20,
+        }
+    }
+}
+
+/// Why a log was flagged or quarantined.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum AnomalyReason {
+    /// `hex_stamp` already seen on another log.
+    DuplicateHexStamp { hex_stamp: String },
+    /// Timestamp before the credible floor or too far in the future.
+    ImpossibleTimestamp { utc_secs: i64 },
+    /// More identical logs from one operator than a burst window allows.
+    Burst { operator: String, identical: usize },
+    /// Operator has many logs and has never reported a side effect.
+    ZeroSideEffects { operator: String, logs: usize },
+}
+
+impl AnomalyReason {
+    fn severity(&self, cfg: &ScreeningConfig) -> Severity {
+        match self {
+            AnomalyReason::DuplicateHexStamp { .. } => cfg.duplicate_stamp,
+            AnomalyReason::ImpossibleTimestamp { .. } => cfg.impossible_timestamp,
+            AnomalyReason::Burst { .. } => cfg.burst,
+            AnomalyReason::ZeroSideEffects { .. } => cfg.zero_side_effects,
+        }
+    }
+}
+
+impl fmt::Display for AnomalyReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnomalyReason::DuplicateHexStamp { hex_stamp } => {
+                write!(f, "duplicate hex_stamp {hex_stamp}")
+            }
+            AnomalyReason::ImpossibleTimestamp { utc_secs } => {
+                write!(f, "impossible timestamp {utc_secs} (UTC secs)")
+            }
+            AnomalyReason::Burst {
+                operator,
+                identical,
+            } => write!(
+                f,
+                "{identical} identical logs from {operator} in one burst window"
+            ),
+            AnomalyReason::ZeroSideEffects { operator, logs } => {
+                write!(f, "{operator} reported no side effect across {logs} logs")
+            }
+        }
+    }
+}
+
+/// Outcome of screening one log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Screening {
+    /// Most severe finding, or `Ignore` when the log is clean.
+    pub severity: Severity,
+    /// Findings at `Flag` or above.
+    pub reasons: Vec<AnomalyReason>,
+}
+
+impl Screening {
+    pub fn quarantined(&self) -> bool {
+        self.severity == Severity::Quarantine
+    }
+}
+
+/// A log held back from the registry, with the reasons.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuarantinedLog {
+    pub log: OutcomeLog,
+    pub reasons: Vec<AnomalyReason>,
+}
+
+/// What the screen keeps of an admitted log: enough to screen later logs against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogStamp {
+    pub hex_stamp: String,
+    /// Operator's `bostrom_address`.
+    pub operator: String,
+    pub timestamp: SystemTime,
+    /// Hash of everything the log reports apart from its provenance metadata;
+    /// only stable within one process, so stamps are not persisted.
+    pub fingerprint: u64,
+    /// Whether the log reports any incident or non-target kill.
+    pub side_effects: bool,
+}
+
+impl LogStamp {
+    pub fn of(log: &OutcomeLog) -> Self {
+        Self {
+            hex_stamp: log.meta.hex_stamp.clone(),
+            operator: log.meta.bostrom_address.clone(),
+            timestamp: log.meta.timestamp,
+            fingerprint: fingerprint(log),
+            side_effects: has_side_effects(log),
+        }
+    }
+}
+
+#[derive(Default)]
+struct OperatorStats {
+    logs: usize,
+    with_side_effects: usize,
+}
+
+/// Stateful screen; seed it with the logs already in the registry.
+#[derive(Default)]
+pub struct EvidenceScreen {
+    cfg: ScreeningConfig,
+    seen_stamps: HashSet<String>,
+    /// (operator, content fingerprint) → timestamps of recent identical logs.
+    recent: HashMap<(String, u64), VecDeque<SystemTime>>,
+    /// Newest timestamp recorded, and the one `recent` was last pruned against.
+    newest: Option<SystemTime>,
+    pruned_at: Option<SystemTime>,
+    operators: HashMap<String, OperatorStats>,
+    quarantine: Vec<QuarantinedLog>,
+}
+
+impl EvidenceScreen {
+    pub fn new(cfg: ScreeningConfig) -> Self {
+        Self {
+            cfg,
+            seen_stamps: HashSet::new(),
+            recent: HashMap::new(),
+            newest: None,
+            pruned_at: None,
+            operators: HashMap::new(),
+            quarantine: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ScreeningConfig {
+        &self.cfg
+    }
+
+    /// Record already-admitted logs without screening them.
+    pub fn seed<'a>(&mut self, logs: impl IntoIterator<Item = &'a OutcomeLog>) {
+        for log in logs {
+            self.record(log);
+        }
+    }
+
+    /// [`seed`](Self::seed) from stamps, e.g. a store's stamp index.
+    pub fn seed_stamps(&mut self, stamps: impl IntoIterator<Item = LogStamp>) {
+        for stamp in stamps {
+            self.record_stamp(stamp);
+        }
+    }
+
+    /// Logs held back so far.
+    pub fn quarantined(&self) -> &[QuarantinedLog] {
+        &self.quarantine
+    }
+
+    /// Hand over the quarantine (e.g. for review), leaving it empty.
+    pub fn take_quarantined(&mut self) -> Vec<QuarantinedLog> {
+        std::mem::take(&mut self.quarantine)
+    }
+
+    /// Hold a log back for review.
+    pub fn hold(&mut self, log: OutcomeLog, reasons: Vec<AnomalyReason>) {
+        self.quarantine.push(QuarantinedLog { log, reasons });
+    }
+
+    /// Screen a log against everything seen so far, as of now, and record it
+    /// unless it is quarantined.
+    pub fn screen(&mut self, log: &OutcomeLog) -> Screening {
+        self.screen_at(log, SystemTime::now())
+    }
+
+    /// [`screen`](Self::screen) with an explicit clock (for replays and tests).
+    pub fn screen_at(&mut self, log: &OutcomeLog, now: SystemTime) -> Screening {
+        let screening = self.check_at(log, now);
+        if !screening.quarantined() {
+            self.record(log);
+        }
+        screening
+    }
+
+    /// Screen a log without recording it; follow with [`record`](Self::record)
+    /// once the log has been admitted.
+    pub fn check_at(&self, log: &OutcomeLog, now: SystemTime) -> Screening {
+        let mut reasons = Vec::new();
+        let meta = &log.meta;
+
+        if self.seen_stamps.contains(&meta.hex_stamp) {
+            reasons.push(AnomalyReason::DuplicateHexStamp {
+                hex_stamp: meta.hex_stamp.clone(),
+            });
+        }
+
+        let floor = UNIX_EPOCH + Duration::from_secs(self.cfg.earliest_timestamp_secs);
+        let ceiling = now + Duration::from_secs(self.cfg.max_future_skew_secs);
+        if meta.timestamp < floor || meta.timestamp > ceiling {
+            let utc_secs = match meta.timestamp.duration_since(UNIX_EPOCH) {
+                Ok(d) => d.as_secs() as i64,
+                Err(e) => -(e.duration().as_secs() as i64),
+            };
+            reasons.push(AnomalyReason::ImpossibleTimestamp { utc_secs });
+        }
+
+        let identical = self.identical_in_window(log) + 1;
+        if identical > self.cfg.burst_max_identical {
+            reasons.push(AnomalyReason::Burst {
+                operator: meta.bostrom_address.clone(),
+                identical,
+            });
+        }
+
+        if let Some(stats) = self.operators.get(&meta.bostrom_address) {
+            let logs = stats.logs + 1;
+            if logs >= self.cfg.zero_side_effect_min_logs
+                && stats.with_side_effects == 0
+                && !has_side_effects(log)
+            {
+                reasons.push(AnomalyReason::ZeroSideEffects {
+                    operator: meta.bostrom_address.clone(),
+                    logs,
+                });
+            }
+        }
+
+        reasons.retain(|r| r.severity(&self.cfg) > Severity::Ignore);
+        let severity = reasons
+            .iter()
+            .map(|r| r.severity(&self.cfg))
+            .max()
+            .unwrap_or(Severity::Ignore);
+        Screening { severity, reasons }
+    }
+
+    /// Add an admitted log to the history later logs are screened against.
+    pub fn record(&mut self, log: &OutcomeLog) {
+        self.record_stamp(LogStamp::of(log));
+    }
+
+    /// [`record`](Self::record) for a log known only by its stamp.
+    pub fn record_stamp(&mut self, stamp: LogStamp) {
+        self.record_burst(&stamp);
+        let stats = self.operators.entry(stamp.operator).or_default();
+        stats.logs += 1;
+        if stamp.side_effects {
+            stats.with_side_effects += 1;
+        }
+        self.seen_stamps.insert(stamp.hex_stamp);
+    }
+
+    /// Recorded logs identical to `log` from its operator within the burst window.
+    fn identical_in_window(&self, log: &OutcomeLog) -> usize {
+        let window = Duration::from_secs(self.cfg.burst_window_secs);
+        let key = (log.meta.bostrom_address.clone(), fingerprint(log));
+        self.recent.get(&key).map_or(0, |times| {
+            times
+                .iter()
+                .filter(|&&t| within(t, log.meta.timestamp, window))
+                .count()
+        })
+    }
+
+    /// Track this log's fingerprint, dropping entries outside its burst window.
+    /// Once the newest timestamp has moved a full window past the last pruning,
+    /// every entry older than one window behind it is dropped.
+    fn record_burst(&mut self, stamp: &LogStamp) {
+        let window = Duration::from_secs(self.cfg.burst_window_secs);
+        let key = (stamp.operator.clone(), stamp.fingerprint);
+        let times = self.recent.entry(key).or_default();
+        let ts = stamp.timestamp;
+        times.retain(|&t| within(t, ts, window));
+        times.push_back(ts);
+
+        let newest = self.newest.map_or(ts, |n| n.max(ts));
+        self.newest = Some(newest);
+        if self.pruned_at.is_some_and(|p| within(p, newest, window)) {
+            return;
+        }
+        self.recent.retain(|_, times| {
+            times.retain(|&t| within(t, newest, window));
+            !times.is_empty()
+        });
+        self.pruned_at = Some(newest);
+    }
+}
+
+/// Whether `a` and `b` lie at most `window` apart.
+fn within(a: SystemTime, b: SystemTime, window: Duration) -> bool {
+    a.max(b).duration_since(a.min(b)).unwrap_or_default() <= window
+}
+
+/// Any reported incident or non-target kill.
+fn has_side_effects(log: &OutcomeLog) -> bool {
+    let s = &log.side_effects;
+    s.pet_incident
+        || s.wildlife_incident
+        || s.human_injury
+        || s.air_quality_concern
+        || s.non_target_kill_count > 0
+}
+
+/// Hash of everything a log reports apart from its provenance metadata.
+fn fingerprint(log: &OutcomeLog) -> u64 {
+    let content = serde_json::to_string(&(
+        &log.method_id,
+        &log.context,
+        log.effectiveness,
+        &log.side_effects,
+        log.target_count,
+        log.observation_days,
+    ))
+    .unwrap_or_default();
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures::{log, DAY};
+
+    /// A clock some years after every fixture log.
+    fn now() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_800_000_000)
+    }
+
+    /// Identical logs from `bostrom0`, a minute apart, each with its own stamp.
+    fn burst(count: u64) -> Vec<OutcomeLog> {
+        (0..count)
+            .map(|i| {
+                let mut l = log("trap.live", i * 3);
+                l.meta.timestamp = log("trap.live", 0).meta.timestamp + Duration::from_secs(60 * i);
+                l
+            })
+            .collect()
+    }
+
+    #[test]
+    fn duplicate_stamps_are_quarantined() {
+        let mut screen = EvidenceScreen::default();
+        assert!(!screen.screen_at(&log("trap.live", 1), now()).quarantined());
+        let again = screen.screen_at(&log("exclusion.seal", 1), now());
+        assert!(again.quarantined());
+        assert_eq!(
+            again.reasons,
+            [AnomalyReason::DuplicateHexStamp {
+                hex_stamp: "0x0001".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn quarantined_logs_leave_no_history() {
+        let mut screen = EvidenceScreen::default();
+        let mut early = log("trap.live", 1);
+        early.meta.timestamp = UNIX_EPOCH + Duration::from_secs(DAY);
+        let held = screen.screen_at(&early, now());
+        assert!(held.quarantined());
+        assert!(matches!(
+            held.reasons.as_slice(),
+            [AnomalyReason::ImpossibleTimestamp { .. }]
+        ));
+
+        // The genuine log with the same stamp is not shadowed.
+        let genuine = screen.screen_at(&log("trap.live", 1), now());
+        assert_eq!(genuine.severity, Severity::Ignore);
+        assert!(genuine.reasons.is_empty());
+    }
+
+    #[test]
+    fn burst_counts_admitted_logs_only() {
+        let mut screen = EvidenceScreen::default();
+        let screenings: Vec<Screening> = burst(5)
+            .iter()
+            .map(|l| screen.screen_at(l, now()))
+            .collect();
+        assert!(screenings[..3].iter().all(|s| !s.quarantined()));
+        for s in &screenings[3..] {
+            assert_eq!(
+                s.reasons,
+                [AnomalyReason::Burst {
+                    operator: "bostrom0".to_string(),
+                    identical: 4
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn seed_fills_the_burst_history() {
+        let logs = burst(4);
+        let mut screen = EvidenceScreen::default();
+        screen.seed(&logs[..3]);
+        let fourth = screen.check_at(&logs[3], now());
+        assert!(fourth.quarantined());
+        assert!(matches!(
+            fourth.reasons.as_slice(),
+            [AnomalyReason::Burst { identical: 4, .. }]
+        ));
+        // Seeded stamps count as seen, too.
+        assert!(screen.check_at(&logs[0], now()).reasons.contains(
+            &AnomalyReason::DuplicateHexStamp {
+                hex_stamp: logs[0].meta.hex_stamp.clone()
+            }
+        ));
+    }
+
+    #[test]
+    fn burst_history_is_pruned_behind_the_newest_log() {
+        let mut screen = EvidenceScreen::default();
+        // One log a day, each with its own content.
+        for n in 0..50 {
+            let mut l = log("trap.live", n);
+            l.target_count = n as u32;
+            screen.screen_at(&l, now());
+        }
+        assert_eq!(screen.recent.len(), 1);
+        assert_eq!(screen.seen_stamps.len(), 50);
+
+        // A burst inside the newest window is still caught.
+        let logs = burst(4);
+        let shifted: Vec<OutcomeLog> = logs
+            .into_iter()
+            .enumerate()
+            .map(|(i, mut l)| {
+                l.meta.hex_stamp = format!("0xb{i:03}");
+                l.meta.timestamp += Duration::from_secs(60 * DAY);
+                l
+            })
+            .collect();
+        screen.seed(&shifted[..3]);
+        assert!(screen.check_at(&shifted[3], now()).quarantined());
+    }
+
+    #[test]
+    fn operators_without_side_effects_are_flagged_not_held() {
+        let mut screen = EvidenceScreen::new(ScreeningConfig {
+            zero_side_effect_min_logs: 3,
+            ..Default::default()
+        });
+        // Days apart and all from `bostrom0`.
+        screen.seed(&[log("trap.live", 0), log("trap.live", 3)]);
+        let third = screen.screen_at(&log("trap.live", 6), now());
+        assert_eq!(third.severity, Severity::Flag);
+        assert_eq!(
+            third.reasons,
+            [AnomalyReason::ZeroSideEffects {
+                operator: "bostrom0".to_string(),
+                logs: 3
+            }]
+        );
+
+        let mut incident = log("trap.live", 9);
+        incident.side_effects.pet_incident = true;
+        assert!(screen.screen_at(&incident, now()).reasons.is_empty());
+    }
+
+    #[test]
+    fn ignored_checks_report_nothing() {
+        let mut screen = EvidenceScreen::new(ScreeningConfig {
+            duplicate_stamp: Severity::Ignore,
+            ..Default::default()
+        });
+        screen.seed(&[log("trap.live", 1)]);
+        let again = screen.screen_at(&log("trap.live", 1), now());
+        assert_eq!(again.severity, Severity::Ignore);
+        assert!(again.reasons.is_empty());
+    }
+}
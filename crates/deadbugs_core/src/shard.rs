@@ -0,0 +1,1196 @@
+#![forbid(unsafe_code)]
+
+//! Versioned qpudatashard encodings for the core model.
+//!
+//! JSON shards wrap records in a `{schema, version, records}` envelope.
+//! CSV shards start with a `# schema=<name> version=<n>` line followed by a header
+//! and one flat row per record; nested structs are flattened into prefixed columns.
+//! Both encodings round-trip every field, with one CSV caveat: an `Option<String>`
+//! holding an empty string reads back as `None`. Without serde_json's
+//! `float_roundtrip` feature, a JSON float may read back one ulp off.
+//!
+//! The envelope, preamble and error type here are shared with the pest-kernel
+//! shards, which map one value onto several CSV rows via [`write_csv`] and [`read_csv`].
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    CaptureEffectiveness, ControlFamily, ControlMethod, CorridorBand, CorridorBands,
+    CredibleInterval, EffectivenessBand, EvidenceMeta, ExclusionDetails, HygieneContext, KerScore,
+    LocationType, LureType, OutcomeLog, PestContext, PestSpecies, ProximityTags, RiskCoordinates,
+    SideEffects, Stratum,
+};
+use crate::recency::RecencyConfig;
+
+/// Current shard layout version; bump on any breaking field change.
+pub const SHARD_SCHEMA_VERSION: u32 = 1;
+
+/// Errors raised while encoding or decoding shards.
+#[derive(Debug)]
+pub enum ShardError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Csv(csv::Error),
+    /// Shard declares a different record schema than requested.
+    Schema {
+        expected: String,
+        found: String,
+    },
+    /// Shard was written with an unsupported layout version.
+    Version {
+        expected: u32,
+        found: u32,
+    },
+    /// CSV shard is missing or has a malformed `# schema=... version=...` line.
+    MissingPreamble,
+    /// Timestamp earlier than the Unix epoch cannot be stored in UTC columns.
+    TimestampBeforeEpoch,
+    /// Rows of one multi-row value disagree on a shared column.
+    InconsistentRows(&'static str),
+    /// Value has no faithful flat CSV encoding.
+    Unrepresentable(&'static str),
+    /// A single-record shard holds some other number of records.
+    RecordCount {
+        schema: String,
+        found: usize,
+    },
+    /// Record content does not hash to the digest it was stamped with.
+    Integrity {
+        expected: String,
+        found: String,
+    },
+    /// Record decodes but holds a value it cannot be used with.
+    Invalid(String),
+}
+
+impl fmt::Display for ShardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShardError::Io(e) => write!(f, "shard i/o error: {e}"),
+            ShardError::Json(e) => write!(f, "json shard error: {e}"),
+            ShardError::Csv(e) => write!(f, "csv shard error: {e}"),
+            ShardError::Schema { expected, found } => {
+                write!(
+                    f,
+                    "shard schema mismatch: expected {expected}, found {found}"
+                )
+            }
+            ShardError::Version { expected, found } => {
+                write!(f, "unsupported shard version {found} (expected {expected})")
+            }
+            ShardError::MissingPreamble => write!(f, "csv shard is missing its schema preamble"),
+            ShardError::TimestampBeforeEpoch => write!(f, "timestamp precedes the Unix epoch"),
+            ShardError::InconsistentRows(column) => {
+                write!(f, "csv rows disagree on shared column {column}")
+            }
+            ShardError::Unrepresentable(why) => write!(f, "no flat csv encoding: {why}"),
+            ShardError::RecordCount { schema, found } => {
+                write!(f, "expected exactly one {schema} record, found {found}")
+            }
+            ShardError::Integrity { expected, found } => {
+                write!(
+                    f,
+                    "record content hashes to {expected}, but is stamped {found}"
+                )
+            }
+            ShardError::Invalid(why) => write!(f, "invalid record: {why}"),
+        }
+    }
+}
+
+impl std::error::Error for ShardError {}
+
+impl From<std::io::Error> for ShardError {
+    fn from(e: std::io::Error) -> Self {
+        ShardError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ShardError {
+    fn from(e: serde_json::Error) -> Self {
+        ShardError::Json(e)
+    }
+}
+
+impl From<csv::Error> for ShardError {
+    fn from(e: csv::Error) -> Self {
+        ShardError::Csv(e)
+    }
+}
+
+/// A type stored in JSON shards under a named schema.
+pub trait ShardSchema: Serialize + DeserializeOwned + Sized {
+    /// Schema name written into every shard of this record type.
+    const SCHEMA: &'static str;
+}
+
+/// A model type that can be stored as a shard record, in JSON or as a flat CSV row.
+pub trait ShardRecord: ShardSchema {
+    /// Flat CSV row for this record.
+    type Row: Serialize + DeserializeOwned;
+
+    fn to_row(&self) -> Result<Self::Row, ShardError>;
+    fn from_row(row: Self::Row) -> Result<Self, ShardError>;
+}
+
+#[derive(Serialize)]
+struct EnvelopeOut<'a, T> {
+    schema: &'a str,
+    version: u32,
+    records: &'a [T],
+}
+
+/// Records stay raw until the header is checked, so a foreign shard reports
+/// its schema or version rather than whichever field fails to parse first.
+#[derive(Deserialize)]
+struct EnvelopeIn {
+    schema: String,
+    version: u32,
+    records: serde_json::Value,
+}
+
+fn check_header(schema: &str, version: u32, expected: &str) -> Result<(), ShardError> {
+    if schema != expected {
+        return Err(ShardError::Schema {
+            expected: expected.to_string(),
+            found: schema.to_string(),
+        });
+    }
+    if version != SHARD_SCHEMA_VERSION {
+        return Err(ShardError::Version {
+            expected: SHARD_SCHEMA_VERSION,
+            found: version,
+        });
+    }
+    Ok(())
+}
+
+/// Encode records as a versioned JSON shard.
+pub fn to_json<T: ShardSchema>(records: &[T]) -> Result<String, ShardError> {
+    let envelope = EnvelopeOut {
+        schema: T::SCHEMA,
+        version: SHARD_SCHEMA_VERSION,
+        records,
+    };
+    Ok(serde_json::to_string_pretty(&envelope)?)
+}
+
+/// Decode a versioned JSON shard, rejecting foreign schemas and versions.
+pub fn from_json<T: ShardSchema>(json: &str) -> Result<Vec<T>, ShardError> {
+    let envelope: EnvelopeIn = serde_json::from_str(json)?;
+    check_header(&envelope.schema, envelope.version, T::SCHEMA)?;
+    Ok(serde_json::from_value(envelope.records)?)
+}
+
+/// Decode a JSON shard that must hold exactly one record.
+pub fn from_json_one<T: ShardSchema>(json: &str) -> Result<T, ShardError> {
+    Ok(serde_json::from_value(one_record::<T>(json)?)?)
+}
+
+/// [`from_json_one`] for a record whose `meta.hex_stamp` is the [`content_digest`]
+/// of its `field`. The digest covers the field as stored, so a record written
+/// before a defaulted field was added still verifies. This is an integrity check
+/// only: anyone can recompute the digest, so it does not authenticate the record.
+pub fn from_json_stamped<T: ShardSchema>(json: &str, field: &str) -> Result<T, ShardError> {
+    let record = one_record::<T>(json)?;
+    let expected = content_digest(&record[field])?;
+    let found = record["meta"]["hex_stamp"].as_str().unwrap_or_default();
+    if found != expected {
+        return Err(ShardError::Integrity {
+            expected,
+            found: found.to_string(),
+        });
+    }
+    Ok(serde_json::from_value(record)?)
+}
+
+fn one_record<T: ShardSchema>(json: &str) -> Result<serde_json::Value, ShardError> {
+    let envelope: EnvelopeIn = serde_json::from_str(json)?;
+    check_header(&envelope.schema, envelope.version, T::SCHEMA)?;
+    match envelope.records {
+        serde_json::Value::Array(mut records) if records.len() == 1 => Ok(records.remove(0)),
+        records => Err(ShardError::RecordCount {
+            schema: T::SCHEMA.to_string(),
+            found: records.as_array().map_or(0, Vec::len),
+        }),
+    }
+}
+
+/// `0x` plus the FNV-1a 64 hash of `value`'s JSON with keys in sorted order.
+pub fn content_digest<T: Serialize>(value: &T) -> Result<String, ShardError> {
+    // Going through `Value` sorts object keys, so field order does not matter.
+    let canonical = serde_json::to_vec(&serde_json::to_value(value)?)?;
+    Ok(format!("0x{:016x}", fnv1a64(&canonical)))
+}
+
+/// Encode records as a flat, versioned CSV shard.
+pub fn to_csv<T: ShardRecord>(records: &[T]) -> Result<String, ShardError> {
+    let rows = records
+        .iter()
+        .map(ShardRecord::to_row)
+        .collect::<Result<Vec<_>, _>>()?;
+    write_csv(T::SCHEMA, &rows)
+}
+
+/// Decode a flat, versioned CSV shard.
+pub fn from_csv<T: ShardRecord>(text: &str) -> Result<Vec<T>, ShardError> {
+    read_csv::<T::Row>(T::SCHEMA, text)?
+        .into_iter()
+        .map(T::from_row)
+        .collect()
+}
+
+/// Write `rows` under a `# schema=... version=...` preamble.
+pub fn write_csv<R: Serialize>(schema: &str, rows: &[R]) -> Result<String, ShardError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    let body = writer
+        .into_inner()
+        .map_err(|e| ShardError::Csv(e.into_error().into()))?;
+
+    let mut out = format!("# schema={} version={}\n", schema, SHARD_SCHEMA_VERSION);
+    out.push_str(&String::from_utf8_lossy(&body));
+    Ok(out)
+}
+
+/// Read the rows of a CSV shard, rejecting foreign schemas and versions.
+pub fn read_csv<R: DeserializeOwned>(schema: &str, text: &str) -> Result<Vec<R>, ShardError> {
+    let (preamble, body) = text.split_once('\n').unwrap_or((text, ""));
+    let mut found = None;
+    let mut version = None;
+    for part in preamble
+        .strip_prefix('#')
+        .ok_or(ShardError::MissingPreamble)?
+        .split_whitespace()
+    {
+        if let Some(v) = part.strip_prefix("schema=") {
+            found = Some(v);
+        } else if let Some(v) = part.strip_prefix("version=") {
+            version = v.parse::<u32>().ok();
+        }
+    }
+    let (found, version) = found.zip(version).ok_or(ShardError::MissingPreamble)?;
+    check_header(found, version, schema)?;
+
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    Ok(reader.deserialize::<R>().collect::<Result<Vec<_>, _>>()?)
+}
+
+/// Split a system timestamp into UTC seconds + nanoseconds since the Unix epoch.
+pub fn to_utc_parts(ts: SystemTime) -> Result<(u64, u32), ShardError> {
+    let d = ts
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| ShardError::TimestampBeforeEpoch)?;
+    Ok((d.as_secs(), d.subsec_nanos()))
+}
+
+/// Inverse of [`to_utc_parts`].
+pub fn from_utc_parts(secs: u64, nanos: u32) -> SystemTime {
+    UNIX_EPOCH + Duration::new(secs, nanos)
+}
+
+/// FNV-1a 64 over `bytes`: a stable hash for content-derived record IDs.
+pub fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Identity of a record by its provenance: method, operator, hex stamp and time.
+pub fn provenance_key(method_id: &str, meta: &EvidenceMeta) -> String {
+    let ts = meta
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!(
+        "{}|{}|{}|{}.{:09}",
+        method_id,
+        meta.bostrom_address,
+        meta.hex_stamp,
+        ts.as_secs(),
+        ts.subsec_nanos()
+    )
+}
+
+/// Records whose fields are already flat reuse themselves as their CSV row.
+macro_rules! flat_record {
+    ($ty:ty, $schema:expr) => {
+        impl ShardSchema for $ty {
+            const SCHEMA: &'static str = $schema;
+        }
+
+        impl ShardRecord for $ty {
+            type Row = $ty;
+
+            fn to_row(&self) -> Result<Self::Row, ShardError> {
+                Ok(self.clone())
+            }
+
+            fn from_row(row: Self::Row) -> Result<Self, ShardError> {
+                Ok(row)
+            }
+        }
+    };
+}
+
+flat_record!(SideEffects, "DeadbugsSideEffects");
+flat_record!(ProximityTags, "DeadbugsProximityTags");
+flat_record!(ExclusionDetails, "DeadbugsExclusionDetails");
+flat_record!(HygieneContext, "DeadbugsHygieneContext");
+flat_record!(RiskCoordinates, "DeadbugsRiskCoordinates");
+
+/// Flat CSV row for [`EvidenceMeta`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EvidenceMetaRow {
+    pub bostrom_address: String,
+    pub alt_address: Option<String>,
+    pub hex_stamp: String,
+    pub location_cell: String,
+    pub timestamp_utc_secs: u64,
+    pub timestamp_utc_nanos: u32,
+}
+
+impl ShardSchema for EvidenceMeta {
+    const SCHEMA: &'static str = "DeadbugsEvidenceMeta";
+}
+
+impl ShardRecord for EvidenceMeta {
+    type Row = EvidenceMetaRow;
+
+    fn to_row(&self) -> Result<Self::Row, ShardError> {
+        let (secs, nanos) = to_utc_parts(self.timestamp)?;
+        Ok(EvidenceMetaRow {
+            bostrom_address: self.bostrom_address.clone(),
+            alt_address: self.alt_address.clone(),
+            hex_stamp: self.hex_stamp.clone(),
+            location_cell: self.location_cell.clone(),
+            timestamp_utc_secs: secs,
+            timestamp_utc_nanos: nanos,
+        })
+    }
+
+    fn from_row(row: Self::Row) -> Result<Self, ShardError> {
+        Ok(EvidenceMeta {
+            bostrom_address: row.bostrom_address,
+            alt_address: row.alt_address,
+            hex_stamp: row.hex_stamp,
+            location_cell: row.location_cell,
+            timestamp: from_utc_parts(row.timestamp_utc_secs, row.timestamp_utc_nanos),
+        })
+    }
+}
+
+/// Flat CSV row for [`PestContext`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PestContextRow {
+    pub location_type: LocationType,
+    pub pest: PestSpecies,
+    pub children_present: bool,
+    pub pets_present: bool,
+    pub livestock_present: bool,
+    pub wildlife_corridor: bool,
+    pub food_left_out: bool,
+    pub open_garbage: bool,
+    pub standing_water: bool,
+    pub organic_debris: bool,
+    pub cleaning_frequency_per_week: u8,
+    pub building_has_gaps: bool,
+    pub moisture_high: bool,
+    pub food_waste_available: bool,
+}
+
+impl ShardSchema for PestContext {
+    const SCHEMA: &'static str = "DeadbugsPestContext";
+}
+
+impl ShardRecord for PestContext {
+    type Row = PestContextRow;
+
+    fn to_row(&self) -> Result<Self::Row, ShardError> {
+        Ok(PestContextRow {
+            location_type: self.location_type,
+            pest: self.pest,
+            children_present: self.proximity.children_present,
+            pets_present: self.proximity.pets_present,
+            livestock_present: self.proximity.livestock_present,
+            wildlife_corridor: self.proximity.wildlife_corridor,
+            food_left_out: self.hygiene.food_left_out,
+            open_garbage: self.hygiene.open_garbage,
+            standing_water: self.hygiene.standing_water,
+            organic_debris: self.hygiene.organic_debris,
+            cleaning_frequency_per_week: self.hygiene.cleaning_frequency_per_week,
+            building_has_gaps: self.building_has_gaps,
+            moisture_high: self.moisture_high,
+            food_waste_available: self.food_waste_available,
+        })
+    }
+
+    fn from_row(row: Self::Row) -> Result<Self, ShardError> {
+        Ok(PestContext {
+            location_type: row.location_type,
+            pest: row.pest,
+            proximity: ProximityTags {
+                children_present: row.children_present,
+                pets_present: row.pets_present,
+                livestock_present: row.livestock_present,
+                wildlife_corridor: row.wildlife_corridor,
+            },
+            hygiene: HygieneContext {
+                food_left_out: row.food_left_out,
+                open_garbage: row.open_garbage,
+                standing_water: row.standing_water,
+                organic_debris: row.organic_debris,
+                cleaning_frequency_per_week: row.cleaning_frequency_per_week,
+            },
+            building_has_gaps: row.building_has_gaps,
+            moisture_high: row.moisture_high,
+            food_waste_available: row.food_waste_available,
+        })
+    }
+}
+
+/// Flat CSV row for [`ControlMethod`]; `has_exclusion` marks whether the
+/// `exclusion_*` columns carry an `ExclusionDetails` value, and the `revised_utc_*`
+/// columns are empty for a method that was never revised.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ControlMethodRow {
+    pub id: String,
+    pub family: ControlFamily,
+    pub trap_type: Option<String>,
+    pub lure_type: LureType,
+    pub has_exclusion: bool,
+    pub exclusion_sealed_cracks: bool,
+    pub exclusion_door_sweeps_installed: bool,
+    pub exclusion_vents_screened: bool,
+    pub exclusion_pipe_entries_sealed: bool,
+    pub exclusion_notes: Option<String>,
+    pub uses_disposable_electronics: bool,
+    pub generates_persistent_plastic: bool,
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub revised_utc_secs: Option<u64>,
+    #[serde(default)]
+    pub revised_utc_nanos: Option<u32>,
+}
+
+impl ShardSchema for ControlMethod {
+    const SCHEMA: &'static str = "DeadbugsControlMethod";
+}
+
+impl ShardRecord for ControlMethod {
+    type Row = ControlMethodRow;
+
+    fn to_row(&self) -> Result<Self::Row, ShardError> {
+        let ex = self.exclusion.clone().unwrap_or_default();
+        let revised = self.revised_at.map(to_utc_parts).transpose()?;
+        Ok(ControlMethodRow {
+            id: self.id.clone(),
+            family: self.family,
+            trap_type: self.trap_type.clone(),
+            lure_type: self.lure_type,
+            has_exclusion: self.exclusion.is_some(),
+            exclusion_sealed_cracks: ex.sealed_cracks,
+            exclusion_door_sweeps_installed: ex.door_sweeps_installed,
+            exclusion_vents_screened: ex.vents_screened,
+            exclusion_pipe_entries_sealed: ex.pipe_entries_sealed,
+            exclusion_notes: ex.notes,
+            uses_disposable_electronics: self.uses_disposable_electronics,
+            generates_persistent_plastic: self.generates_persistent_plastic,
+            notes: self.notes.clone(),
+            revised_utc_secs: revised.map(|(secs, _)| secs),
+            revised_utc_nanos: revised.map(|(_, nanos)| nanos),
+        })
+    }
+
+    fn from_row(row: Self::Row) -> Result<Self, ShardError> {
+        let exclusion = if row.has_exclusion {
+            Some(ExclusionDetails {
+                sealed_cracks: row.exclusion_sealed_cracks,
+                door_sweeps_installed: row.exclusion_door_sweeps_installed,
+                vents_screened: row.exclusion_vents_screened,
+                pipe_entries_sealed: row.exclusion_pipe_entries_sealed,
+                notes: row.exclusion_notes,
+            })
+        } else {
+            None
+        };
+        Ok(ControlMethod {
+            id: row.id,
+            family: row.family,
+            trap_type: row.trap_type,
+            lure_type: row.lure_type,
+            exclusion,
+            uses_disposable_electronics: row.uses_disposable_electronics,
+            generates_persistent_plastic: row.generates_persistent_plastic,
+            notes: row.notes,
+            revised_at: row
+                .revised_utc_secs
+                .map(|secs| from_utc_parts(secs, row.revised_utc_nanos.unwrap_or(0))),
+        })
+    }
+}
+
+/// Flat CSV row for [`OutcomeLog`]: context, side-effects and evidence metadata inlined.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OutcomeLogRow {
+    pub method_id: String,
+    pub location_type: LocationType,
+    pub pest: PestSpecies,
+    pub children_present: bool,
+    pub pets_present: bool,
+    pub livestock_present: bool,
+    pub wildlife_corridor: bool,
+    pub food_left_out: bool,
+    pub open_garbage: bool,
+    pub standing_water: bool,
+    pub organic_debris: bool,
+    pub cleaning_frequency_per_week: u8,
+    pub building_has_gaps: bool,
+    pub moisture_high: bool,
+    pub food_waste_available: bool,
+    pub effectiveness: EffectivenessBand,
+    pub non_target_kill_count: u32,
+    pub pet_incident: bool,
+    pub wildlife_incident: bool,
+    pub human_injury: bool,
+    pub waste_burden: String,
+    pub air_quality_concern: bool,
+    pub target_count: u32,
+    pub observation_days: u32,
+    pub bostrom_address: String,
+    pub alt_address: Option<String>,
+    pub hex_stamp: String,
+    pub location_cell: String,
+    pub timestamp_utc_secs: u64,
+    pub timestamp_utc_nanos: u32,
+    #[serde(default)]
+    pub recommendation_id: Option<String>,
+}
+
+impl ShardSchema for OutcomeLog {
+    const SCHEMA: &'static str = "DeadbugsOutcomeLog";
+}
+
+impl ShardRecord for OutcomeLog {
+    type Row = OutcomeLogRow;
+
+    fn to_row(&self) -> Result<Self::Row, ShardError> {
+        let ctx = self.context.to_row()?;
+        let meta = self.meta.to_row()?;
+        let side = &self.side_effects;
+        Ok(OutcomeLogRow {
+            method_id: self.method_id.clone(),
+            location_type: ctx.location_type,
+            pest: ctx.pest,
+            children_present: ctx.children_present,
+            pets_present: ctx.pets_present,
+            livestock_present: ctx.livestock_present,
+            wildlife_corridor: ctx.wildlife_corridor,
+            food_left_out: ctx.food_left_out,
+            open_garbage: ctx.open_garbage,
+            standing_water: ctx.standing_water,
+            organic_debris: ctx.organic_debris,
+            cleaning_frequency_per_week: ctx.cleaning_frequency_per_week,
+            building_has_gaps: ctx.building_has_gaps,
+            moisture_high: ctx.moisture_high,
+            food_waste_available: ctx.food_waste_available,
+            effectiveness: self.effectiveness,
+            non_target_kill_count: side.non_target_kill_count,
+            pet_incident: side.pet_incident,
+            wildlife_incident: side.wildlife_incident,
+            human_injury: side.human_injury,
+            waste_burden: side.waste_burden.clone(),
+            air_quality_concern: side.air_quality_concern,
+            target_count: self.target_count,
+            observation_days: self.observation_days,
+            bostrom_address: meta.bostrom_address,
+            alt_address: meta.alt_address,
+            hex_stamp: meta.hex_stamp,
+            location_cell: meta.location_cell,
+            timestamp_utc_secs: meta.timestamp_utc_secs,
+            timestamp_utc_nanos: meta.timestamp_utc_nanos,
+            recommendation_id: self.recommendation_id.clone(),
+        })
+    }
+
+    fn from_row(row: Self::Row) -> Result<Self, ShardError> {
+        let context = PestContext::from_row(PestContextRow {
+            location_type: row.location_type,
+            pest: row.pest,
+            children_present: row.children_present,
+            pets_present: row.pets_present,
+            livestock_present: row.livestock_present,
+            wildlife_corridor: row.wildlife_corridor,
+            food_left_out: row.food_left_out,
+            open_garbage: row.open_garbage,
+            standing_water: row.standing_water,
+            organic_debris: row.organic_debris,
+            cleaning_frequency_per_week: row.cleaning_frequency_per_week,
+            building_has_gaps: row.building_has_gaps,
+            moisture_high: row.moisture_high,
+            food_waste_available: row.food_waste_available,
+        })?;
+        let meta = EvidenceMeta::from_row(EvidenceMetaRow {
+            bostrom_address: row.bostrom_address,
+            alt_address: row.alt_address,
+            hex_stamp: row.hex_stamp,
+            location_cell: row.location_cell,
+            timestamp_utc_secs: row.timestamp_utc_secs,
+            timestamp_utc_nanos: row.timestamp_utc_nanos,
+        })?;
+        Ok(OutcomeLog {
+            method_id: row.method_id,
+            context,
+            effectiveness: row.effectiveness,
+            side_effects: SideEffects {
+                non_target_kill_count: row.non_target_kill_count,
+                pet_incident: row.pet_incident,
+                wildlife_incident: row.wildlife_incident,
+                human_injury: row.human_injury,
+                waste_burden: row.waste_burden,
+                air_quality_concern: row.air_quality_concern,
+            },
+            target_count: row.target_count,
+            observation_days: row.observation_days,
+            meta,
+            recommendation_id: row.recommendation_id,
+        })
+    }
+}
+
+/// A `KerScore` keyed by the method it was computed for.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KerScoreRecord {
+    pub method_id: String,
+    pub score: KerScore,
+}
+
+/// Flat CSV row for [`KerScoreRecord`]; each corridor carries its mean, credible bounds and band.
+/// Columns added after the first shard version may be absent: lower bounds then read
+/// 0, upper bounds 1, incidence 0, bands safe, counts 0 and `config_id` empty.
+/// `has_recency` marks whether the `recency_*` columns carry a `RecencyConfig`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct KerScoreRow {
+    pub method_id: String,
+    pub k: f64,
+    #[serde(default)]
+    pub k_lower: f64,
+    #[serde(default = "unit")]
+    pub k_upper: f64,
+    pub e: f64,
+    pub r: f64,
+    #[serde(default)]
+    pub r_lower: f64,
+    #[serde(default = "unit")]
+    pub r_upper: f64,
+    #[serde(default)]
+    pub n_effective: f64,
+    #[serde(default)]
+    pub captures_per_day: f64,
+    #[serde(default)]
+    pub capture_decline: Option<f64>,
+    #[serde(default)]
+    pub capture_paired_logs: u32,
+    #[serde(default)]
+    pub capture_agreeing_logs: u32,
+    pub r_pets: f64,
+    #[serde(default)]
+    pub r_pets_lower: f64,
+    #[serde(default = "unit")]
+    pub r_pets_upper: f64,
+    pub r_wildlife: f64,
+    #[serde(default)]
+    pub r_wildlife_lower: f64,
+    #[serde(default = "unit")]
+    pub r_wildlife_upper: f64,
+    pub r_waste: f64,
+    #[serde(default)]
+    pub r_waste_lower: f64,
+    #[serde(default = "unit")]
+    pub r_waste_upper: f64,
+    pub r_air: f64,
+    #[serde(default)]
+    pub r_air_lower: f64,
+    #[serde(default = "unit")]
+    pub r_air_upper: f64,
+    pub r_human_injury: f64,
+    #[serde(default)]
+    pub r_human_injury_lower: f64,
+    #[serde(default = "unit")]
+    pub r_human_injury_upper: f64,
+    #[serde(default)]
+    pub r_bycatch: f64,
+    #[serde(default)]
+    pub r_bycatch_lower: f64,
+    #[serde(default = "unit")]
+    pub r_bycatch_upper: f64,
+    #[serde(default)]
+    pub incidence_pets: f64,
+    #[serde(default)]
+    pub incidence_wildlife: f64,
+    #[serde(default)]
+    pub incidence_waste: f64,
+    #[serde(default)]
+    pub incidence_air: f64,
+    #[serde(default)]
+    pub incidence_human_injury: f64,
+    #[serde(default)]
+    pub incidence_bycatch: f64,
+    #[serde(default)]
+    pub band_pets: CorridorBand,
+    #[serde(default)]
+    pub band_wildlife: CorridorBand,
+    #[serde(default)]
+    pub band_waste: CorridorBand,
+    #[serde(default)]
+    pub band_air: CorridorBand,
+    #[serde(default)]
+    pub band_human_injury: CorridorBand,
+    #[serde(default)]
+    pub band_bycatch: CorridorBand,
+    pub hard_violation: bool,
+    #[serde(default)]
+    pub stratum: Stratum,
+    #[serde(default)]
+    pub config_id: String,
+    #[serde(default)]
+    pub has_recency: bool,
+    #[serde(default)]
+    pub recency_half_life_days: Option<f64>,
+    #[serde(default)]
+    pub recency_window_days: Option<f64>,
+    #[serde(default)]
+    pub recency_as_of_utc_secs: Option<u64>,
+    #[serde(default)]
+    pub recency_as_of_utc_nanos: Option<u32>,
+}
+
+fn unit() -> f64 {
+    1.0
+}
+
+impl ShardSchema for KerScoreRecord {
+    const SCHEMA: &'static str = "DeadbugsKerScore";
+}
+
+impl ShardRecord for KerScoreRecord {
+    type Row = KerScoreRow;
+
+    fn to_row(&self) -> Result<Self::Row, ShardError> {
+        let s = &self.score;
+        let (lo, hi) = (&s.coords_lower, &s.coords_upper);
+        let recency = s.recency.clone().unwrap_or_default();
+        let as_of = recency.as_of.map(to_utc_parts).transpose()?;
+        Ok(KerScoreRow {
+            method_id: self.method_id.clone(),
+            k: s.k,
+            k_lower: s.k_interval.lower,
+            k_upper: s.k_interval.upper,
+            e: s.e,
+            r: s.r,
+            r_lower: s.r_interval.lower,
+            r_upper: s.r_interval.upper,
+            n_effective: s.n_effective,
+            captures_per_day: s.capture.captures_per_day,
+            capture_decline: s.capture.decline,
+            capture_paired_logs: s.capture.paired_logs,
+            capture_agreeing_logs: s.capture.agreeing_logs,
+            r_pets: s.coords.r_pets,
+            r_pets_lower: lo.r_pets,
+            r_pets_upper: hi.r_pets,
+            r_wildlife: s.coords.r_wildlife,
+            r_wildlife_lower: lo.r_wildlife,
+            r_wildlife_upper: hi.r_wildlife,
+            r_waste: s.coords.r_waste,
+            r_waste_lower: lo.r_waste,
+            r_waste_upper: hi.r_waste,
+            r_air: s.coords.r_air,
+            r_air_lower: lo.r_air,
+            r_air_upper: hi.r_air,
+            r_human_injury: s.coords.r_human_injury,
+            r_human_injury_lower: lo.r_human_injury,
+            r_human_injury_upper: hi.r_human_injury,
+            r_bycatch: s.coords.r_bycatch,
+            r_bycatch_lower: lo.r_bycatch,
+            r_bycatch_upper: hi.r_bycatch,
+            incidence_pets: s.incidence.r_pets,
+            incidence_wildlife: s.incidence.r_wildlife,
+            incidence_waste: s.incidence.r_waste,
+            incidence_air: s.incidence.r_air,
+            incidence_human_injury: s.incidence.r_human_injury,
+            incidence_bycatch: s.incidence.r_bycatch,
+            band_pets: s.bands.pets,
+            band_wildlife: s.bands.wildlife,
+            band_waste: s.bands.waste,
+            band_air: s.bands.air,
+            band_human_injury: s.bands.human_injury,
+            band_bycatch: s.bands.bycatch,
+            hard_violation: s.hard_violation,
+            stratum: s.stratum,
+            config_id: s.config_id.clone(),
+            has_recency: s.recency.is_some(),
+            recency_half_life_days: recency.half_life_days,
+            recency_window_days: recency.window_days,
+            recency_as_of_utc_secs: as_of.map(|(secs, _)| secs),
+            recency_as_of_utc_nanos: as_of.map(|(_, nanos)| nanos),
+        })
+    }
+
+    fn from_row(row: Self::Row) -> Result<Self, ShardError> {
+        Ok(KerScoreRecord {
+            method_id: row.method_id,
+            score: KerScore {
+                k: row.k,
+                e: row.e,
+                r: row.r,
+                k_interval: CredibleInterval {
+                    lower: row.k_lower,
+                    upper: row.k_upper,
+                },
+                r_interval: CredibleInterval {
+                    lower: row.r_lower,
+                    upper: row.r_upper,
+                },
+                n_effective: row.n_effective,
+                capture: CaptureEffectiveness {
+                    captures_per_day: row.captures_per_day,
+                    decline: row.capture_decline,
+                    paired_logs: row.capture_paired_logs,
+                    agreeing_logs: row.capture_agreeing_logs,
+                },
+                coords: RiskCoordinates {
+                    r_pets: row.r_pets,
+                    r_wildlife: row.r_wildlife,
+                    r_waste: row.r_waste,
+                    r_air: row.r_air,
+                    r_human_injury: row.r_human_injury,
+                    r_bycatch: row.r_bycatch,
+                },
+                coords_lower: RiskCoordinates {
+                    r_pets: row.r_pets_lower,
+                    r_wildlife: row.r_wildlife_lower,
+                    r_waste: row.r_waste_lower,
+                    r_air: row.r_air_lower,
+                    r_human_injury: row.r_human_injury_lower,
+                    r_bycatch: row.r_bycatch_lower,
+                },
+                coords_upper: RiskCoordinates {
+                    r_pets: row.r_pets_upper,
+                    r_wildlife: row.r_wildlife_upper,
+                    r_waste: row.r_waste_upper,
+                    r_air: row.r_air_upper,
+                    r_human_injury: row.r_human_injury_upper,
+                    r_bycatch: row.r_bycatch_upper,
+                },
+                incidence: RiskCoordinates {
+                    r_pets: row.incidence_pets,
+                    r_wildlife: row.incidence_wildlife,
+                    r_waste: row.incidence_waste,
+                    r_air: row.incidence_air,
+                    r_human_injury: row.incidence_human_injury,
+                    r_bycatch: row.incidence_bycatch,
+                },
+                bands: CorridorBands {
+                    pets: row.band_pets,
+                    wildlife: row.band_wildlife,
+                    waste: row.band_waste,
+                    air: row.band_air,
+                    human_injury: row.band_human_injury,
+                    bycatch: row.band_bycatch,
+                },
+                hard_violation: row.hard_violation,
+                stratum: row.stratum,
+                config_id: row.config_id,
+                recency: row.has_recency.then(|| RecencyConfig {
+                    half_life_days: row.recency_half_life_days,
+                    window_days: row.recency_window_days,
+                    as_of: row
+                        .recency_as_of_utc_secs
+                        .map(|secs| from_utc_parts(secs, row.recency_as_of_utc_nanos.unwrap_or(0))),
+                }),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures::{context, log, meta, method};
+    use crate::model::Stratum;
+
+    /// Both encodings must give back a value that serializes identically, floats
+    /// aside, which may come back one ulp off.
+    fn round_trip<T: ShardRecord>(values: &[T]) {
+        let want = serde_json::to_value(values).unwrap();
+        let json = from_json::<T>(&to_json(values).unwrap()).unwrap();
+        assert_same(&serde_json::to_value(&json).unwrap(), &want, "json");
+        let csv = from_csv::<T>(&to_csv(values).unwrap()).unwrap();
+        assert_same(&serde_json::to_value(&csv).unwrap(), &want, "csv");
+    }
+
+    fn assert_same(got: &serde_json::Value, want: &serde_json::Value, path: &str) {
+        use serde_json::Value;
+        match (got, want) {
+            (Value::Number(g), Value::Number(w)) if g.is_f64() || w.is_f64() => {
+                let (g, w) = (g.as_f64().unwrap(), w.as_f64().unwrap());
+                assert!(
+                    (g - w).abs() <= 1e-12 * w.abs().max(1.0),
+                    "{path}: {g} != {w}"
+                );
+            }
+            (Value::Array(g), Value::Array(w)) => {
+                assert_eq!(g.len(), w.len(), "{path}: length");
+                for (i, (g, w)) in g.iter().zip(w).enumerate() {
+                    assert_same(g, w, &format!("{path}[{i}]"));
+                }
+            }
+            (Value::Object(g), Value::Object(w)) => {
+                assert!(g.keys().eq(w.keys()), "{path}: fields");
+                for (key, w) in w {
+                    assert_same(&g[key], w, &format!("{path}.{key}"));
+                }
+            }
+            _ => assert_eq!(got, want, "{path}"),
+        }
+    }
+
+    fn coords(base: f64) -> RiskCoordinates {
+        RiskCoordinates {
+            r_pets: base,
+            r_wildlife: base + 0.01,
+            r_waste: base + 0.02,
+            r_air: base + 0.03,
+            r_human_injury: base + 0.04,
+            r_bycatch: base + 0.05,
+        }
+    }
+
+    fn score() -> KerScore {
+        KerScore {
+            k: 0.81,
+            e: 0.72,
+            r: 0.13,
+            k_interval: CredibleInterval {
+                lower: 0.7,
+                upper: 0.9,
+            },
+            r_interval: CredibleInterval {
+                lower: 0.05,
+                upper: 0.3,
+            },
+            n_effective: 12.5,
+            capture: CaptureEffectiveness {
+                captures_per_day: 0.4,
+                decline: Some(0.6),
+                paired_logs: 3,
+                agreeing_logs: 2,
+            },
+            coords: coords(0.1),
+            coords_lower: coords(0.0),
+            coords_upper: coords(0.2),
+            incidence: coords(0.3),
+            bands: CorridorBands {
+                pets: CorridorBand::Gold,
+                human_injury: CorridorBand::Hard,
+                ..Default::default()
+            },
+            hard_violation: true,
+            stratum: Stratum::Location,
+            config_id: "cfg-1".to_string(),
+            recency: Some(RecencyConfig {
+                as_of: Some(meta(9).timestamp),
+                ..RecencyConfig::decay()
+            }),
+        }
+    }
+
+    #[test]
+    fn flat_records_round_trip() {
+        round_trip(&[
+            SideEffects::default(),
+            SideEffects {
+                non_target_kill_count: 2,
+                pet_incident: true,
+                wildlife_incident: true,
+                human_injury: true,
+                waste_burden: "high, bagged".to_string(),
+                air_quality_concern: true,
+            },
+        ]);
+        round_trip(&[ProximityTags {
+            children_present: true,
+            wildlife_corridor: true,
+            ..Default::default()
+        }]);
+        round_trip(&[ExclusionDetails {
+            sealed_cracks: true,
+            notes: Some("north wall, \"weep\" holes".to_string()),
+            ..Default::default()
+        }]);
+        round_trip(&[HygieneContext {
+            open_garbage: true,
+            cleaning_frequency_per_week: 7,
+            ..Default::default()
+        }]);
+        round_trip(&[coords(0.3)]);
+    }
+
+    #[test]
+    fn evidence_meta_round_trips() {
+        let mut signed = meta(3);
+        signed.alt_address = Some("bostrom-alt".to_string());
+        round_trip(&[meta(0), signed]);
+    }
+
+    #[test]
+    fn pest_context_round_trips() {
+        let mut busy = context(LocationType::Restaurant);
+        busy.pest = PestSpecies::Cockroach;
+        busy.proximity.children_present = true;
+        busy.hygiene.standing_water = true;
+        busy.hygiene.cleaning_frequency_per_week = 14;
+        round_trip(&[context(LocationType::Home), busy]);
+    }
+
+    #[test]
+    fn control_method_round_trips() {
+        let mut detailed = method("exclusion.full", ControlFamily::Exclusion);
+        detailed.trap_type = Some("none".to_string());
+        detailed.lure_type = LureType::FoodGradeBait;
+        detailed.exclusion = Some(ExclusionDetails {
+            door_sweeps_installed: true,
+            vents_screened: true,
+            notes: Some("all vents".to_string()),
+            ..Default::default()
+        });
+        detailed.uses_disposable_electronics = true;
+        detailed.generates_persistent_plastic = true;
+        detailed.notes = Some("retrofit".to_string());
+        detailed.revised_at = Some(meta(3).timestamp);
+        round_trip(&[method("trap.snap", ControlFamily::MechanicalKill), detailed]);
+    }
+
+    #[test]
+    fn outcome_log_round_trips() {
+        let mut followup = log("trap.snap", 9);
+        followup.effectiveness = EffectivenessBand::Low;
+        followup.side_effects.non_target_kill_count = 1;
+        followup.side_effects.pet_incident = true;
+        followup.meta.alt_address = Some("bostrom-alt".to_string());
+        followup.recommendation_id = Some("rec-0001".to_string());
+        round_trip(&[log("trap.snap", 1), followup]);
+    }
+
+    #[test]
+    fn ker_score_round_trips() {
+        let mut sparse = score();
+        sparse.capture.decline = None;
+        sparse.stratum = Stratum::Pooled;
+        sparse.recency = Some(RecencyConfig::uniform());
+        let mut unrecorded = score();
+        unrecorded.recency = None;
+        round_trip(&[
+            KerScoreRecord {
+                method_id: "m0".to_string(),
+                score: unrecorded,
+            },
+            KerScoreRecord {
+                method_id: "m1".to_string(),
+                score: score(),
+            },
+            KerScoreRecord {
+                method_id: "m2".to_string(),
+                score: sparse,
+            },
+        ]);
+    }
+
+    #[test]
+    fn rejects_foreign_schema_and_version() {
+        let json = to_json(&[meta(0)]).unwrap();
+        assert!(matches!(
+            from_json::<PestContext>(&json),
+            Err(ShardError::Schema { .. })
+        ));
+        let bumped = json.replace("\"version\": 1", "\"version\": 99");
+        assert!(matches!(
+            from_json::<EvidenceMeta>(&bumped),
+            Err(ShardError::Version { found: 99, .. })
+        ));
+
+        let csv = to_csv(&[meta(0)]).unwrap();
+        assert!(matches!(
+            from_csv::<PestContext>(&csv),
+            Err(ShardError::Schema { .. })
+        ));
+        let body = csv.split_once('\n').unwrap().1;
+        assert!(matches!(
+            from_csv::<EvidenceMeta>(body),
+            Err(ShardError::MissingPreamble)
+        ));
+    }
+
+    #[test]
+    fn rejects_timestamps_before_the_epoch() {
+        let mut early = meta(0);
+        early.timestamp = UNIX_EPOCH - Duration::from_secs(1);
+        assert!(matches!(
+            to_csv(&[early]),
+            Err(ShardError::TimestampBeforeEpoch)
+        ));
+    }
+
+    #[test]
+    fn content_keys_are_stable() {
+        assert_eq!(fnv1a64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a64(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(
+            provenance_key("m1", &meta(0)),
+            "m1|bostrom0|0x0000|1700000000.000000005"
+        );
+    }
+
+    #[test]
+    fn first_version_score_shards_still_load() {
+        let json = r#"{
+            "schema": "DeadbugsKerScore",
+            "version": 1,
+            "records": [{
+                "method_id": "trap.live",
+                "score": {
+                    "k": 0.7, "e": 0.6, "r": 0.2,
+                    "coords": {"r_pets": 0.1, "r_wildlife": 0.0, "r_waste": 0.3,
+                               "r_air": 0.0, "r_human_injury": 0.0},
+                    "hard_violation": false
+                }
+            }]
+        }"#;
+        let json = &from_json::<KerScoreRecord>(json).unwrap()[0].score;
+        let csv = "# schema=DeadbugsKerScore version=1\n\
+                   method_id,k,e,r,r_pets,r_wildlife,r_waste,r_air,r_human_injury,hard_violation\n\
+                   trap.live,0.7,0.6,0.2,0.1,0.0,0.3,0.0,0.0,false\n";
+        let csv = &from_csv::<KerScoreRecord>(csv).unwrap()[0].score;
+        for s in [json, csv] {
+            assert_eq!(s.k, 0.7);
+            assert_eq!(s.coords.r_waste, 0.3);
+            assert_eq!(s.k_interval, CredibleInterval::unit());
+            assert_eq!(s.r_interval, CredibleInterval::unit());
+            assert_eq!(s.coords_lower.r_pets, 0.0);
+            assert_eq!(s.coords_upper.r_pets, 1.0);
+            assert_eq!(s.coords_upper.r_bycatch, 1.0);
+            assert_eq!(s.bands.pets, CorridorBand::Safe);
+            assert!(!s.hard_violation);
+            assert_eq!(s.config_id, "");
+        }
+    }
+}
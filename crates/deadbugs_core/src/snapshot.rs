@@ -0,0 +1,543 @@
+#![forbid(unsafe_code)]
+
+//! Immutable registry snapshots and ranking diffs.
+//!
+//! A `RegistrySnapshot` freezes everything a query depends on (methods, lifecycle
+//! states, logs, scoring config and eligibility policy) under an ID derived from
+//! its content. `diff_snapshots` runs the same query against two snapshots and
+//! lists the methods whose rank, K, E, R or eligibility moved, each with the logs
+//! for that method that were added or removed in between, and names any change in
+//! the scoring model, recency reference time or eligibility policy.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::explain::Rejection;
+use crate::ker::KerConfig;
+use crate::lifecycle::LifecycleState;
+use crate::model::{ControlMethod, OutcomeLog, PestContext};
+use crate::policy::EligibilityPolicy;
+use crate::query::{MethodRegistry, QueryOptions};
+use crate::recency::RecencyConfig;
+use crate::shard::{
+    content_digest, fnv1a64, from_json_one, provenance_key, to_json, ShardError, ShardSchema,
+};
+use crate::store::StoreError;
+
+/// Frozen registry contents; build with `MethodRegistry::snapshot`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RegistrySnapshot {
+    snapshot_id: String,
+    label: String,
+    taken_at: SystemTime,
+    /// Recency is pinned to `taken_at`, so the snapshot scores the same later.
+    config: KerConfig,
+    policy: EligibilityPolicy,
+    methods: Vec<ControlMethod>,
+    lifecycle: BTreeMap<String, LifecycleState>,
+    logs: Vec<OutcomeLog>,
+}
+
+impl ShardSchema for RegistrySnapshot {
+    const SCHEMA: &'static str = "DeadbugsRegistrySnapshot";
+}
+
+impl RegistrySnapshot {
+    /// Fails only if the content cannot be serialized to compute its ID.
+    pub fn new(
+        label: &str,
+        taken_at: SystemTime,
+        config: KerConfig,
+        policy: EligibilityPolicy,
+        methods: Vec<ControlMethod>,
+        lifecycle: BTreeMap<String, LifecycleState>,
+        logs: Vec<OutcomeLog>,
+    ) -> Result<Self, ShardError> {
+        let mut snapshot = Self {
+            snapshot_id: String::new(),
+            label: label.to_string(),
+            taken_at,
+            config,
+            policy,
+            methods,
+            lifecycle,
+            logs,
+        };
+        snapshot.snapshot_id = snapshot.content_id()?;
+        Ok(snapshot)
+    }
+
+    /// `snap-` plus a hash of the config, policy, methods, lifecycle and logs.
+    pub fn snapshot_id(&self) -> &str {
+        &self.snapshot_id
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn taken_at(&self) -> SystemTime {
+        self.taken_at
+    }
+
+    pub fn config(&self) -> &KerConfig {
+        &self.config
+    }
+
+    pub fn policy(&self) -> &EligibilityPolicy {
+        &self.policy
+    }
+
+    pub fn methods(&self) -> &[ControlMethod] {
+        &self.methods
+    }
+
+    /// Lifecycle state of every method.
+    pub fn lifecycle(&self) -> &BTreeMap<String, LifecycleState> {
+        &self.lifecycle
+    }
+
+    pub fn logs(&self) -> &[OutcomeLog] {
+        &self.logs
+    }
+
+    /// True when the content still hashes to `snapshot_id`.
+    pub fn is_intact(&self) -> bool {
+        self.content_id().is_ok_and(|id| id == self.snapshot_id)
+    }
+
+    fn content_id(&self) -> Result<String, ShardError> {
+        let content = serde_json::to_vec(&(
+            &self.config,
+            &self.policy,
+            &self.methods,
+            &self.lifecycle,
+            &self.logs,
+        ))?;
+        Ok(format!("snap-{:016x}", fnv1a64(&content)))
+    }
+
+    /// Write the snapshot as a single-record JSON shard.
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), ShardError> {
+        fs::write(path, to_json(std::slice::from_ref(self))?)?;
+        Ok(())
+    }
+
+    /// Load the single snapshot record of a JSON shard file, rejecting it unless
+    /// its content still hashes to its `snapshot_id`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ShardError> {
+        let snapshot: Self = from_json_one(&fs::read_to_string(path)?)?;
+        let found = snapshot.content_id()?;
+        if found != snapshot.snapshot_id {
+            return Err(ShardError::Integrity {
+                expected: snapshot.snapshot_id,
+                found,
+            });
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Where one method stood in one snapshot's query.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MethodStanding {
+    /// 1-based position among accepted methods; `None` when rejected.
+    pub rank: Option<usize>,
+    pub k: f64,
+    pub e: f64,
+    pub r: f64,
+    /// Empty when the method passed every filter.
+    pub rejections: Vec<Rejection>,
+}
+
+impl MethodStanding {
+    pub fn eligible(&self) -> bool {
+        self.rejections.is_empty()
+    }
+}
+
+/// A standing that moved between snapshots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangedField {
+    Rank,
+    K,
+    E,
+    R,
+    Eligibility,
+}
+
+/// A log named in a diff.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LogRef {
+    pub bostrom_address: String,
+    pub hex_stamp: String,
+    pub timestamp: SystemTime,
+}
+
+impl LogRef {
+    fn new(log: &OutcomeLog) -> Self {
+        Self {
+            bostrom_address: log.meta.bostrom_address.clone(),
+            hex_stamp: log.meta.hex_stamp.clone(),
+            timestamp: log.meta.timestamp,
+        }
+    }
+}
+
+/// One method whose standing changed, with the evidence behind the change.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MethodChange {
+    pub method_id: String,
+    /// `None` when the method is not in that snapshot.
+    pub before: Option<MethodStanding>,
+    pub after: Option<MethodStanding>,
+    pub changed: Vec<ChangedField>,
+    /// Logs for this method and pest only in the later snapshot.
+    pub added_logs: Vec<LogRef>,
+    /// Logs for this method and pest only in the earlier snapshot.
+    pub removed_logs: Vec<LogRef>,
+    /// Lifecycle state before and after, when it changed.
+    pub lifecycle: Option<(LifecycleState, LifecycleState)>,
+}
+
+/// A change in what the query was scored or filtered under.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigChange {
+    /// Scoring constants differ, by config ID (the IDs match when a config was
+    /// edited in place).
+    Model { before: String, after: String },
+    /// Recency is aged from a different time and decays or windows logs, so
+    /// unchanged evidence can score differently.
+    RecencyAsOf {
+        before: Option<SystemTime>,
+        after: Option<SystemTime>,
+    },
+    /// Eligibility rules or priorities differ, by policy ID.
+    Policy { before: String, after: String },
+}
+
+/// Changes in one query's results between two snapshots.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub before_id: String,
+    pub after_id: String,
+    /// The scoring model, recency reference time or eligibility policy changed;
+    /// `config_changes` says which.
+    pub config_changed: bool,
+    pub config_changes: Vec<ConfigChange>,
+    /// Logs added and removed across the whole corpus.
+    pub added_logs: usize,
+    pub removed_logs: usize,
+    /// Changed methods by ID. A change with no logs or lifecycle change of its own
+    /// comes from other methods moving, a rules change, or evidence aging.
+    pub methods: Vec<MethodChange>,
+}
+
+impl SnapshotDiff {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Run the query for `ctx` against both snapshots and report what changed.
+/// K, E and R count as changed when they move by more than `tolerance`.
+pub fn diff_snapshots(
+    before: &RegistrySnapshot,
+    after: &RegistrySnapshot,
+    ctx: &PestContext,
+    opts: &QueryOptions,
+    tolerance: f64,
+) -> Result<SnapshotDiff, StoreError> {
+    let mut old = standings(before, ctx, opts)?;
+    let mut new = standings(after, ctx, opts)?;
+
+    let old_logs = keyed_logs(before);
+    let new_logs = keyed_logs(after);
+    let added: Vec<&OutcomeLog> = new_logs
+        .iter()
+        .filter(|(key, _)| !old_logs.contains_key(*key))
+        .map(|(_, &log)| log)
+        .collect();
+    let removed: Vec<&OutcomeLog> = old_logs
+        .iter()
+        .filter(|(key, _)| !new_logs.contains_key(*key))
+        .map(|(_, &log)| log)
+        .collect();
+    let refs_for = |logs: &[&OutcomeLog], method_id: &str| -> Vec<LogRef> {
+        logs.iter()
+            .filter(|l| l.method_id == method_id && l.context.pest == ctx.pest)
+            .map(|l| LogRef::new(l))
+            .collect()
+    };
+
+    let ids: BTreeSet<String> = old.keys().chain(new.keys()).cloned().collect();
+    let mut methods = Vec::new();
+    for id in ids {
+        let b = old.remove(&id);
+        let a = new.remove(&id);
+        let changed = changed_fields(b.as_ref(), a.as_ref(), tolerance);
+        if changed.is_empty() {
+            continue;
+        }
+        let lifecycle = match (before.lifecycle.get(&id), after.lifecycle.get(&id)) {
+            (Some(&from), Some(&to)) if from != to => Some((from, to)),
+            _ => None,
+        };
+        methods.push(MethodChange {
+            added_logs: refs_for(&added, &id),
+            removed_logs: refs_for(&removed, &id),
+            method_id: id,
+            before: b,
+            after: a,
+            changed,
+            lifecycle,
+        });
+    }
+
+    let config_changes = config_changes(before, after)?;
+    Ok(SnapshotDiff {
+        before_id: before.snapshot_id.clone(),
+        after_id: after.snapshot_id.clone(),
+        config_changed: !config_changes.is_empty(),
+        config_changes,
+        added_logs: added.len(),
+        removed_logs: removed.len(),
+        methods,
+    })
+}
+
+/// What differs between the two snapshots' scoring and eligibility setups.
+fn config_changes(
+    before: &RegistrySnapshot,
+    after: &RegistrySnapshot,
+) -> Result<Vec<ConfigChange>, ShardError> {
+    // Compare the model without the recency reference time, reported on its own.
+    let model = |snapshot: &RegistrySnapshot| {
+        let mut config = snapshot.config.clone();
+        config.recency.as_of = None;
+        content_digest(&config)
+    };
+    let mut changes = Vec::new();
+    if model(before)? != model(after)? {
+        changes.push(ConfigChange::Model {
+            before: before.config.config_id.clone(),
+            after: after.config.config_id.clone(),
+        });
+    }
+    let (old, new) = (&before.config.recency, &after.config.recency);
+    let ages = |r: &RecencyConfig| r.half_life_days.is_some() || r.window_days.is_some();
+    if old.as_of != new.as_of && (ages(old) || ages(new)) {
+        changes.push(ConfigChange::RecencyAsOf {
+            before: old.as_of,
+            after: new.as_of,
+        });
+    }
+    if content_digest(&before.policy)? != content_digest(&after.policy)? {
+        changes.push(ConfigChange::Policy {
+            before: before.policy.policy_id.clone(),
+            after: after.policy.policy_id.clone(),
+        });
+    }
+    Ok(changes)
+}
+
+/// Every method's standing in `snapshot` for the query, by method ID.
+fn standings(
+    snapshot: &RegistrySnapshot,
+    ctx: &PestContext,
+    opts: &QueryOptions,
+) -> Result<BTreeMap<String, MethodStanding>, StoreError> {
+    let registry = MethodRegistry::from_snapshot(snapshot);
+    let mut accepted = 0;
+    Ok(registry
+        .explain_safest_methods_in_context(ctx, opts)?
+        .into_iter()
+        .map(|x| {
+            let rank = x.rejections.is_empty().then(|| {
+                accepted += 1;
+                accepted
+            });
+            let standing = MethodStanding {
+                rank,
+                k: x.k.final_k,
+                e: x.e.final_e,
+                r: x.r,
+                rejections: x.rejections,
+            };
+            (x.method_id, standing)
+        })
+        .collect())
+}
+
+fn keyed_logs(snapshot: &RegistrySnapshot) -> BTreeMap<String, &OutcomeLog> {
+    snapshot
+        .logs
+        .iter()
+        .map(|log| (provenance_key(&log.method_id, &log.meta), log))
+        .collect()
+}
+
+fn changed_fields(
+    before: Option<&MethodStanding>,
+    after: Option<&MethodStanding>,
+    tolerance: f64,
+) -> Vec<ChangedField> {
+    let mut changed = Vec::new();
+    match (before, after) {
+        (Some(b), Some(a)) => {
+            if b.rank != a.rank {
+                changed.push(ChangedField::Rank);
+            }
+            if (b.k - a.k).abs() > tolerance {
+                changed.push(ChangedField::K);
+            }
+            if (b.e - a.e).abs() > tolerance {
+                changed.push(ChangedField::E);
+            }
+            if (b.r - a.r).abs() > tolerance {
+                changed.push(ChangedField::R);
+            }
+            if b.eligible() != a.eligible() {
+                changed.push(ChangedField::Eligibility);
+            }
+        }
+        // Added or dropped from the registry.
+        (Some(s), None) | (None, Some(s)) => {
+            if s.rank.is_some() {
+                changed.push(ChangedField::Rank);
+            }
+            changed.push(ChangedField::Eligibility);
+        }
+        (None, None) => {}
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+    use crate::model::fixtures::{log, meta, method};
+    use crate::model::ControlFamily;
+
+    fn registry() -> MethodRegistry {
+        let mut registry = MethodRegistry::new();
+        for (n, id) in ["trap.live", "exclusion.seal"].into_iter().enumerate() {
+            let family = if n == 0 {
+                ControlFamily::LiveCapture
+            } else {
+                ControlFamily::Exclusion
+            };
+            registry.add_method(method(id, family), meta(0)).unwrap();
+            registry
+                .transition(id, LifecycleState::Approved, "reviewed", meta(0))
+                .unwrap();
+            for i in 0..4 {
+                registry.add_log(log(id, 10 * n as u64 + i)).unwrap();
+            }
+        }
+        registry
+    }
+
+    fn taken_at() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_800_000_000)
+    }
+
+    fn diff(before: &RegistrySnapshot, after: &RegistrySnapshot) -> SnapshotDiff {
+        let ctx = log("trap.live", 0).context;
+        diff_snapshots(before, after, &ctx, &QueryOptions::new(1.0), 1e-9).unwrap()
+    }
+
+    fn scratch(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "deadbugs-snapshot-{name}-{}.json",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn saved_snapshots_load_intact() {
+        let snapshot = registry().snapshot("v1", taken_at()).unwrap();
+        assert!(snapshot.is_intact());
+        assert_eq!(snapshot.config().recency.as_of, Some(taken_at()));
+        let path = scratch("round-trip");
+        snapshot.save_json(&path).unwrap();
+        let loaded = RegistrySnapshot::load(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(loaded.snapshot_id(), snapshot.snapshot_id());
+        assert_eq!(loaded.logs().len(), 8);
+        let same = diff(&snapshot, &loaded);
+        assert!(same.methods.is_empty());
+        assert!(!same.config_changed);
+    }
+
+    #[test]
+    fn load_rejects_edited_content() {
+        let mut snapshot = registry().snapshot("v1", taken_at()).unwrap();
+        snapshot.logs.pop();
+        assert!(!snapshot.is_intact());
+        let path = scratch("tampered");
+        snapshot.save_json(&path).unwrap();
+        let loaded = RegistrySnapshot::load(&path);
+        let _ = fs::remove_file(&path);
+        match loaded {
+            Err(ShardError::Integrity { expected, found }) => {
+                assert_eq!(expected, snapshot.snapshot_id());
+                assert_ne!(found, expected);
+            }
+            other => panic!("expected an integrity error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn config_changes_name_what_moved() {
+        let mut registry = registry();
+        let before = registry.snapshot("v1", taken_at()).unwrap();
+
+        // Uniform recency: a later as_of cannot change any score.
+        let later = registry
+            .snapshot("v2", taken_at() + Duration::from_secs(86_400))
+            .unwrap();
+        assert_ne!(later.snapshot_id(), before.snapshot_id());
+        assert!(diff(&before, &later).config_changes.is_empty());
+
+        let mut config = registry.config().clone();
+        config.recency = RecencyConfig::decay();
+        registry.set_config(config);
+        let decayed = registry.snapshot("v3", taken_at()).unwrap();
+        let d = diff(&before, &decayed);
+        assert!(d.config_changed);
+        assert!(matches!(d.config_changes[..], [ConfigChange::Model { .. }]));
+
+        let aged = registry
+            .snapshot("v4", taken_at() + Duration::from_secs(86_400))
+            .unwrap();
+        assert_eq!(
+            diff(&decayed, &aged).config_changes,
+            [ConfigChange::RecencyAsOf {
+                before: Some(taken_at()),
+                after: Some(taken_at() + Duration::from_secs(86_400)),
+            }]
+        );
+
+        let mut policy = registry.policy().clone();
+        policy.rules.pop();
+        registry.set_policy(policy);
+        let filtered = registry.snapshot("v5", taken_at()).unwrap();
+        let id = filtered.policy().policy_id.clone();
+        assert_eq!(
+            diff(&decayed, &filtered).config_changes,
+            [ConfigChange::Policy {
+                before: id.clone(),
+                after: id,
+            }]
+        );
+    }
+}
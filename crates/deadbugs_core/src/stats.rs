@@ -0,0 +1,305 @@
+#![forbid(unsafe_code)]
+
+//! Small numeric kernels for the Bayesian K/E/R posteriors (no external deps):
+//! Beta for per-deployment proportions, Gamma for per-day event rates.
+
+use crate::model::CredibleInterval;
+
+/// ln Γ(x) for x > 0 (Lanczos, g = 7).
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEF: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection keeps the approximation accurate near zero.
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let mut acc = COEF[0];
+    let t = x + 7.5;
+    for (i, c) in COEF.iter().enumerate().skip(1) {
+        acc += c / (x + i as f64);
+    }
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + acc.ln()
+}
+
+/// Continued fraction for the regularized incomplete beta (modified Lentz).
+fn beta_cf(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let qab = a + b;
+    let qap = a + 1.0;
+    let qam = a - 1.0;
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+
+    for m in 1..300 {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    h
+}
+
+/// CDF of Beta(a, b) at x.
+pub fn beta_cdf(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    let front = ln_front.exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_cf(a, b, x) / a
+    } else {
+        1.0 - front * beta_cf(b, a, 1.0 - x) / b
+    }
+}
+
+/// Quantile of Beta(a, b) by bisection on the CDF.
+pub fn beta_quantile(p: f64, a: f64, b: f64) -> f64 {
+    let p = p.clamp(0.0, 1.0);
+    let (mut lo, mut hi) = (0.0_f64, 1.0_f64);
+    for _ in 0..100 {
+        let mid = 0.5 * (lo + hi);
+        if beta_cdf(mid, a, b) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Posterior mean of Beta(a, b).
+pub fn beta_mean(a: f64, b: f64) -> f64 {
+    a / (a + b)
+}
+
+/// Equal-tailed credible interval holding `mass` of Beta(a, b).
+pub fn beta_interval(a: f64, b: f64, mass: f64) -> CredibleInterval {
+    let tail = (1.0 - mass.clamp(0.0, 1.0)) / 2.0;
+    CredibleInterval {
+        lower: beta_quantile(tail, a, b),
+        upper: beta_quantile(1.0 - tail, a, b),
+    }
+}
+
+/// Regularized lower incomplete gamma P(a, x).
+pub fn gamma_p(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let ln_front = a * x.ln() - x - ln_gamma(a);
+
+    if x < a + 1.0 {
+        // Series expansion.
+        let mut ap = a;
+        let mut sum = 1.0 / a;
+        let mut del = sum;
+        for _ in 0..500 {
+            ap += 1.0;
+            del *= x / ap;
+            sum += del;
+            if del.abs() < sum.abs() * 1e-14 {
+                break;
+            }
+        }
+        sum * ln_front.exp()
+    } else {
+        // Continued fraction for Q(a, x) (modified Lentz).
+        const TINY: f64 = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < 1e-14 {
+                break;
+            }
+        }
+        1.0 - ln_front.exp() * h
+    }
+}
+
+/// Quantile of Gamma(shape, rate) by bisection on the CDF.
+pub fn gamma_quantile(p: f64, shape: f64, rate: f64) -> f64 {
+    let p = p.clamp(0.0, 1.0);
+    let mut hi = (shape + 1.0) / rate;
+    while gamma_p(shape, hi * rate) < p && hi < 1e12 {
+        hi *= 2.0;
+    }
+    let mut lo = 0.0_f64;
+    for _ in 0..100 {
+        let mid = 0.5 * (lo + hi);
+        if gamma_p(shape, mid * rate) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+/// Equal-tailed credible interval holding `mass` of Gamma(shape, rate).
+pub fn gamma_interval(shape: f64, rate: f64, mass: f64) -> CredibleInterval {
+    let tail = (1.0 - mass.clamp(0.0, 1.0)) / 2.0;
+    CredibleInterval {
+        lower: gamma_quantile(tail, shape, rate),
+        upper: gamma_quantile(1.0 - tail, shape, rate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tol: f64) -> bool {
+        (a - b).abs() < tol
+    }
+
+    #[test]
+    fn ln_gamma_matches_factorials_and_half_integers() {
+        for (n, fact) in [(1.0, 1.0), (2.0, 1.0), (5.0, 24.0), (11.0, 3_628_800.0_f64)] {
+            assert!(close(ln_gamma(n), fact.ln(), 1e-10), "ln Γ({n})");
+        }
+        let sqrt_pi = std::f64::consts::PI.sqrt();
+        assert!(close(ln_gamma(0.5), sqrt_pi.ln(), 1e-10));
+        assert!(close(ln_gamma(0.25), 3.625_609_908_221_908_f64.ln(), 1e-10));
+    }
+
+    #[test]
+    fn beta_cdf_matches_closed_forms() {
+        for x in [0.05, 0.3, 0.5, 0.72, 0.99] {
+            assert!(close(beta_cdf(x, 1.0, 1.0), x, 1e-10));
+            assert!(close(beta_cdf(x, 2.0, 1.0), x * x, 1e-10));
+            assert!(close(beta_cdf(x, 1.0, 2.0), 1.0 - (1.0 - x).powi(2), 1e-10));
+        }
+        assert!(close(beta_cdf(0.5, 7.5, 7.5), 0.5, 1e-10));
+        assert_eq!(beta_cdf(-0.1, 2.0, 3.0), 0.0);
+        assert_eq!(beta_cdf(1.0, 2.0, 3.0), 1.0);
+    }
+
+    #[test]
+    fn beta_quantile_inverts_the_cdf() {
+        for (a, b) in [(1.0, 1.0), (0.5, 0.5), (3.0, 40.0), (120.0, 4.0)] {
+            for p in [0.025, 0.5, 0.975] {
+                let q = beta_quantile(p, a, b);
+                assert!(close(beta_cdf(q, a, b), p, 1e-9), "Beta({a}, {b}) at {p}");
+            }
+        }
+    }
+
+    #[test]
+    fn beta_interval_is_equal_tailed_around_the_mean() {
+        let uniform = beta_interval(1.0, 1.0, 0.9);
+        assert!(close(uniform.lower, 0.05, 1e-9));
+        assert!(close(uniform.upper, 0.95, 1e-9));
+
+        let (a, b) = (9.0, 3.0);
+        assert_eq!(beta_mean(a, b), 0.75);
+        let narrow = beta_interval(a, b, 0.5);
+        let wide = beta_interval(a, b, 0.95);
+        assert!(wide.lower < narrow.lower && narrow.lower < 0.75);
+        assert!(0.75 < narrow.upper && narrow.upper < wide.upper);
+        let more = beta_interval(10.0 * a, 10.0 * b, 0.95);
+        assert!(more.upper - more.lower < wide.upper - wide.lower);
+    }
+
+    #[test]
+    fn gamma_p_matches_closed_forms_on_both_branches() {
+        // 0.5 and 1.5 take the series; 3.0 and 9.0 the continued fraction.
+        for x in [0.5, 1.5, 3.0, 9.0_f64] {
+            assert!(close(gamma_p(1.0, x), 1.0 - (-x).exp(), 1e-12), "P(1, {x})");
+            let erlang = 1.0 - (-x).exp() * (1.0 + x);
+            assert!(close(gamma_p(2.0, x), erlang, 1e-12), "P(2, {x})");
+        }
+        assert_eq!(gamma_p(3.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn gamma_quantile_inverts_the_cdf_and_scales_with_rate() {
+        let median = gamma_quantile(0.5, 1.0, 2.0);
+        assert!(close(median, std::f64::consts::LN_2 / 2.0, 1e-9));
+        for (shape, rate) in [(0.5, 1.0), (3.0, 200.0), (40.0, 0.01)] {
+            for p in [0.025, 0.5, 0.975] {
+                let q = gamma_quantile(p, shape, rate);
+                assert!(
+                    close(gamma_p(shape, q * rate), p, 1e-9),
+                    "Gamma({shape}, {rate}) at {p}"
+                );
+            }
+            let per_day = gamma_quantile(0.9, shape, rate);
+            let per_week = gamma_quantile(0.9, shape, rate / 7.0);
+            assert!(close(per_week, 7.0 * per_day, 1e-6 * per_week));
+        }
+    }
+
+    #[test]
+    fn gamma_interval_narrows_with_exposure() {
+        // Two events in 100 observation days versus twenty in 1000: same mean rate.
+        let short = gamma_interval(2.0, 100.0, 0.95);
+        let long = gamma_interval(20.0, 1000.0, 0.95);
+        for interval in [&short, &long] {
+            assert!(interval.lower < 0.02 && 0.02 < interval.upper);
+        }
+        assert!(long.upper - long.lower < short.upper - short.lower);
+    }
+}
@@ -0,0 +1,646 @@
+#![forbid(unsafe_code)]
+
+//! Durable backends for `MethodRegistry`.
+//!
+//! `JsonlStore` is an embedded append-only log: one directory holding
+//! `methods.jsonl`, `logs.jsonl`, `recommendations.jsonl`, `lifecycle.jsonl` and
+//! `quarantine.jsonl`, each starting with a schema/version header line followed by
+//! one JSON record per line. Every append is fsynced before it returns. The
+//! quarantine file records held logs and releases; a release empties the
+//! quarantine held up to that point.
+//! Log lookups go through an in-memory offset index keyed by (method_id, pest), so
+//! only the matching lines are read back from disk. Each log's screening stamp is
+//! kept alongside, so a registry can seed its screen without reading the corpus.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::feedback::RecommendationRecord;
+use crate::lifecycle::LifecycleTransition;
+use crate::model::{ControlMethod, OutcomeLog, PestSpecies};
+use crate::screen::{LogStamp, QuarantinedLog};
+use crate::shard::{ShardError, ShardSchema, SHARD_SCHEMA_VERSION};
+
+/// Errors raised by registry storage backends.
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    /// A stored line could not be decoded.
+    Corrupt {
+        file: PathBuf,
+        line: usize,
+        reason: String,
+    },
+    /// File header names a different schema or an unsupported version.
+    Header {
+        file: PathBuf,
+        found: String,
+    },
+    /// A recommendation with this ID is already recorded.
+    DuplicateRecommendation(String),
+    /// Registry content could not be encoded (e.g. for a snapshot).
+    Encode(ShardError),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "store i/o error: {e}"),
+            StoreError::Corrupt { file, line, reason } => {
+                write!(f, "corrupt record at {}:{line}: {reason}", file.display())
+            }
+            StoreError::Header { file, found } => {
+                write!(f, "unexpected header in {}: {found}", file.display())
+            }
+            StoreError::DuplicateRecommendation(id) => {
+                write!(f, "recommendation {id} already recorded")
+            }
+            StoreError::Encode(e) => write!(f, "cannot encode registry content: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+    fn from(e: io::Error) -> Self {
+        StoreError::Io(e)
+    }
+}
+
+impl From<ShardError> for StoreError {
+    fn from(e: ShardError) -> Self {
+        StoreError::Encode(e)
+    }
+}
+
+/// Storage backend behind a `MethodRegistry`.
+pub trait RegistryStore {
+    /// All registered methods, in insertion order.
+    fn load_methods(&self) -> Result<Vec<ControlMethod>, StoreError>;
+
+    /// Entire log corpus, in insertion order (used for resident registries).
+    fn load_logs(&self) -> Result<Vec<OutcomeLog>, StoreError>;
+
+    /// Screening stamps of the whole corpus, in insertion order, without
+    /// materializing the logs (used to seed on-demand registries).
+    fn log_stamps(&self) -> Result<Vec<LogStamp>, StoreError>;
+
+    /// Logs for one method and pest, without materializing the rest of the corpus.
+    fn logs_for_method(
+        &self,
+        method_id: &str,
+        pest: PestSpecies,
+    ) -> Result<Vec<OutcomeLog>, StoreError>;
+
+    /// Durably append a method; returns only once the write is synced.
+    fn append_method(&mut self, method: &ControlMethod) -> Result<(), StoreError>;
+
+    /// Durably append a log; returns only once the write is synced.
+    fn append_log(&mut self, log: &OutcomeLog) -> Result<(), StoreError>;
+
+    /// All recommendation records, in insertion order.
+    fn load_recommendations(&self) -> Result<Vec<RecommendationRecord>, StoreError>;
+
+    /// Durably append a recommendation record; returns only once the write is synced.
+    fn append_recommendation(&mut self, record: &RecommendationRecord) -> Result<(), StoreError>;
+
+    /// All lifecycle transitions, in insertion order.
+    fn load_transitions(&self) -> Result<Vec<LifecycleTransition>, StoreError>;
+
+    /// Durably append a lifecycle transition; returns only once the write is synced.
+    fn append_transition(&mut self, transition: &LifecycleTransition) -> Result<(), StoreError>;
+
+    /// Logs currently held in quarantine, oldest first.
+    fn load_quarantined(&self) -> Result<Vec<QuarantinedLog>, StoreError>;
+
+    /// Durably add a log to the quarantine; returns only once the write is synced.
+    fn append_quarantined(&mut self, held: &QuarantinedLog) -> Result<(), StoreError>;
+
+    /// Durably empty the quarantine once its logs have been handed over for review.
+    fn release_quarantined(&mut self) -> Result<(), StoreError>;
+}
+
+/// One line of the quarantine file.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "entry", rename_all = "snake_case")]
+enum QuarantineEntry {
+    Held(Box<QuarantinedLog>),
+    /// Every log held before this line was released.
+    Released,
+}
+
+impl ShardSchema for QuarantineEntry {
+    const SCHEMA: &'static str = "DeadbugsQuarantine";
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileHeader {
+    schema: String,
+    version: u32,
+}
+
+/// One append-only JSON-lines file with a schema header.
+struct JsonlFile {
+    path: PathBuf,
+    file: File,
+    /// Length of the whole-line prefix: header plus every successful append.
+    len: u64,
+}
+
+impl JsonlFile {
+    /// Open or create the file, writing the header on first use and truncating a
+    /// torn trailing line left by an interrupted append (or header write). Each
+    /// stored record is streamed to `visit` with its byte offset; nothing is
+    /// retained here.
+    fn open<T: ShardSchema>(
+        path: PathBuf,
+        mut visit: impl FnMut(u64, T),
+    ) -> Result<Self, StoreError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+
+        if file.metadata()?.len() == 0 {
+            write_header::<T>(&mut file, &path)?;
+        }
+
+        let mut reader = BufReader::new(File::open(&path)?);
+        let mut buf = String::new();
+        let mut offset = 0_u64;
+        let mut line_no = 0_usize;
+
+        loop {
+            buf.clear();
+            let read = reader.read_line(&mut buf)?;
+            if read == 0 {
+                break;
+            }
+            line_no += 1;
+
+            if !buf.ends_with('\n') {
+                // Torn append: drop the partial record and keep the durable prefix.
+                file.set_len(offset)?;
+                file.sync_data()?;
+                if offset == 0 {
+                    // The header itself was torn; the file holds nothing else.
+                    offset = write_header::<T>(&mut file, &path)?;
+                }
+                break;
+            }
+
+            if line_no == 1 {
+                let header: Option<FileHeader> = serde_json::from_str(buf.trim_end()).ok();
+                match header {
+                    Some(h) if h.schema == T::SCHEMA && h.version == SHARD_SCHEMA_VERSION => {}
+                    _ => {
+                        return Err(StoreError::Header {
+                            file: path,
+                            found: buf.trim_end().to_string(),
+                        })
+                    }
+                }
+            } else {
+                let record: T =
+                    serde_json::from_str(buf.trim_end()).map_err(|e| StoreError::Corrupt {
+                        file: path.clone(),
+                        line: line_no,
+                        reason: e.to_string(),
+                    })?;
+                visit(offset, record);
+            }
+
+            offset += read as u64;
+        }
+
+        Ok(JsonlFile {
+            path,
+            file,
+            len: offset,
+        })
+    }
+
+    /// Append one record and fsync; returns the byte offset of the new line.
+    /// A failed append is cut back off the file, so the next one starts on a
+    /// line boundary.
+    fn append<T: Serialize>(&mut self, record: &T) -> Result<u64, StoreError> {
+        let mut line = serde_json::to_string(record).map_err(|e| StoreError::Corrupt {
+            file: self.path.clone(),
+            line: 0,
+            reason: e.to_string(),
+        })?;
+        line.push('\n');
+
+        // An earlier failed append whose rollback also failed left a partial line.
+        if self.file.metadata()?.len() != self.len {
+            self.file.set_len(self.len)?;
+        }
+        let offset = self.len;
+        if let Err(e) = self
+            .file
+            .write_all(line.as_bytes())
+            .and_then(|()| self.file.sync_data())
+        {
+            // Best effort; retried by the check above on the next append.
+            let _ = self.file.set_len(offset);
+            return Err(e.into());
+        }
+        self.len += line.len() as u64;
+        Ok(offset)
+    }
+
+    /// Read the records starting at the given byte offsets.
+    fn read_at<T: ShardSchema>(&self, offsets: &[u64]) -> Result<Vec<T>, StoreError> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut buf = String::new();
+        let mut out = Vec::with_capacity(offsets.len());
+        for &off in offsets {
+            reader.seek(SeekFrom::Start(off))?;
+            buf.clear();
+            reader.read_line(&mut buf)?;
+            let record = serde_json::from_str(buf.trim_end()).map_err(|e| StoreError::Corrupt {
+                file: self.path.clone(),
+                line: 0,
+                reason: format!("at byte {off}: {e}"),
+            })?;
+            out.push(record);
+        }
+        Ok(out)
+    }
+}
+
+/// Write the schema header line and fsync; returns its length in bytes.
+fn write_header<T: ShardSchema>(file: &mut File, path: &Path) -> Result<u64, StoreError> {
+    let header = FileHeader {
+        schema: T::SCHEMA.to_string(),
+        version: SHARD_SCHEMA_VERSION,
+    };
+    let mut line = serde_json::to_string(&header).map_err(|e| StoreError::Corrupt {
+        file: path.to_path_buf(),
+        line: 1,
+        reason: e.to_string(),
+    })?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+    file.sync_data()?;
+    Ok(line.len() as u64)
+}
+
+/// Embedded append-only store; see the module docs for the on-disk layout.
+pub struct JsonlStore {
+    methods: JsonlFile,
+    logs: JsonlFile,
+    /// Byte offsets of every method line, in insertion order.
+    method_offsets: Vec<u64>,
+    /// Byte offsets of every log line, in insertion order.
+    log_offsets: Vec<u64>,
+    /// (method_id, pest) → byte offsets of matching log lines.
+    log_index: HashMap<(String, PestSpecies), Vec<u64>>,
+    /// Screening stamp of every log line, in insertion order.
+    log_stamps: Vec<LogStamp>,
+    recommendations: JsonlFile,
+    /// Byte offsets of every recommendation line, in insertion order.
+    recommendation_offsets: Vec<u64>,
+    transitions: JsonlFile,
+    /// Byte offsets of every lifecycle transition line, in insertion order.
+    transition_offsets: Vec<u64>,
+    quarantine: JsonlFile,
+    /// Byte offsets of the held lines since the last release.
+    quarantine_offsets: Vec<u64>,
+}
+
+impl JsonlStore {
+    /// Open (or create) a store rooted at `dir`, rebuilding the log index.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let mut method_offsets = Vec::new();
+        let methods = JsonlFile::open::<ControlMethod>(dir.join("methods.jsonl"), |offset, _| {
+            method_offsets.push(offset)
+        })?;
+
+        let mut log_offsets = Vec::new();
+        let mut log_index: HashMap<(String, PestSpecies), Vec<u64>> = HashMap::new();
+        let mut log_stamps = Vec::new();
+        let logs = JsonlFile::open::<OutcomeLog>(dir.join("logs.jsonl"), |offset, log| {
+            log_offsets.push(offset);
+            log_stamps.push(LogStamp::of(&log));
+            log_index
+                .entry((log.method_id, log.context.pest))
+                .or_default()
+                .push(offset);
+        })?;
+
+        let mut recommendation_offsets = Vec::new();
+        let recommendations = JsonlFile::open::<RecommendationRecord>(
+            dir.join("recommendations.jsonl"),
+            |offset, _| recommendation_offsets.push(offset),
+        )?;
+
+        let mut transition_offsets = Vec::new();
+        let transitions =
+            JsonlFile::open::<LifecycleTransition>(dir.join("lifecycle.jsonl"), |offset, _| {
+                transition_offsets.push(offset)
+            })?;
+
+        let mut quarantine_offsets = Vec::new();
+        let quarantine =
+            JsonlFile::open::<QuarantineEntry>(dir.join("quarantine.jsonl"), |offset, entry| {
+                match entry {
+                    QuarantineEntry::Held(_) => quarantine_offsets.push(offset),
+                    QuarantineEntry::Released => quarantine_offsets.clear(),
+                }
+            })?;
+
+        Ok(JsonlStore {
+            methods,
+            logs,
+            method_offsets,
+            log_offsets,
+            log_index,
+            log_stamps,
+            recommendations,
+            recommendation_offsets,
+            transitions,
+            transition_offsets,
+            quarantine,
+            quarantine_offsets,
+        })
+    }
+}
+
+impl RegistryStore for JsonlStore {
+    fn load_methods(&self) -> Result<Vec<ControlMethod>, StoreError> {
+        self.methods.read_at(&self.method_offsets)
+    }
+
+    fn load_logs(&self) -> Result<Vec<OutcomeLog>, StoreError> {
+        self.logs.read_at(&self.log_offsets)
+    }
+
+    fn log_stamps(&self) -> Result<Vec<LogStamp>, StoreError> {
+        Ok(self.log_stamps.clone())
+    }
+
+    fn logs_for_method(
+        &self,
+        method_id: &str,
+        pest: PestSpecies,
+    ) -> Result<Vec<OutcomeLog>, StoreError> {
+        match self.log_index.get(&(method_id.to_string(), pest)) {
+            Some(offsets) => self.logs.read_at(offsets),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn append_method(&mut self, method: &ControlMethod) -> Result<(), StoreError> {
+        let offset = self.methods.append(method)?;
+        self.method_offsets.push(offset);
+        Ok(())
+    }
+
+    fn append_log(&mut self, log: &OutcomeLog) -> Result<(), StoreError> {
+        let offset = self.logs.append(log)?;
+        self.log_offsets.push(offset);
+        self.log_index
+            .entry((log.method_id.clone(), log.context.pest))
+            .or_default()
+            .push(offset);
+        self.log_stamps.push(LogStamp::of(log));
+        Ok(())
+    }
+
+    fn load_recommendations(&self) -> Result<Vec<RecommendationRecord>, StoreError> {
+        self.recommendations.read_at(&self.recommendation_offsets)
+    }
+
+    fn append_recommendation(&mut self, record: &RecommendationRecord) -> Result<(), StoreError> {
+        let offset = self.recommendations.append(record)?;
+        self.recommendation_offsets.push(offset);
+        Ok(())
+    }
+
+    fn load_transitions(&self) -> Result<Vec<LifecycleTransition>, StoreError> {
+        self.transitions.read_at(&self.transition_offsets)
+    }
+
+    fn append_transition(&mut self, transition: &LifecycleTransition) -> Result<(), StoreError> {
+        let offset = self.transitions.append(transition)?;
+        self.transition_offsets.push(offset);
+        Ok(())
+    }
+
+    fn load_quarantined(&self) -> Result<Vec<QuarantinedLog>, StoreError> {
+        let entries = self
+            .quarantine
+            .read_at::<QuarantineEntry>(&self.quarantine_offsets)?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|entry| match entry {
+                QuarantineEntry::Held(held) => Some(*held),
+                QuarantineEntry::Released => None,
+            })
+            .collect())
+    }
+
+    fn append_quarantined(&mut self, held: &QuarantinedLog) -> Result<(), StoreError> {
+        let offset = self
+            .quarantine
+            .append(&QuarantineEntry::Held(Box::new(held.clone())))?;
+        self.quarantine_offsets.push(offset);
+        Ok(())
+    }
+
+    fn release_quarantined(&mut self) -> Result<(), StoreError> {
+        if !self.quarantine_offsets.is_empty() {
+            self.quarantine.append(&QuarantineEntry::Released)?;
+            self.quarantine_offsets.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures::{log, method};
+    use crate::model::ControlFamily;
+    use crate::screen::AnomalyReason;
+
+    /// A fresh directory per test under the system temp dir.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("deadbugs-store-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn raw_append(path: &Path, bytes: &str) {
+        let mut file = OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(bytes.as_bytes()).unwrap();
+    }
+
+    fn stamps(logs: &[OutcomeLog]) -> Vec<&str> {
+        logs.iter().map(|l| l.meta.hex_stamp.as_str()).collect()
+    }
+
+    #[test]
+    fn reopens_appended_records() {
+        let dir = scratch_dir("reopen");
+        let mut store = JsonlStore::open(&dir).unwrap();
+        store
+            .append_method(&method("trap.snap", ControlFamily::MechanicalKill))
+            .unwrap();
+        store.append_log(&log("trap.snap", 1)).unwrap();
+        store.append_log(&log("exclusion.seal", 2)).unwrap();
+        store.append_log(&log("trap.snap", 3)).unwrap();
+        drop(store);
+
+        let store = JsonlStore::open(&dir).unwrap();
+        assert_eq!(store.load_methods().unwrap()[0].id, "trap.snap");
+        assert_eq!(
+            stamps(&store.load_logs().unwrap()),
+            ["0x0001", "0x0002", "0x0003"]
+        );
+        let rodent = store
+            .logs_for_method("trap.snap", PestSpecies::Rodent)
+            .unwrap();
+        assert_eq!(stamps(&rodent), ["0x0001", "0x0003"]);
+        assert!(store
+            .logs_for_method("trap.snap", PestSpecies::Ant)
+            .unwrap()
+            .is_empty());
+        // The stamp index is rebuilt without keeping the logs.
+        let expected: Vec<LogStamp> = store
+            .load_logs()
+            .unwrap()
+            .iter()
+            .map(LogStamp::of)
+            .collect();
+        assert_eq!(store.log_stamps().unwrap(), expected);
+    }
+
+    #[test]
+    fn quarantine_survives_reopening_until_released() {
+        let dir = scratch_dir("quarantine");
+        let held = |n| QuarantinedLog {
+            log: log("trap.snap", n),
+            reasons: vec![AnomalyReason::DuplicateHexStamp {
+                hex_stamp: format!("0x{n:04x}"),
+            }],
+        };
+        let mut store = JsonlStore::open(&dir).unwrap();
+        store.append_quarantined(&held(1)).unwrap();
+        store.append_quarantined(&held(2)).unwrap();
+        drop(store);
+
+        let mut store = JsonlStore::open(&dir).unwrap();
+        let loaded = store.load_quarantined().unwrap();
+        let held_stamps: Vec<&str> = loaded
+            .iter()
+            .map(|h| h.log.meta.hex_stamp.as_str())
+            .collect();
+        assert_eq!(held_stamps, ["0x0001", "0x0002"]);
+        assert_eq!(loaded[0].reasons, held(1).reasons);
+        store.release_quarantined().unwrap();
+        store.append_quarantined(&held(3)).unwrap();
+        drop(store);
+
+        let store = JsonlStore::open(&dir).unwrap();
+        let loaded = store.load_quarantined().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].log.meta.hex_stamp, "0x0003");
+        // Held logs never reach the log corpus.
+        assert!(store.load_logs().unwrap().is_empty());
+    }
+
+    #[test]
+    fn drops_a_torn_trailing_record() {
+        let dir = scratch_dir("torn-record");
+        let mut store = JsonlStore::open(&dir).unwrap();
+        store.append_log(&log("m1", 1)).unwrap();
+        store.append_log(&log("m1", 2)).unwrap();
+        drop(store);
+        raw_append(&dir.join("logs.jsonl"), "{\"method_id\":\"m1\",\"cont");
+
+        let mut store = JsonlStore::open(&dir).unwrap();
+        assert_eq!(stamps(&store.load_logs().unwrap()), ["0x0001", "0x0002"]);
+        store.append_log(&log("m1", 3)).unwrap();
+        drop(store);
+
+        let store = JsonlStore::open(&dir).unwrap();
+        assert_eq!(
+            stamps(&store.load_logs().unwrap()),
+            ["0x0001", "0x0002", "0x0003"]
+        );
+    }
+
+    #[test]
+    fn rewrites_a_torn_header() {
+        let dir = scratch_dir("torn-header");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("logs.jsonl"), "{\"schema\":\"Deadbu").unwrap();
+
+        let mut store = JsonlStore::open(&dir).unwrap();
+        assert!(store.load_logs().unwrap().is_empty());
+        store.append_log(&log("m1", 1)).unwrap();
+        drop(store);
+
+        let store = JsonlStore::open(&dir).unwrap();
+        assert_eq!(stamps(&store.load_logs().unwrap()), ["0x0001"]);
+    }
+
+    #[test]
+    fn cuts_back_a_partial_append_before_the_next() {
+        let dir = scratch_dir("partial-append");
+        let mut store = JsonlStore::open(&dir).unwrap();
+        store.append_log(&log("m1", 1)).unwrap();
+        // Bytes a failed write left behind while the store stayed open.
+        raw_append(&dir.join("logs.jsonl"), "{\"method_id\":\"m1\"");
+        store.append_log(&log("m1", 2)).unwrap();
+        assert_eq!(stamps(&store.load_logs().unwrap()), ["0x0001", "0x0002"]);
+        drop(store);
+
+        let store = JsonlStore::open(&dir).unwrap();
+        assert_eq!(stamps(&store.load_logs().unwrap()), ["0x0001", "0x0002"]);
+    }
+
+    #[test]
+    fn rejects_a_foreign_header() {
+        let dir = scratch_dir("foreign-header");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("logs.jsonl"),
+            "{\"schema\":\"DeadbugsControlMethod\",\"version\":1}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            JsonlStore::open(&dir),
+            Err(StoreError::Header { .. })
+        ));
+    }
+
+    #[test]
+    fn reports_a_corrupt_middle_line() {
+        let dir = scratch_dir("corrupt");
+        let mut store = JsonlStore::open(&dir).unwrap();
+        store.append_log(&log("m1", 1)).unwrap();
+        drop(store);
+        raw_append(&dir.join("logs.jsonl"), "not json\n");
+        // A complete but undecodable line is not a torn append and is never dropped.
+        for _ in 0..2 {
+            assert!(matches!(
+                JsonlStore::open(&dir),
+                Err(StoreError::Corrupt { line: 3, .. })
+            ));
+        }
+    }
+}
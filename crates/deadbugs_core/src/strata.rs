@@ -0,0 +1,224 @@
+#![forbid(unsafe_code)]
+
+//! Context-conditional scoring.
+//!
+//! Logs are stratified by `LocationType` and `ProximityTags`. A score for a given
+//! deployment context uses the narrowest stratum holding at least `min_logs` logs:
+//! same location and proximity, then same location, then every log for the pest.
+
+use serde::{Deserialize, Serialize};
+
+use crate::explain::KerExplanation;
+use crate::ker::{explain_method_refs, score_method_refs, KerConfig};
+use crate::model::{ControlMethod, KerScore, LocationType, OutcomeLog, ProximityTags, Stratum};
+
+/// Fallback pooling settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrataConfig {
+    /// Minimum logs a stratum needs before it is used on its own.
+    pub min_logs: usize,
+}
+
+impl Default for StrataConfig {
+    fn default() -> Self {
+        Self { min_logs: 5 }
+    }
+}
+
+/// Deployment context a score is conditioned on.
+#[derive(Clone, Debug)]
+pub struct ScoringContext {
+    pub location: LocationType,
+    /// `None` skips the proximity stratum.
+    pub proximity: Option<ProximityTags>,
+}
+
+impl ScoringContext {
+    pub fn new(location: LocationType) -> Self {
+        Self {
+            location,
+            proximity: None,
+        }
+    }
+
+    pub fn with_proximity(mut self, proximity: ProximityTags) -> Self {
+        self.proximity = Some(proximity);
+        self
+    }
+
+    /// Whether `log` falls in `stratum` around this context.
+    pub fn matches(&self, log: &OutcomeLog, stratum: Stratum) -> bool {
+        match stratum {
+            Stratum::LocationProximity => {
+                log.context.location_type == self.location
+                    && self.proximity.as_ref() == Some(&log.context.proximity)
+            }
+            Stratum::Location => log.context.location_type == self.location,
+            Stratum::Pooled => true,
+        }
+    }
+}
+
+/// Narrowest stratum with enough logs, and the logs in it.
+pub fn select_stratum<'a>(
+    logs: &[&'a OutcomeLog],
+    ctx: &ScoringContext,
+    cfg: &StrataConfig,
+) -> (Stratum, Vec<&'a OutcomeLog>) {
+    let levels: &[Stratum] = if ctx.proximity.is_some() {
+        &[Stratum::LocationProximity, Stratum::Location]
+    } else {
+        &[Stratum::Location]
+    };
+
+    for &stratum in levels {
+        let selected: Vec<&OutcomeLog> = logs
+            .iter()
+            .copied()
+            .filter(|l| ctx.matches(l, stratum))
+            .collect();
+        if selected.len() >= cfg.min_logs {
+            return (stratum, selected);
+        }
+    }
+    (Stratum::Pooled, logs.to_vec())
+}
+
+/// Score a method against the logs most similar to `ctx`.
+pub fn score_method_in_context(
+    method: &ControlMethod,
+    logs: &[&OutcomeLog],
+    ctx: &ScoringContext,
+    cfg: &KerConfig,
+) -> KerScore {
+    let (stratum, selected) = select_stratum(logs, ctx, &cfg.strata);
+    let mut ker = score_method_refs(method, &selected, cfg);
+    ker.stratum = stratum;
+    ker
+}
+
+/// [`score_method_in_context`] plus the explanation trace.
+pub fn explain_method_in_context(
+    method: &ControlMethod,
+    logs: &[&OutcomeLog],
+    ctx: &ScoringContext,
+    cfg: &KerConfig,
+) -> (KerScore, KerExplanation) {
+    let (stratum, selected) = select_stratum(logs, ctx, &cfg.strata);
+    let (mut ker, mut explanation) = explain_method_refs(method, &selected, cfg);
+    ker.stratum = stratum;
+    explanation.stratum = stratum;
+    (ker, explanation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::fixtures::{context, log, method};
+    use crate::model::ControlFamily;
+    use crate::recency::RecencyConfig;
+
+    fn pets() -> ProximityTags {
+        ProximityTags {
+            pets_present: true,
+            ..ProximityTags::default()
+        }
+    }
+
+    /// `n` logs from `location`, the first `with_pets` of them with pets present.
+    fn logs(location: LocationType, n: u64, with_pets: u64, first: u64) -> Vec<OutcomeLog> {
+        (first..first + n)
+            .map(|i| {
+                let mut l = log("trap.live", i);
+                l.context = context(location);
+                l.context.proximity.pets_present = i - first < with_pets;
+                l
+            })
+            .collect()
+    }
+
+    fn refs(logs: &[OutcomeLog]) -> Vec<&OutcomeLog> {
+        logs.iter().collect()
+    }
+
+    #[test]
+    fn picks_the_narrowest_stratum_with_enough_logs() {
+        let mut corpus = logs(LocationType::Home, 6, 5, 0);
+        corpus.extend(logs(LocationType::Warehouse, 6, 0, 10));
+        let corpus = refs(&corpus);
+        let cfg = StrataConfig::default();
+
+        let ctx = ScoringContext::new(LocationType::Home).with_proximity(pets());
+        let (stratum, selected) = select_stratum(&corpus, &ctx, &cfg);
+        assert_eq!(stratum, Stratum::LocationProximity);
+        assert_eq!(selected.len(), 5);
+
+        let ctx = ScoringContext::new(LocationType::Home);
+        let (stratum, selected) = select_stratum(&corpus, &ctx, &cfg);
+        assert_eq!(stratum, Stratum::Location);
+        assert_eq!(selected.len(), 6);
+    }
+
+    #[test]
+    fn falls_back_when_strata_are_sparse() {
+        let mut corpus = logs(LocationType::Home, 4, 2, 0);
+        corpus.extend(logs(LocationType::Warehouse, 6, 0, 10));
+        let corpus = refs(&corpus);
+        let cfg = StrataConfig::default();
+
+        let ctx = ScoringContext::new(LocationType::Home).with_proximity(pets());
+        let (stratum, selected) = select_stratum(&corpus, &ctx, &cfg);
+        assert_eq!(stratum, Stratum::Pooled);
+        assert_eq!(selected.len(), 10);
+
+        let ctx = ScoringContext::new(LocationType::Warehouse).with_proximity(pets());
+        let (stratum, _) = select_stratum(&corpus, &ctx, &cfg);
+        assert_eq!(stratum, Stratum::Location);
+    }
+
+    #[test]
+    fn proximity_must_match_exactly() {
+        let corpus = logs(LocationType::Home, 6, 6, 0);
+        let corpus = refs(&corpus);
+        let both = ProximityTags {
+            children_present: true,
+            ..pets()
+        };
+        let ctx = ScoringContext::new(LocationType::Home).with_proximity(both);
+        let (stratum, _) = select_stratum(&corpus, &ctx, &StrataConfig::default());
+        assert_eq!(stratum, Stratum::Location);
+    }
+
+    #[test]
+    fn scores_see_the_risk_profile_of_similar_sites() {
+        // Pets are harmed at homes with pets, never in warehouses.
+        let mut corpus = logs(LocationType::Home, 5, 5, 0);
+        for l in &mut corpus {
+            l.side_effects.pet_incident = true;
+        }
+        corpus.extend(logs(LocationType::Warehouse, 20, 0, 10));
+        let corpus = refs(&corpus);
+        let m = method("trap.live", ControlFamily::LiveCapture);
+        let cfg = KerConfig {
+            recency: RecencyConfig::uniform(),
+            ..KerConfig::default()
+        };
+
+        let home = ScoringContext::new(LocationType::Home).with_proximity(pets());
+        let home = score_method_in_context(&m, &corpus, &home, &cfg);
+        let pooled = score_method_refs(&m, &corpus, &cfg);
+        assert_eq!(home.stratum, Stratum::LocationProximity);
+        assert_eq!(home.incidence.r_pets, 1.0);
+        assert!(home.hard_violation);
+        assert_eq!(pooled.stratum, Stratum::Pooled);
+        assert!(home.coords.r_pets > pooled.coords.r_pets);
+
+        let ctx = ScoringContext::new(LocationType::Warehouse);
+        let (warehouse, explanation) = explain_method_in_context(&m, &corpus, &ctx, &cfg);
+        assert_eq!(warehouse.stratum, Stratum::Location);
+        assert_eq!(explanation.stratum, Stratum::Location);
+        assert_eq!(warehouse.incidence.r_pets, 0.0);
+        assert!(!warehouse.hard_violation);
+    }
+}
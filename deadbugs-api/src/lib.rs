@@ -0,0 +1,461 @@
+#![forbid(unsafe_code)]
+
+use std::time::SystemTime;
+
+use deadbugs_guard::{evaluate_plan_guard, GuardVerdict, PlanGuardConfig};
+use deadbugs_ker_engine::{
+    score_method_with, ControlMethod, OutcomeLog, RecencyConfig, RiskScoreKER,
+};
+use deadbugs_pest_kernel::{
+    simulate_pest_risk, ControlAction, InterventionPlan, PestContext, PestSpeciesPlugin,
+    SimulationConfig,
+};
+
+/// Site request as received from HTTP/ALN callers.
+#[derive(Clone, Debug)]
+pub struct UserContext {
+    pub pest_class: String,      // matches OutcomeLog::pest_class, e.g. "rodent".
+    pub structure_type: String,  // "home", "restaurant", "farm", etc.
+    pub climate_band: String,    // "arid-hot", "temperate", etc.
+    pub human_proximity: f64,    // 0–1.
+    pub animal_proximity: f64,   // 0–1.
+    pub food_availability: f64,  // 0–1.
+    pub water_availability: f64, // 0–1.
+    pub harborage_quality: f64,  // 0–1.
+    pub max_risk_harm: f64,      // user tolerance: plan-level R ceiling.
+    pub horizon_days: u32,
+    /// Evaluation time; log ages for recency weighting are measured from here.
+    pub as_of: SystemTime,
+}
+
+impl UserContext {
+    /// Map the user request onto the kernel's species-agnostic context.
+    pub fn to_pest_context(&self) -> PestContext {
+        PestContext {
+            structure_type: self.structure_type.clone(),
+            climate_band: self.climate_band.clone(),
+            human_proximity: self.human_proximity.clamp(0.0, 1.0),
+            animal_proximity: self.animal_proximity.clamp(0.0, 1.0),
+            food_availability: self.food_availability.clamp(0.0, 1.0),
+            water_availability: self.water_availability.clamp(0.0, 1.0),
+            harborage_quality: self.harborage_quality.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// One curated library entry: the evidence-side method and its simulator action.
+#[derive(Clone, Debug)]
+pub struct LibraryEntry {
+    pub method: ControlMethod,
+    pub action: ControlAction,
+}
+
+/// Orchestration configuration (pulled from DID-signed shards in production).
+#[derive(Clone, Debug)]
+pub struct RankingConfig {
+    pub sim: SimulationConfig,
+    pub guard: PlanGuardConfig,
+    pub max_actions_per_plan: usize, // upper bound on plan size during enumeration.
+    /// Recency weighting of outcome logs; its `as_of` is replaced by `UserContext::as_of`.
+    pub recency: RecencyConfig,
+}
+
+/// Plan-level K/E/R triad aggregated from the per-method scores.
+#[derive(Clone, Debug)]
+pub struct PlanKer {
+    pub k_knowledge: f64,  // weakest evidence among the plan's methods.
+    pub e_eco_impact: f64, // mean eco-impact across methods.
+    pub r_risk_harm: f64,  // worst risk-of-harm among methods.
+}
+
+/// A corridor-safe plan returned to API/UI callers.
+#[derive(Clone, Debug)]
+pub struct PlanRecommendation {
+    pub plan: InterventionPlan,
+    pub method_ids: Vec<String>,
+    pub verdict: GuardVerdict,
+    pub method_scores: Vec<RiskScoreKER>,
+    pub plan_ker: PlanKer,
+    /// Residual V_T at the end of the horizon.
+    pub final_v: f64,
+    /// Context hashes of the outcome-log shards behind the K/E/R scores.
+    pub provenance: Vec<String>,
+}
+
+/// Enumerate library index sets of size 1..=max_actions, skipping chemical entries.
+pub fn generate_candidate_plans(library: &[LibraryEntry], max_actions: usize) -> Vec<Vec<usize>> {
+    // Curated library must be non-chemical; drop anything that slipped through.
+    let admissible: Vec<usize> = library
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| !entry.method.chemical)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut out = Vec::new();
+    let mut current = Vec::new();
+    collect_combinations(&admissible, 0, max_actions.max(1), &mut current, &mut out);
+    out
+}
+
+fn collect_combinations(
+    pool: &[usize],
+    start: usize,
+    max_len: usize,
+    current: &mut Vec<usize>,
+    out: &mut Vec<Vec<usize>>,
+) {
+    for i in start..pool.len() {
+        current.push(pool[i]);
+        out.push(current.clone());
+        if current.len() < max_len {
+            collect_combinations(pool, i + 1, max_len, current, out);
+        }
+        current.pop();
+    }
+}
+
+/// Aggregate per-method K/E/R into a conservative plan triad.
+fn aggregate_plan_ker(scores: &[RiskScoreKER]) -> PlanKer {
+    if scores.is_empty() {
+        return PlanKer {
+            k_knowledge: 0.0,
+            e_eco_impact: 0.0,
+            r_risk_harm: 1.0,
+        };
+    }
+
+    let n = scores.len() as f64;
+    let k = scores
+        .iter()
+        .map(|s| s.k_knowledge)
+        .fold(f64::INFINITY, f64::min);
+    let e = scores.iter().map(|s| s.e_eco_impact).sum::<f64>() / n;
+    let r = scores
+        .iter()
+        .map(|s| s.r_risk_harm)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    PlanKer {
+        k_knowledge: k,
+        e_eco_impact: e,
+        r_risk_harm: r,
+    }
+}
+
+/// Full API flow: context → species plugin → plans → simulate → guard → K/E/R → rank.
+/// Returns only corridor-safe plans within the user's R tolerance,
+/// sorted by lowest final V_T, then highest plan-level E.
+pub fn rank_plans(
+    user: &UserContext,
+    plugin: &dyn PestSpeciesPlugin,
+    library: &[LibraryEntry],
+    logs: &[OutcomeLog],
+    cfg: &RankingConfig,
+) -> Vec<PlanRecommendation> {
+    let ctx = user.to_pest_context();
+    let species = plugin.species_model(&ctx);
+
+    // Evidence for this pest class only.
+    let pest_logs: Vec<OutcomeLog> = logs
+        .iter()
+        .filter(|l| l.pest_class == user.pest_class)
+        .cloned()
+        .collect();
+
+    // Score each library method once, aged against the request's evaluation time
+    // so the same request ranks the same whenever it is replayed.
+    let recency = RecencyConfig {
+        as_of: Some(user.as_of),
+        ..cfg.recency.clone()
+    };
+    let method_scores: Vec<RiskScoreKER> = library
+        .iter()
+        .map(|entry| score_method_with(&entry.method, &pest_logs, &recency))
+        .collect();
+
+    let mut out = Vec::new();
+
+    for combo in generate_candidate_plans(library, cfg.max_actions_per_plan) {
+        let plan = InterventionPlan {
+            actions: combo.iter().map(|&i| library[i].action.clone()).collect(),
+            horizon_days: user.horizon_days,
+        };
+
+        let sim = simulate_pest_risk(&ctx, &species, &plan, &cfg.sim);
+        let verdict = evaluate_plan_guard(&sim, &cfg.guard);
+        if !verdict.corridor_safe {
+            continue;
+        }
+
+        let scores: Vec<RiskScoreKER> = combo.iter().map(|&i| method_scores[i].clone()).collect();
+        let plan_ker = aggregate_plan_ker(&scores);
+        if plan_ker.r_risk_harm > user.max_risk_harm {
+            continue;
+        }
+
+        let method_ids: Vec<String> = combo
+            .iter()
+            .map(|&i| library[i].method.method_id.clone())
+            .collect();
+
+        let mut provenance: Vec<String> = pest_logs
+            .iter()
+            .filter(|l| method_ids.contains(&l.method_id))
+            .map(|l| l.context_hash.clone())
+            .collect();
+        provenance.sort();
+        provenance.dedup();
+
+        let final_v = sim.state.residual_v.last().copied().unwrap_or(0.0);
+
+        out.push(PlanRecommendation {
+            plan,
+            method_ids,
+            verdict,
+            method_scores: scores,
+            plan_ker,
+            final_v,
+            provenance,
+        });
+    }
+
+    out.sort_by(|a, b| {
+        a.final_v
+            .partial_cmp(&b.final_v)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| {
+                b.plan_ker
+                    .e_eco_impact
+                    .partial_cmp(&a.plan_ker.e_eco_impact)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    });
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use deadbugs_guard::PlanGuardConfig;
+    use deadbugs_pest_kernel::{PestContext, PestSpeciesModel, PestSpeciesPlugin};
+
+    use super::*;
+
+    const DAY: u64 = 86_400;
+
+    /// Grows from one individual toward 100 unless reproduction is suppressed.
+    struct Rodent;
+
+    impl PestSpeciesPlugin for Rodent {
+        fn species_model(&self, _ctx: &PestContext) -> PestSpeciesModel {
+            PestSpeciesModel {
+                species_id: "rodent.test".to_string(),
+                base_arrival_rate: 0.0,
+                base_repro_rate: 0.5,
+                seasonality_amp: 0.0,
+                seasonality_phase: 0.0,
+                damage_sensitivity: 0.0,
+                eco_sensitivity: 0.0,
+                abundance_hard_limit: 100.0,
+                damage_hard_limit: 100.0,
+                eco_hard_limit: 100.0,
+            }
+        }
+    }
+
+    fn as_of() -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(20_000 * DAY)
+    }
+
+    fn user(max_risk_harm: f64) -> UserContext {
+        UserContext {
+            pest_class: "rodent".to_string(),
+            structure_type: "home".to_string(),
+            climate_band: "temperate".to_string(),
+            human_proximity: 0.5,
+            animal_proximity: 0.5,
+            food_availability: 1.0,
+            water_availability: 1.0,
+            harborage_quality: 1.0,
+            max_risk_harm,
+            horizon_days: 30,
+            as_of: as_of(),
+        }
+    }
+
+    fn entry(method_id: &str, class: &str, lethal: bool, repro_reduction: f64) -> LibraryEntry {
+        LibraryEntry {
+            method: ControlMethod {
+                method_id: method_id.to_string(),
+                class: class.to_string(),
+                lethal,
+                chemical: false,
+            },
+            action: ControlAction {
+                method_id: method_id.to_string(),
+                intensity: 1.0,
+                continuous: true,
+                arrival_reduction_frac: 0.0,
+                repro_reduction_frac: repro_reduction,
+                damage_reduction_frac: 0.0,
+                eco_disturbance_score: 0.0,
+            },
+        }
+    }
+
+    /// Exclusion stops growth on its own; the snap trap does not.
+    fn library() -> Vec<LibraryEntry> {
+        vec![
+            entry("exclusion.seal", "exclusion", false, 1.0),
+            entry("trap.snap", "trap", true, 0.0),
+        ]
+    }
+
+    fn log(method_id: &str, age_days: u64, hash: &str) -> OutcomeLog {
+        OutcomeLog {
+            method_id: method_id.to_string(),
+            pest_class: "rodent".to_string(),
+            context_hash: hash.to_string(),
+            n_cases: 4,
+            effectiveness_band: 0.8,
+            bycatch_band: 0.0,
+            waste_band: 0.2,
+            pet_band: 0.0,
+            wildlife_band: 0.0,
+            human_injury_band: 0.0,
+            air_band: 0.0,
+            timestamp: Some(as_of() - Duration::from_secs(age_days * DAY)),
+        }
+    }
+
+    fn config(recency: RecencyConfig) -> RankingConfig {
+        RankingConfig {
+            sim: SimulationConfig {
+                w_pest: 1.0,
+                w_damage: 0.0,
+                w_eco: 0.0,
+                r_pest_max: 0.5,
+                r_damage_max: 1.0,
+                r_eco_max: 1.0,
+            },
+            guard: PlanGuardConfig {
+                v_max: 1.0,
+                require_v_nonincrease: false,
+                require_all_below_max: false,
+            },
+            max_actions_per_plan: 2,
+            recency,
+        }
+    }
+
+    fn ids(plans: &[PlanRecommendation]) -> Vec<Vec<&str>> {
+        plans
+            .iter()
+            .map(|p| p.method_ids.iter().map(String::as_str).collect())
+            .collect()
+    }
+
+    #[test]
+    fn drops_plans_the_guard_rejects() {
+        let logs = [
+            log("exclusion.seal", 10, "ctx-a"),
+            log("trap.snap", 10, "ctx-b"),
+        ];
+        let plans = rank_plans(
+            &user(1.0),
+            &Rodent,
+            &library(),
+            &logs,
+            &config(RecencyConfig::uniform()),
+        );
+
+        // The trap alone lets the population pass r_pest_max.
+        assert_eq!(
+            ids(&plans),
+            [vec!["exclusion.seal"], vec!["exclusion.seal", "trap.snap"]]
+        );
+        assert!(plans.iter().all(|p| p.verdict.corridor_safe));
+    }
+
+    #[test]
+    fn drops_plans_above_the_risk_ceiling() {
+        let logs = [
+            log("exclusion.seal", 10, "ctx-a"),
+            log("trap.snap", 10, "ctx-b"),
+        ];
+        // The lethal trap alone carries R >= 0.3.
+        let plans = rank_plans(
+            &user(0.25),
+            &Rodent,
+            &library(),
+            &logs,
+            &config(RecencyConfig::uniform()),
+        );
+        assert_eq!(ids(&plans), [vec!["exclusion.seal"]]);
+    }
+
+    #[test]
+    fn attaches_method_scores_and_plan_triad() {
+        let logs = [
+            log("exclusion.seal", 10, "ctx-a"),
+            log("trap.snap", 10, "ctx-b"),
+            log("trap.snap", 20, "ctx-a"),
+        ];
+        let recency = RecencyConfig::uniform();
+        let plans = rank_plans(
+            &user(1.0),
+            &Rodent,
+            &library(),
+            &logs,
+            &config(recency.clone()),
+        );
+        let pair = plans.iter().find(|p| p.method_ids.len() == 2).unwrap();
+
+        let expected: Vec<RiskScoreKER> = library()
+            .iter()
+            .map(|e| score_method_with(&e.method, &logs, &recency))
+            .collect();
+        for (got, want) in pair.method_scores.iter().zip(&expected) {
+            assert_eq!(got.method_id, want.method_id);
+            assert_eq!(got.k_knowledge, want.k_knowledge);
+            assert_eq!(got.e_eco_impact, want.e_eco_impact);
+            assert_eq!(got.r_risk_harm, want.r_risk_harm);
+        }
+        let k_min = expected[0].k_knowledge.min(expected[1].k_knowledge);
+        let e_mean = (expected[0].e_eco_impact + expected[1].e_eco_impact) / 2.0;
+        let r_max = expected[0].r_risk_harm.max(expected[1].r_risk_harm);
+        assert_eq!(pair.plan_ker.k_knowledge, k_min);
+        assert!((pair.plan_ker.e_eco_impact - e_mean).abs() < 1e-12);
+        assert_eq!(pair.plan_ker.r_risk_harm, r_max);
+        assert_eq!(pair.provenance, ["ctx-a", "ctx-b"]);
+    }
+
+    #[test]
+    fn scores_age_against_the_request_time() {
+        let logs = [log("exclusion.seal", 100, "ctx-a")];
+        let recency = RecencyConfig {
+            half_life_days: None,
+            window_days: Some(30.0),
+            as_of: None,
+        };
+        let library = &library()[..1];
+
+        let now = rank_plans(
+            &user(1.0),
+            &Rodent,
+            library,
+            &logs,
+            &config(recency.clone()),
+        );
+        assert_eq!(now[0].method_scores[0].n_effective, 0.0);
+
+        // Replaying the request as of the week after the log keeps it in the window.
+        let mut earlier = user(1.0);
+        earlier.as_of = as_of() - Duration::from_secs(93 * DAY);
+        let then = rank_plans(&earlier, &Rodent, library, &logs, &config(recency));
+        assert!(then[0].method_scores[0].n_effective > 0.0);
+    }
+}
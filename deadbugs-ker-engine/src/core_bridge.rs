@@ -0,0 +1,576 @@
+#![forbid(unsafe_code)]
+
+//! Conversions between `deadbugs_core::model` (canonical, typed) and this engine's
+//! band-based shard records, so one corpus of core logs can feed both scorers.
+//!
+//! Core → engine is total. Engine → core is only defined for `ControlMethod`:
+//! engine logs are pre-aggregated bands and cannot be expanded back into
+//! per-deployment core logs.
+
+use std::fmt;
+use std::time::UNIX_EPOCH;
+
+use deadbugs_core::ker::BandTable;
+use deadbugs_core::model as core;
+use deadbugs_core::recency::RecencyConfig;
+use deadbugs_core::scoring::ScoringModel;
+use deadbugs_core::shard::content_digest;
+use deadbugs_core::strata::ScoringContext;
+
+use crate::{score_method, score_method_with, ControlMethod, OutcomeLog, RiskScoreKER};
+
+/// Why an engine record has no core equivalent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BridgeError {
+    /// Core model excludes chemical methods by construction.
+    ChemicalMethod(String),
+    /// `class` string does not map to any `ControlFamily`.
+    UnknownClass(String),
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeError::ChemicalMethod(id) => {
+                write!(
+                    f,
+                    "method {id} is chemical; not representable in deadbugs_core"
+                )
+            }
+            BridgeError::UnknownClass(class) => write!(f, "unknown control class {class:?}"),
+        }
+    }
+}
+
+impl std::error::Error for BridgeError {}
+
+/// Engine `class` string for a core control family.
+pub fn family_class(family: core::ControlFamily) -> &'static str {
+    match family {
+        core::ControlFamily::Exclusion => "exclusion",
+        core::ControlFamily::Sanitation => "sanitation",
+        core::ControlFamily::HabitatChange => "habitat_mod",
+        core::ControlFamily::MechanicalKill | core::ControlFamily::LiveCapture => "trap",
+        core::ControlFamily::PredatorSupport => "predator_support",
+        core::ControlFamily::MonitoringOnly => "monitoring",
+    }
+}
+
+/// Engine `pest_class` string for a core species.
+pub fn pest_class(pest: core::PestSpecies) -> &'static str {
+    match pest {
+        core::PestSpecies::Rodent => "rodent",
+        core::PestSpecies::Cockroach => "cockroach",
+        core::PestSpecies::Fly => "fly",
+        core::PestSpecies::Mosquito => "mosquito",
+        core::PestSpecies::Termite => "termite",
+        core::PestSpecies::Ant => "ant",
+        core::PestSpecies::StoredProductInsect => "stored_product_insect",
+        core::PestSpecies::Other => "other",
+    }
+}
+
+/// Operator band → 0–1 effectiveness band.
+pub fn effectiveness_band(band: core::EffectivenessBand) -> f64 {
+    match band {
+        core::EffectivenessBand::High => 1.0,
+        core::EffectivenessBand::Medium => 0.5,
+        core::EffectivenessBand::Low => 0.0,
+    }
+}
+
+/// Non-target kills saturate towards 1.0; any wildlife incident floors the band at 0.5.
+fn bycatch_band(side: &core::SideEffects) -> f64 {
+    let kills = side.non_target_kill_count.min(i32::MAX as u32) as i32;
+    let from_kills = 1.0 - 0.5_f64.powi(kills);
+    if side.wildlife_incident {
+        from_kills.max(0.5)
+    } else {
+        from_kills
+    }
+}
+
+/// A single deployment's incident flag as a share of one case.
+fn flag_band(incident: bool) -> f64 {
+    if incident {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Same qualitative classes as `deadbugs_core::ker`.
+fn waste_band(burden: &str) -> f64 {
+    match burden {
+        "high" => 1.0,
+        "moderate" => 0.5,
+        _ => 0.0,
+    }
+}
+
+impl From<&core::ControlMethod> for ControlMethod {
+    fn from(m: &core::ControlMethod) -> Self {
+        ControlMethod {
+            method_id: m.id.clone(),
+            class: family_class(m.family).to_string(),
+            lethal: matches!(m.family, core::ControlFamily::MechanicalKill),
+            chemical: false, // core families are non-chemical by construction.
+        }
+    }
+}
+
+impl TryFrom<&ControlMethod> for core::ControlMethod {
+    type Error = BridgeError;
+
+    /// Material and lure details are not carried by engine records. The material
+    /// flags default to `true`, so material rules (e.g. no plastics at home or in
+    /// hospitals) treat the method as if it used both; lure and trap details stay
+    /// unset.
+    fn try_from(m: &ControlMethod) -> Result<Self, Self::Error> {
+        if m.chemical {
+            return Err(BridgeError::ChemicalMethod(m.method_id.clone()));
+        }
+        let family = match m.class.as_str() {
+            "exclusion" => core::ControlFamily::Exclusion,
+            "sanitation" => core::ControlFamily::Sanitation,
+            "habitat_mod" => core::ControlFamily::HabitatChange,
+            "trap" if m.lethal => core::ControlFamily::MechanicalKill,
+            "trap" => core::ControlFamily::LiveCapture,
+            "predator_support" => core::ControlFamily::PredatorSupport,
+            "monitoring" => core::ControlFamily::MonitoringOnly,
+            other => return Err(BridgeError::UnknownClass(other.to_string())),
+        };
+        Ok(core::ControlMethod {
+            id: m.method_id.clone(),
+            family,
+            trap_type: None,
+            lure_type: core::LureType::None,
+            exclusion: None,
+            uses_disposable_electronics: true,
+            generates_persistent_plastic: true,
+            notes: None,
+            revised_at: None,
+        })
+    }
+}
+
+impl From<&core::OutcomeLog> for OutcomeLog {
+    /// One core deployment becomes a single-case engine log.
+    fn from(log: &core::OutcomeLog) -> Self {
+        OutcomeLog {
+            method_id: log.method_id.clone(),
+            pest_class: pest_class(log.context.pest).to_string(),
+            context_hash: log.meta.hex_stamp.clone(),
+            n_cases: 1,
+            effectiveness_band: effectiveness_band(log.effectiveness),
+            bycatch_band: bycatch_band(&log.side_effects),
+            waste_band: waste_band(log.side_effects.waste_burden.as_str()),
+            pet_band: flag_band(log.side_effects.pet_incident),
+            wildlife_band: flag_band(log.side_effects.wildlife_incident),
+            human_injury_band: flag_band(log.side_effects.human_injury),
+            air_band: flag_band(log.side_effects.air_quality_concern),
+            timestamp: Some(log.meta.timestamp),
+        }
+    }
+}
+
+/// Score a core method against core logs with this engine's K/E/R rules.
+pub fn score_core_method(method: &core::ControlMethod, logs: &[core::OutcomeLog]) -> RiskScoreKER {
+    let engine_method = ControlMethod::from(method);
+    let engine_logs: Vec<OutcomeLog> = logs.iter().map(OutcomeLog::from).collect();
+    score_method(&engine_method, &engine_logs)
+}
+
+/// This engine as a `deadbugs_core::scoring::ScoringModel`.
+///
+/// The engine has no corridor breakdown, strata or credible intervals: it scores
+/// every log it is given, intervals collapse to the point estimates, and corridor
+/// coordinates stay at zero. Bands still grade incidence as the core scorer does,
+/// so automatic quarantine and the hard-violation filter work under this model:
+/// the share of in-window deployments with each incident, and for bycatch and
+/// waste the mean engine band.
+#[derive(Clone, Debug, Default)]
+pub struct EngineModel {
+    pub recency: RecencyConfig,
+    pub bands: BandTable,
+}
+
+impl EngineModel {
+    pub const MODEL_ID: &'static str = "deadbugs-ker-engine-v2";
+
+    /// Incidence of the method's in-window logs, each deployment counting once.
+    fn incidence(
+        &self,
+        method_id: &str,
+        logs: &[&core::OutcomeLog],
+        recency: &RecencyConfig,
+    ) -> core::RiskCoordinates {
+        let as_of = recency.reference_time();
+        let in_window: Vec<&core::SideEffects> = logs
+            .iter()
+            .filter(|l| l.method_id == method_id && recency.weight(l.meta.timestamp, as_of) > 0.0)
+            .map(|l| &l.side_effects)
+            .collect();
+        if in_window.is_empty() {
+            return core::RiskCoordinates::default();
+        }
+        let n = in_window.len() as f64;
+        let mean = |band: fn(&core::SideEffects) -> f64| {
+            in_window.iter().map(|&s| band(s)).sum::<f64>() / n
+        };
+        core::RiskCoordinates {
+            r_pets: mean(|s| flag_band(s.pet_incident)),
+            r_wildlife: mean(|s| flag_band(s.wildlife_incident)),
+            r_waste: mean(|s| waste_band(s.waste_burden.as_str())),
+            r_air: mean(|s| flag_band(s.air_quality_concern)),
+            r_human_injury: mean(|s| flag_band(s.human_injury)),
+            r_bycatch: mean(bycatch_band),
+        }
+    }
+}
+
+impl ScoringModel for EngineModel {
+    /// `MODEL_ID`, plus the recency settings and band table when they differ from
+    /// the defaults, so scores under different settings never share an ID.
+    fn model_id(&self) -> String {
+        let mut id = Self::MODEL_ID.to_string();
+        if let Some(half_life) = self.recency.half_life_days {
+            id.push_str(&format!("+half-life-{half_life}d"));
+        }
+        if let Some(window) = self.recency.window_days {
+            id.push_str(&format!("+window-{window}d"));
+        }
+        if let Some(as_of) = self.recency.as_of {
+            let secs = as_of.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            id.push_str(&format!("+as-of-{secs}"));
+        }
+        // Band tables hold only numbers, which always serialize.
+        let bands = content_digest(&self.bands).unwrap_or_default();
+        if bands != content_digest(&BandTable::default()).unwrap_or_default() {
+            id.push_str(&format!("+bands-{bands}"));
+        }
+        id
+    }
+
+    fn score(
+        &self,
+        method: &core::ControlMethod,
+        logs: &[&core::OutcomeLog],
+        _ctx: &ScoringContext,
+    ) -> core::KerScore {
+        // Pin the clock once so K/E/R and the bands age the logs alike.
+        let recency = RecencyConfig {
+            as_of: Some(self.recency.reference_time()),
+            ..self.recency.clone()
+        };
+        let engine_method = ControlMethod::from(method);
+        let engine_logs: Vec<OutcomeLog> = logs.iter().map(|&l| OutcomeLog::from(l)).collect();
+        let ker = score_method_with(&engine_method, &engine_logs, &recency);
+        let incidence = self.incidence(&method.id, logs, &recency);
+        let bands = self.bands.classify(&incidence);
+        let hard_violation = core::Corridor::PROTECTED
+            .iter()
+            .any(|&c| bands.get(c) == core::CorridorBand::Hard);
+
+        let point = |v: f64| core::CredibleInterval { lower: v, upper: v };
+        core::KerScore {
+            k: ker.k_knowledge,
+            e: ker.e_eco_impact,
+            r: ker.r_risk_harm,
+            k_interval: point(ker.k_knowledge),
+            r_interval: point(ker.r_risk_harm),
+            n_effective: ker.n_effective,
+            capture: core::CaptureEffectiveness::default(),
+            coords: core::RiskCoordinates::default(),
+            coords_lower: core::RiskCoordinates::default(),
+            coords_upper: core::RiskCoordinates::default(),
+            incidence,
+            bands,
+            hard_violation,
+            stratum: core::Stratum::Pooled,
+            config_id: self.model_id(),
+            recency: Some(recency),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    const FAMILIES: [core::ControlFamily; 7] = [
+        core::ControlFamily::Exclusion,
+        core::ControlFamily::Sanitation,
+        core::ControlFamily::MechanicalKill,
+        core::ControlFamily::LiveCapture,
+        core::ControlFamily::HabitatChange,
+        core::ControlFamily::PredatorSupport,
+        core::ControlFamily::MonitoringOnly,
+    ];
+
+    fn core_method(family: core::ControlFamily) -> core::ControlMethod {
+        core::ControlMethod {
+            id: "m1".to_string(),
+            family,
+            trap_type: None,
+            lure_type: core::LureType::None,
+            exclusion: None,
+            uses_disposable_electronics: false,
+            generates_persistent_plastic: false,
+            notes: None,
+            revised_at: None,
+        }
+    }
+
+    fn core_log(side_effects: core::SideEffects) -> core::OutcomeLog {
+        core::OutcomeLog {
+            method_id: "m1".to_string(),
+            context: core::PestContext {
+                location_type: core::LocationType::Home,
+                pest: core::PestSpecies::Cockroach,
+                proximity: core::ProximityTags::default(),
+                hygiene: core::HygieneContext::default(),
+                building_has_gaps: false,
+                moisture_high: false,
+                food_waste_available: false,
+            },
+            effectiveness: core::EffectivenessBand::Medium,
+            side_effects,
+            target_count: 3,
+            observation_days: 14,
+            meta: core::EvidenceMeta {
+                bostrom_address: "bostrom1".to_string(),
+                alt_address: None,
+                hex_stamp: "0xabc".to_string(),
+                location_cell: "PHX-1".to_string(),
+                timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            },
+            recommendation_id: None,
+        }
+    }
+
+    fn clean() -> core::SideEffects {
+        core::SideEffects {
+            waste_burden: "moderate".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The four incident bands of an engine log, in a fixed order.
+    fn incident_bands(log: &OutcomeLog) -> [f64; 4] {
+        [
+            log.pet_band,
+            log.wildlife_band,
+            log.human_injury_band,
+            log.air_band,
+        ]
+    }
+
+    #[test]
+    fn engine_methods_round_trip_through_core() {
+        for (class, lethal) in [
+            ("exclusion", false),
+            ("sanitation", false),
+            ("habitat_mod", false),
+            ("trap", true),
+            ("trap", false),
+            ("predator_support", false),
+            ("monitoring", false),
+        ] {
+            let engine = ControlMethod {
+                method_id: format!("{class}.x"),
+                class: class.to_string(),
+                lethal,
+                chemical: false,
+            };
+            let back = ControlMethod::from(&core::ControlMethod::try_from(&engine).unwrap());
+            assert_eq!(back.method_id, engine.method_id);
+            assert_eq!(back.class, engine.class);
+            assert_eq!(back.lethal, engine.lethal);
+            assert_eq!(back.chemical, engine.chemical);
+        }
+    }
+
+    #[test]
+    fn core_families_round_trip_through_engine() {
+        for family in FAMILIES {
+            let method = core_method(family);
+            let back = core::ControlMethod::try_from(&ControlMethod::from(&method)).unwrap();
+            assert_eq!(back.id, method.id);
+            assert_eq!(back.family, family);
+        }
+    }
+
+    #[test]
+    fn unknown_material_flags_default_to_set() {
+        let engine = ControlMethod::from(&core_method(core::ControlFamily::MonitoringOnly));
+        let back = core::ControlMethod::try_from(&engine).unwrap();
+        assert!(back.uses_disposable_electronics);
+        assert!(back.generates_persistent_plastic);
+    }
+
+    #[test]
+    fn rejects_chemical_and_unknown_classes() {
+        let mut engine = ControlMethod {
+            method_id: "bait.poison".to_string(),
+            class: "trap".to_string(),
+            lethal: true,
+            chemical: true,
+        };
+        assert_eq!(
+            core::ControlMethod::try_from(&engine).unwrap_err(),
+            BridgeError::ChemicalMethod("bait.poison".to_string())
+        );
+        engine.chemical = false;
+        engine.class = "fumigation".to_string();
+        assert_eq!(
+            core::ControlMethod::try_from(&engine).unwrap_err(),
+            BridgeError::UnknownClass("fumigation".to_string())
+        );
+    }
+
+    #[test]
+    fn core_log_fields_carry_into_engine_log() {
+        let core = core_log(clean());
+        let log = OutcomeLog::from(&core);
+        assert_eq!(log.method_id, "m1");
+        assert_eq!(log.pest_class, "cockroach");
+        assert_eq!(log.context_hash, "0xabc");
+        assert_eq!(log.n_cases, 1);
+        assert_eq!(log.effectiveness_band, 0.5);
+        assert_eq!(log.bycatch_band, 0.0);
+        assert_eq!(log.waste_band, 0.5);
+        assert_eq!(incident_bands(&log), [0.0; 4]);
+        assert_eq!(log.timestamp, Some(core.meta.timestamp));
+    }
+
+    #[test]
+    fn every_incident_flag_reaches_the_engine_log() {
+        let flags: [fn(&mut core::SideEffects); 4] = [
+            |s| s.pet_incident = true,
+            |s| s.wildlife_incident = true,
+            |s| s.human_injury = true,
+            |s| s.air_quality_concern = true,
+        ];
+        for (i, set) in flags.iter().enumerate() {
+            let mut side = clean();
+            set(&mut side);
+            let log = OutcomeLog::from(&core_log(side));
+            let mut expected = [0.0; 4];
+            expected[i] = 1.0;
+            assert_eq!(incident_bands(&log), expected);
+        }
+
+        let mut side = clean();
+        side.non_target_kill_count = 2;
+        assert_eq!(OutcomeLog::from(&core_log(side)).bycatch_band, 0.75);
+    }
+
+    #[test]
+    fn incidents_raise_engine_risk() {
+        let method = core_method(core::ControlFamily::Exclusion);
+        let clean_logs = vec![core_log(clean()); 10];
+        let mut injured = clean();
+        injured.human_injury = true;
+        let injured_logs = vec![core_log(injured); 10];
+
+        let r_clean = score_core_method(&method, &clean_logs).r_risk_harm;
+        let r_injured = score_core_method(&method, &injured_logs).r_risk_harm;
+        assert!((r_injured - r_clean - 0.3).abs() < 1e-9);
+    }
+
+    fn engine_score(model: &EngineModel, logs: &[core::OutcomeLog]) -> core::KerScore {
+        let refs: Vec<&core::OutcomeLog> = logs.iter().collect();
+        model.score(
+            &core_method(core::ControlFamily::LiveCapture),
+            &refs,
+            &ScoringContext::new(core::LocationType::Home),
+        )
+    }
+
+    #[test]
+    fn engine_scores_band_incidence() {
+        let model = EngineModel::default();
+        let mut logs = vec![core_log(clean()); 10];
+        let score = engine_score(&model, &logs);
+        assert!(core::Corridor::PROTECTED
+            .iter()
+            .all(|&c| score.bands.get(c) == core::CorridorBand::Safe));
+        assert!(!score.hard_violation);
+        // Moderate waste everywhere sits on the gold edge.
+        assert_eq!(score.incidence.r_waste, 0.5);
+        assert_eq!(score.bands.waste, core::CorridorBand::Gold);
+
+        // One pet incident in ten deployments reaches the hard pets band.
+        logs[0].side_effects.pet_incident = true;
+        let score = engine_score(&model, &logs);
+        assert_eq!(score.incidence.r_pets, 0.1);
+        assert_eq!(score.bands.pets, core::CorridorBand::Hard);
+        assert!(score.hard_violation);
+
+        // Bycatch reads the engine band: one kill in every deployment grades 0.5.
+        let mut side = clean();
+        side.non_target_kill_count = 1;
+        let score = engine_score(&model, &vec![core_log(side); 4]);
+        assert_eq!(score.incidence.r_bycatch, 0.5);
+        assert_eq!(score.bands.bycatch, core::CorridorBand::Hard);
+        assert!(score.hard_violation);
+    }
+
+    #[test]
+    fn engine_bands_skip_logs_outside_the_window() {
+        let mut pet = clean();
+        pet.pet_incident = true;
+        let logs = vec![core_log(pet), core_log(clean())];
+        let model = EngineModel {
+            recency: RecencyConfig {
+                window_days: Some(30.0),
+                as_of: Some(logs[0].meta.timestamp + Duration::from_secs(365 * 86_400)),
+                ..RecencyConfig::uniform()
+            },
+            ..EngineModel::default()
+        };
+        let score = engine_score(&model, &logs);
+        assert_eq!(score.incidence.r_pets, 0.0);
+        assert_eq!(score.bands.pets, core::CorridorBand::Safe);
+        assert!(!score.hard_violation);
+    }
+
+    #[test]
+    fn model_id_tracks_recency_and_bands() {
+        let default = EngineModel::default();
+        assert_eq!(default.model_id(), EngineModel::MODEL_ID);
+
+        let decay = EngineModel {
+            recency: RecencyConfig::decay(),
+            ..EngineModel::default()
+        };
+        assert_eq!(
+            decay.model_id(),
+            "deadbugs-ker-engine-v2+half-life-365d+window-1825d"
+        );
+        let pinned = EngineModel {
+            recency: RecencyConfig {
+                as_of: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+                ..RecencyConfig::decay()
+            },
+            ..EngineModel::default()
+        };
+        assert_eq!(
+            pinned.model_id(),
+            "deadbugs-ker-engine-v2+half-life-365d+window-1825d+as-of-1700000000"
+        );
+
+        let mut strict = EngineModel::default();
+        strict.bands.pets.hard = 0.05;
+        assert!(strict
+            .model_id()
+            .starts_with("deadbugs-ker-engine-v2+bands-0x"));
+
+        let score = engine_score(&pinned, &[core_log(clean())]);
+        assert_eq!(score.config_id, pinned.model_id());
+        assert_eq!(score.recency.unwrap().as_of, pinned.recency.as_of);
+    }
+}
@@ -1,3 +1,10 @@
+use std::f64::consts::E;
+use std::time::SystemTime;
+
+pub use deadbugs_core::recency::RecencyConfig;
+
+pub mod core_bridge;
+
 #[derive(Clone, Debug)]
 pub struct ControlMethod {
     pub method_id: String,
@@ -15,6 +22,11 @@ pub struct OutcomeLog {
     pub effectiveness_band: f64,        // 0–1 (field-logged, not guessed).
     pub bycatch_band: f64,              // 0–1 (0 = no bycatch).
     pub waste_band: f64,                // 0–1 (0 = minimal waste).
+    pub pet_band: f64,                  // 0–1 share of cases with a pet incident.
+    pub wildlife_band: f64,             // 0–1 share of cases with a wildlife incident.
+    pub human_injury_band: f64,         // 0–1 share of cases injuring an operator or bystander.
+    pub air_band: f64,                  // 0–1 share of cases with an air-quality concern.
+    pub timestamp: Option<SystemTime>,  // EvidenceMeta.timestamp; None = undated, full weight.
 }
 
 #[derive(Clone, Debug)]
@@ -23,31 +35,56 @@ pub struct RiskScoreKER {
     pub k_knowledge: f64,   // 0–1
     pub e_eco_impact: f64,  // 0–1 (higher = more eco-positive).
     pub r_risk_harm: f64,   // 0–1 (higher = more harmful).
+    pub n_effective: f64,   // effective number of cases after recency weighting.
 }
 
-/// Aggregate K/E/R from outcome logs for a single method.
+/// Aggregate K/E/R from outcome logs for a single method; every log weighs the same.
 pub fn score_method(method: &ControlMethod, logs: &[OutcomeLog]) -> RiskScoreKER {
+    score_method_with(method, logs, &RecencyConfig::default())
+}
+
+/// Aggregate K/E/R with explicit recency weighting; each case counts by its log's age.
+pub fn score_method_with(
+    method: &ControlMethod,
+    logs: &[OutcomeLog],
+    recency: &RecencyConfig,
+) -> RiskScoreKER {
+    let as_of = recency.reference_time();
     let mut total_cases: f64 = 0.0;
+    let mut total_cases_sq: f64 = 0.0;
     let mut eff_weighted: f64 = 0.0;
     let mut bycatch_weighted: f64 = 0.0;
     let mut waste_weighted: f64 = 0.0;
+    let mut incident_weighted: f64 = 0.0;
 
     for log in logs.iter().filter(|l| l.method_id == method.method_id) {
-        let w = log.n_cases.max(1) as f64;
+        let decay = log.timestamp.map_or(1.0, |ts| recency.weight(ts, as_of));
+        if decay <= 0.0 {
+            continue;
+        }
+        let cases = log.n_cases.max(1) as f64;
+        let w = cases * decay;
         total_cases += w;
+        total_cases_sq += cases * decay * decay;
         eff_weighted += w * log.effectiveness_band.clamp(0.0, 1.0);
         bycatch_weighted += w * log.bycatch_band.clamp(0.0, 1.0);
         waste_weighted += w * log.waste_band.clamp(0.0, 1.0);
+        // Wildlife incidents already floor the bycatch band.
+        incident_weighted += w
+            * (0.3 * log.human_injury_band.clamp(0.0, 1.0)
+                + 0.2 * log.pet_band.clamp(0.0, 1.0)
+                + 0.1 * log.air_band.clamp(0.0, 1.0));
     }
 
-    let (avg_eff, avg_bycatch, avg_waste) = if total_cases > 0.0 {
+    let (avg_eff, avg_bycatch, avg_waste, avg_incident) = if total_cases > 0.0 {
         (
             eff_weighted / total_cases,
             bycatch_weighted / total_cases,
             waste_weighted / total_cases,
+            incident_weighted / total_cases,
         )
     } else {
-        (0.0, 0.5, 0.5) // low evidence defaults.
+        (0.0, 0.5, 0.5, 0.0) // low evidence defaults.
     };
 
     // Knowledge: more and more consistent logs → higher K.
@@ -75,8 +112,8 @@ pub fn score_method(method: &ControlMethod, logs: &[OutcomeLog]) -> RiskScoreKER
         - chemical_penalty;
     e = e.clamp(0.0, 1.0);
 
-    // Risk-of-harm: dominated by bycatch, waste, and lethal/chemical flags.
-    let mut r = 0.2 * avg_bycatch + 0.2 * avg_waste;
+    // Risk-of-harm: dominated by bycatch, waste, incidents, and lethal/chemical flags.
+    let mut r = 0.2 * avg_bycatch + 0.2 * avg_waste + avg_incident;
     if method.lethal {
         r += 0.3;
     }
@@ -90,5 +127,10 @@ pub fn score_method(method: &ControlMethod, logs: &[OutcomeLog]) -> RiskScoreKER
         k_knowledge: k,
         e_eco_impact: e,
         r_risk_harm: r,
+        n_effective: if total_cases_sq > 0.0 {
+            total_cases * total_cases / total_cases_sq
+        } else {
+            0.0
+        },
     }
 }
@@ -1,7 +1,9 @@
 use std::f64::consts::E;
 
+use serde::{Deserialize, Serialize};
+
 /// Species-agnostic context for one site and pest class.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PestContext {
     // Structural / environmental descriptors (normalized or scalar).
     pub structure_type: String,   // "home", "restaurant", "farm", etc.
@@ -14,7 +16,7 @@ pub struct PestContext {
 }
 
 /// Species-specific parameters loaded from a plugin.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PestSpeciesModel {
     pub species_id: String,       // e.g., "bedbug.cimex_lectularius", "rodent.rattus".
     // Baseline biological parameters (per day).
@@ -32,7 +34,7 @@ pub struct PestSpeciesModel {
 }
 
 /// Abstract, non-toxic control methods (physical, mechanical, behavioral).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ControlAction {
     pub method_id: String,      // e.g., "exclusion.seal_cracks", "trap.snap", "sanitation.deep_clean".
     pub intensity: f64,         // 0–1, normalized effort level.
@@ -45,14 +47,14 @@ pub struct ControlAction {
 }
 
 /// A full candidate plan: set of actions with timing.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InterventionPlan {
     pub actions: Vec<ControlAction>,
     pub horizon_days: u32,
 }
 
 /// Simulated state over time for one plan.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PestRiskState {
     pub times_days: Vec<u32>,
     pub abundance: Vec<f64>,        // N_t
@@ -65,7 +67,7 @@ pub struct PestRiskState {
 }
 
 /// Simulation-level configuration (weights and hard limits).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SimulationConfig {
     pub w_pest: f64,
     pub w_damage: f64,
@@ -76,7 +78,7 @@ pub struct SimulationConfig {
 }
 
 /// Result + convenience summary for guards/UI.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SimulationResult {
     pub state: PestRiskState,
     pub violated_hard_limit: bool,
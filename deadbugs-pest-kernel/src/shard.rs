@@ -0,0 +1,449 @@
+#![forbid(unsafe_code)]
+
+//! Versioned JSON and flat CSV shard encodings for kernel inputs and outputs.
+//!
+//! Built on the `deadbugs_core` shard framework (envelope, preamble, versioning
+//! and `ShardError`): JSON is a `{schema, version, records}` envelope, CSV is a
+//! `# schema=<name> version=<n>` line, a header, then flat rows. Time-series types
+//! (`PestRiskState`, `SimulationResult`) become one CSV row per simulated day;
+//! `InterventionPlan` becomes one row per action. Values a flat encoding cannot
+//! reproduce exactly are rejected with `ShardError::Unrepresentable`.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+pub use deadbugs_core::shard::{from_json, to_json, ShardError, ShardSchema, SHARD_SCHEMA_VERSION};
+use deadbugs_core::shard::{read_csv, write_csv};
+
+use crate::{
+    ControlAction, InterventionPlan, PestContext, PestRiskState, PestSpeciesModel,
+    SimulationConfig, SimulationResult,
+};
+
+/// A kernel type that can be stored as a CSV shard. One value may span several rows.
+pub trait ShardRows: ShardSchema {
+    type Row: Serialize + DeserializeOwned;
+
+    fn to_rows(&self) -> Result<Vec<Self::Row>, ShardError>;
+    fn from_rows(rows: Vec<Self::Row>) -> Result<Self, ShardError>;
+}
+
+/// Encode a single value as a flat, versioned CSV shard.
+pub fn to_csv<T: ShardRows>(value: &T) -> Result<String, ShardError> {
+    write_csv(T::SCHEMA, &value.to_rows()?)
+}
+
+/// Decode a flat, versioned CSV shard back into a single value.
+pub fn from_csv<T: ShardRows>(text: &str) -> Result<T, ShardError> {
+    T::from_rows(read_csv(T::SCHEMA, text)?)
+}
+
+/// Flat types are their own single CSV row.
+macro_rules! flat_record {
+    ($ty:ty, $schema:expr) => {
+        impl ShardSchema for $ty {
+            const SCHEMA: &'static str = $schema;
+        }
+
+        impl ShardRows for $ty {
+            type Row = $ty;
+
+            fn to_rows(&self) -> Result<Vec<Self::Row>, ShardError> {
+                Ok(vec![self.clone()])
+            }
+
+            fn from_rows(rows: Vec<Self::Row>) -> Result<Self, ShardError> {
+                let mut rows = rows.into_iter();
+                match (rows.next(), rows.next()) {
+                    (Some(row), None) => Ok(row),
+                    _ => Err(ShardError::InconsistentRows("row count")),
+                }
+            }
+        }
+    };
+}
+
+// Distinct from the core `DeadbugsPestContext`, which has a different shape.
+flat_record!(PestContext, "DeadbugsKernelPestContext");
+flat_record!(PestSpeciesModel, "DeadbugsPestSpeciesModel");
+flat_record!(ControlAction, "DeadbugsControlAction");
+flat_record!(SimulationConfig, "DeadbugsSimulationConfig");
+
+/// One action of an [`InterventionPlan`], with the plan horizon repeated on each row.
+/// A plan with no actions is written as a single row with an empty `method_id`,
+/// so actions themselves must have a non-empty one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlanActionRow {
+    pub horizon_days: u32,
+    pub method_id: String,
+    pub intensity: f64,
+    pub continuous: bool,
+    pub arrival_reduction_frac: f64,
+    pub repro_reduction_frac: f64,
+    pub damage_reduction_frac: f64,
+    pub eco_disturbance_score: f64,
+}
+
+impl ShardSchema for InterventionPlan {
+    const SCHEMA: &'static str = "DeadbugsInterventionPlan";
+}
+
+impl ShardRows for InterventionPlan {
+    type Row = PlanActionRow;
+
+    fn to_rows(&self) -> Result<Vec<Self::Row>, ShardError> {
+        if self.actions.is_empty() {
+            return Ok(vec![PlanActionRow {
+                horizon_days: self.horizon_days,
+                method_id: String::new(),
+                intensity: 0.0,
+                continuous: false,
+                arrival_reduction_frac: 0.0,
+                repro_reduction_frac: 0.0,
+                damage_reduction_frac: 0.0,
+                eco_disturbance_score: 0.0,
+            }]);
+        }
+        if self.actions.iter().any(|a| a.method_id.is_empty()) {
+            return Err(ShardError::Unrepresentable(
+                "an action with an empty method_id reads back as an empty plan",
+            ));
+        }
+        Ok(self
+            .actions
+            .iter()
+            .map(|a| PlanActionRow {
+                horizon_days: self.horizon_days,
+                method_id: a.method_id.clone(),
+                intensity: a.intensity,
+                continuous: a.continuous,
+                arrival_reduction_frac: a.arrival_reduction_frac,
+                repro_reduction_frac: a.repro_reduction_frac,
+                damage_reduction_frac: a.damage_reduction_frac,
+                eco_disturbance_score: a.eco_disturbance_score,
+            })
+            .collect())
+    }
+
+    fn from_rows(rows: Vec<Self::Row>) -> Result<Self, ShardError> {
+        let horizon_days = rows
+            .first()
+            .map(|r| r.horizon_days)
+            .ok_or(ShardError::InconsistentRows("horizon_days"))?;
+        if rows.iter().any(|r| r.horizon_days != horizon_days) {
+            return Err(ShardError::InconsistentRows("horizon_days"));
+        }
+        // Only the lone placeholder row of an empty plan may lack a method_id.
+        if rows.len() == 1 && rows[0].method_id.is_empty() {
+            return Ok(InterventionPlan {
+                actions: Vec::new(),
+                horizon_days,
+            });
+        }
+        if rows.iter().any(|r| r.method_id.is_empty()) {
+            return Err(ShardError::InconsistentRows("method_id"));
+        }
+        let actions = rows
+            .into_iter()
+            .map(|r| ControlAction {
+                method_id: r.method_id,
+                intensity: r.intensity,
+                continuous: r.continuous,
+                arrival_reduction_frac: r.arrival_reduction_frac,
+                repro_reduction_frac: r.repro_reduction_frac,
+                damage_reduction_frac: r.damage_reduction_frac,
+                eco_disturbance_score: r.eco_disturbance_score,
+            })
+            .collect();
+        Ok(InterventionPlan {
+            actions,
+            horizon_days,
+        })
+    }
+}
+
+/// One simulated day of a [`PestRiskState`] (plus the result flag for [`SimulationResult`]).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RiskStateRow {
+    pub day: u32,
+    pub abundance: f64,
+    pub damage_metric: f64,
+    pub eco_metric: f64,
+    pub r_pest: f64,
+    pub r_damage: f64,
+    pub r_eco: f64,
+    pub residual_v: f64,
+    pub violated_hard_limit: bool,
+}
+
+fn state_rows(state: &PestRiskState, violated: bool) -> Result<Vec<RiskStateRow>, ShardError> {
+    let lens = [
+        state.times_days.len(),
+        state.abundance.len(),
+        state.damage_metric.len(),
+        state.eco_metric.len(),
+        state.r_pest.len(),
+        state.r_damage.len(),
+        state.r_eco.len(),
+        state.residual_v.len(),
+    ];
+    let n = lens[0];
+    if lens.iter().any(|&len| len != n) {
+        return Err(ShardError::Unrepresentable(
+            "PestRiskState series differ in length",
+        ));
+    }
+
+    Ok((0..n)
+        .map(|i| RiskStateRow {
+            day: state.times_days[i],
+            abundance: state.abundance[i],
+            damage_metric: state.damage_metric[i],
+            eco_metric: state.eco_metric[i],
+            r_pest: state.r_pest[i],
+            r_damage: state.r_damage[i],
+            r_eco: state.r_eco[i],
+            residual_v: state.residual_v[i],
+            violated_hard_limit: violated,
+        })
+        .collect())
+}
+
+fn state_from_rows(rows: &[RiskStateRow]) -> PestRiskState {
+    PestRiskState {
+        times_days: rows.iter().map(|r| r.day).collect(),
+        abundance: rows.iter().map(|r| r.abundance).collect(),
+        damage_metric: rows.iter().map(|r| r.damage_metric).collect(),
+        eco_metric: rows.iter().map(|r| r.eco_metric).collect(),
+        r_pest: rows.iter().map(|r| r.r_pest).collect(),
+        r_damage: rows.iter().map(|r| r.r_damage).collect(),
+        r_eco: rows.iter().map(|r| r.r_eco).collect(),
+        residual_v: rows.iter().map(|r| r.residual_v).collect(),
+    }
+}
+
+impl ShardSchema for PestRiskState {
+    const SCHEMA: &'static str = "DeadbugsPestRiskState";
+}
+
+impl ShardRows for PestRiskState {
+    type Row = RiskStateRow;
+
+    fn to_rows(&self) -> Result<Vec<Self::Row>, ShardError> {
+        state_rows(self, false)
+    }
+
+    fn from_rows(rows: Vec<Self::Row>) -> Result<Self, ShardError> {
+        Ok(state_from_rows(&rows))
+    }
+}
+
+impl ShardSchema for SimulationResult {
+    const SCHEMA: &'static str = "DeadbugsSimulationResult";
+}
+
+impl ShardRows for SimulationResult {
+    type Row = RiskStateRow;
+
+    /// The hard-limit flag rides on every day's row, so a result with no days
+    /// can only be encoded when the flag is unset.
+    fn to_rows(&self) -> Result<Vec<Self::Row>, ShardError> {
+        if self.state.times_days.is_empty() && self.violated_hard_limit {
+            return Err(ShardError::Unrepresentable(
+                "a SimulationResult with no states cannot carry violated_hard_limit",
+            ));
+        }
+        state_rows(&self.state, self.violated_hard_limit)
+    }
+
+    fn from_rows(rows: Vec<Self::Row>) -> Result<Self, ShardError> {
+        let violated_hard_limit = rows.first().map(|r| r.violated_hard_limit).unwrap_or(false);
+        if rows
+            .iter()
+            .any(|r| r.violated_hard_limit != violated_hard_limit)
+        {
+            return Err(ShardError::InconsistentRows("violated_hard_limit"));
+        }
+        Ok(SimulationResult {
+            state: state_from_rows(&rows),
+            violated_hard_limit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// JSON and CSV must both give back a value that serializes identically.
+    fn round_trip<T: ShardRows>(value: &T) {
+        let want = serde_json::to_value(value).unwrap();
+        let json = from_json::<T>(&to_json(std::slice::from_ref(value)).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&json).unwrap(),
+            serde_json::json!([want])
+        );
+        let csv = from_csv::<T>(&to_csv(value).unwrap()).unwrap();
+        assert_eq!(serde_json::to_value(&csv).unwrap(), want);
+    }
+
+    fn action(method_id: &str) -> ControlAction {
+        ControlAction {
+            method_id: method_id.to_string(),
+            intensity: 0.8,
+            continuous: true,
+            arrival_reduction_frac: 0.6,
+            repro_reduction_frac: 0.1,
+            damage_reduction_frac: 0.3,
+            eco_disturbance_score: 0.05,
+        }
+    }
+
+    fn state(days: u32) -> PestRiskState {
+        let series = |scale: f64| (0..days).map(|d| d as f64 * scale).collect::<Vec<_>>();
+        PestRiskState {
+            times_days: (0..days).collect(),
+            abundance: series(1.5),
+            damage_metric: series(0.25),
+            eco_metric: series(0.01),
+            r_pest: series(0.015),
+            r_damage: series(0.0025),
+            r_eco: series(0.001),
+            residual_v: series(0.02),
+        }
+    }
+
+    #[test]
+    fn flat_records_round_trip() {
+        round_trip(&PestContext {
+            structure_type: "restaurant".to_string(),
+            climate_band: "arid-hot".to_string(),
+            human_proximity: 0.9,
+            animal_proximity: 0.1,
+            food_availability: 0.7,
+            water_availability: 0.3,
+            harborage_quality: 0.5,
+        });
+        round_trip(&PestSpeciesModel {
+            species_id: "rodent.rattus".to_string(),
+            base_arrival_rate: 0.2,
+            base_repro_rate: 0.05,
+            seasonality_amp: 0.3,
+            seasonality_phase: 1.2,
+            damage_sensitivity: 0.4,
+            eco_sensitivity: 0.6,
+            abundance_hard_limit: 200.0,
+            damage_hard_limit: 50.0,
+            eco_hard_limit: 10.0,
+        });
+        round_trip(&action("exclusion.seal_cracks"));
+        round_trip(&SimulationConfig {
+            w_pest: 0.5,
+            w_damage: 0.3,
+            w_eco: 0.2,
+            r_pest_max: 0.8,
+            r_damage_max: 0.7,
+            r_eco_max: 0.6,
+        });
+    }
+
+    #[test]
+    fn plans_round_trip() {
+        round_trip(&InterventionPlan {
+            actions: vec![action("exclusion.seal_cracks"), action("trap.snap")],
+            horizon_days: 60,
+        });
+        round_trip(&InterventionPlan {
+            actions: Vec::new(),
+            horizon_days: 30,
+        });
+    }
+
+    #[test]
+    fn trajectories_round_trip() {
+        round_trip(&state(5));
+        round_trip(&state(0));
+        round_trip(&SimulationResult {
+            state: state(4),
+            violated_hard_limit: true,
+        });
+        round_trip(&SimulationResult {
+            state: state(0),
+            violated_hard_limit: false,
+        });
+    }
+
+    #[test]
+    fn rejects_ragged_trajectories() {
+        let mut ragged = state(3);
+        ragged.r_eco.pop();
+        assert!(matches!(
+            to_csv(&ragged),
+            Err(ShardError::Unrepresentable(_))
+        ));
+        let result = SimulationResult {
+            state: ragged,
+            violated_hard_limit: false,
+        };
+        assert!(matches!(
+            to_csv(&result),
+            Err(ShardError::Unrepresentable(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_empty_violating_result() {
+        let result = SimulationResult {
+            state: state(0),
+            violated_hard_limit: true,
+        };
+        assert!(matches!(
+            to_csv(&result),
+            Err(ShardError::Unrepresentable(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_actions_without_method_id() {
+        let plan = InterventionPlan {
+            actions: vec![action("trap.snap"), action("")],
+            horizon_days: 30,
+        };
+        assert!(matches!(to_csv(&plan), Err(ShardError::Unrepresentable(_))));
+
+        // A hand-edited shard with a blank action among real ones is not an empty plan.
+        let csv = to_csv(&InterventionPlan {
+            actions: vec![action("trap.snap"), action("trap.live")],
+            horizon_days: 30,
+        })
+        .unwrap()
+        .replace("trap.live", "");
+        assert!(matches!(
+            from_csv::<InterventionPlan>(&csv),
+            Err(ShardError::InconsistentRows("method_id"))
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_shared_columns() {
+        let csv = to_csv(&SimulationResult {
+            state: state(2),
+            violated_hard_limit: true,
+        })
+        .unwrap();
+        let (head, last) = csv.trim_end().rsplit_once('\n').unwrap();
+        let tampered = format!("{head}\n{}\n", last.replace("true", "false"));
+        assert!(matches!(
+            from_csv::<SimulationResult>(&tampered),
+            Err(ShardError::InconsistentRows("violated_hard_limit"))
+        ));
+    }
+
+    #[test]
+    fn kernel_and_core_contexts_use_distinct_schemas() {
+        assert_ne!(
+            <PestContext as ShardSchema>::SCHEMA,
+            <deadbugs_core::model::PestContext as ShardSchema>::SCHEMA
+        );
+    }
+}